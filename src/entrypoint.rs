@@ -1,5 +1,23 @@
+use crate::tokenitis_instruction::accept_creator::AcceptCreator;
+use crate::tokenitis_instruction::cancel_pack::CancelPack;
+use crate::tokenitis_instruction::claim_buyout::ClaimBuyout;
+use crate::tokenitis_instruction::close_permit::ClosePermit;
+use crate::tokenitis_instruction::close_transform::CloseTransform;
 use crate::tokenitis_instruction::create_transform::CreateTransform;
+use crate::tokenitis_instruction::execute_route::ExecuteRoute;
 use crate::tokenitis_instruction::execute_transform::ExecuteTransform;
+use crate::tokenitis_instruction::initialize_tokenitis::InitializeTokenitis;
+use crate::tokenitis_instruction::place_bid::PlaceBid;
+use crate::tokenitis_instruction::propose_creator::ProposeCreator;
+use crate::tokenitis_instruction::rebalance::Rebalance;
+use crate::tokenitis_instruction::redeem_fractions::RedeemFractions;
+use crate::tokenitis_instruction::reveal_pack::RevealPack;
+use crate::tokenitis_instruction::set_protocol_fee::SetProtocolFee;
+use crate::tokenitis_instruction::set_transform_status::SetTransformStatus;
+use crate::tokenitis_instruction::update_tokenitis_config::UpdateTokenitisConfig;
+use crate::tokenitis_instruction::update_transform::UpdateTransform;
+use crate::tokenitis_instruction::withdraw_fees::WithdrawFees;
+use crate::tokenitis_instruction::withdraw_protocol_fees::WithdrawProtocolFees;
 use crate::tokenitis_instruction::TokenitisInstruction;
 use crate::tokenitis_instruction::TokenitisInstructionType;
 use borsh::BorshDeserialize;
@@ -8,7 +26,7 @@ use solana_program::{
 };
 
 entrypoint!(process_instruction);
-fn process_instruction<'a>(
+pub fn process_instruction<'a>(
     program_id: &Pubkey,
     accounts: &'a [AccountInfo<'a>],
     args: &[u8],
@@ -23,6 +41,52 @@ fn process_instruction<'a>(
         TokenitisInstructionType::ExecuteTransform(args) => {
             Box::new(ExecuteTransform::new(*program_id, accounts, args)?)
         }
+        TokenitisInstructionType::UpdateTransform(args) => {
+            Box::new(UpdateTransform::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::CloseTransform => {
+            Box::new(CloseTransform::new(*program_id, accounts)?)
+        }
+        TokenitisInstructionType::SetTransformStatus(args) => {
+            Box::new(SetTransformStatus::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::ProposeCreator(args) => {
+            Box::new(ProposeCreator::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::AcceptCreator => {
+            Box::new(AcceptCreator::new(*program_id, accounts)?)
+        }
+        TokenitisInstructionType::WithdrawFees => {
+            Box::new(WithdrawFees::new(*program_id, accounts)?)
+        }
+        TokenitisInstructionType::SetProtocolFee(args) => {
+            Box::new(SetProtocolFee::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::WithdrawProtocolFees(args) => {
+            Box::new(WithdrawProtocolFees::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::InitializeTokenitis(args) => {
+            Box::new(InitializeTokenitis::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::UpdateTokenitisConfig(args) => {
+            Box::new(UpdateTokenitisConfig::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::RevealPack => Box::new(RevealPack::new(*program_id, accounts)?),
+        TokenitisInstructionType::PlaceBid(args) => {
+            Box::new(PlaceBid::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::ClaimBuyout => Box::new(ClaimBuyout::new(*program_id, accounts)?),
+        TokenitisInstructionType::RedeemFractions(args) => {
+            Box::new(RedeemFractions::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::Rebalance(args) => {
+            Box::new(Rebalance::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::ExecuteRoute(args) => {
+            Box::new(ExecuteRoute::new(*program_id, accounts, args)?)
+        }
+        TokenitisInstructionType::ClosePermit => Box::new(ClosePermit::new(*program_id, accounts)?),
+        TokenitisInstructionType::CancelPack => Box::new(CancelPack::new(*program_id, accounts)?),
     };
 
     instruction.validate()?;
@@ -5,6 +5,98 @@ use solana_program::program_error::ProgramError;
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum TokenitisError {
     // 0
+    /// Transform is paused, or only allows reverse execution
+    TransformPaused,
+    // 1
+    /// Transform cannot be executed in this direction
+    DirectionDisabled,
+    // 2
+    /// Arithmetic overflowed or underflowed
+    ArithmeticOverflow,
+    // 3
+    /// Tokenitis is paused by the admin
+    TokenitisPaused,
+    // 4
+    /// Mint is on the tokenitis denylist
+    MintDenied,
+    // 5
+    /// Transform cannot be executed in this direction yet
+    TransformNotStarted,
+    // 6
+    /// Transform can no longer be executed in this direction
+    TransformEnded,
+    // 7
+    /// Transform has reached its execution limit
+    ExecutionLimitReached,
+    // 8
+    /// Wallet has reached its execution limit for the transform
+    WalletLimitReached,
+    // 9
+    /// Wallet has executed the transform too recently
+    CooldownActive,
+    // 10
+    /// Caller does not hold enough of the gate mint
+    GateNotMet,
+    // 11
+    /// Wallet is not on the allowlist of the transform
+    NotAllowlisted,
+    // 12
+    /// Wallet has claimed its allowlist allocation
+    AllocationExceeded,
+    // 13
+    /// Permit is missing or was not signed by the permit signer
+    InvalidPermit,
+    // 14
+    /// Permit has expired
+    PermitExpired,
+    // 15
+    /// Permit nonce has already been used
+    PermitUsed,
+    // 16
+    /// Wallet or transform has a pack that has not been revealed yet
+    PackPending,
+    // 17
+    /// Pack cannot be revealed in the slot it was committed in
+    PackNotRevealable,
+    // 18
+    /// Bid is below the reserve price or the highest bid
+    BidTooLow,
+    // 19
+    /// Buyout auction has ended
+    BuyoutEnded,
+    // 20
+    /// Buyout auction has not ended or has not been claimed yet
+    BuyoutNotEnded,
+    // 21
+    /// Input amounts changed by more than the per epoch limit
+    RebalanceLimitExceeded,
+    // 22
+    /// Rebalance drops the nav per unit by more than the tolerance
+    NavDropExceeded,
+    // 23
+    /// Route output is below the minimum
+    RouteOutputTooLow,
+    // 24
+    /// Mint is non transferable
+    MintNotTransferable,
+    // 25
+    /// Mint has a permanent delegate
+    MintHasPermanentDelegate,
+    // 26
+    /// Mint has a close authority
+    MintHasCloseAuthority,
+    // 27
+    /// Mint freezes new token accounts
+    MintDefaultFrozen,
+    // 28
+    /// Escrowed outputs cannot cover every pick of the pack
+    PackOutputsUnavailable,
+    // 29
+    /// Pack can only be cancelled once its slot hash has expired
+    PackNotExpired,
+    // 30
+    /// Fee vaults have to be withdrawn before the creator changes
+    FeesNotWithdrawn,
 }
 
 impl From<TokenitisError> for ProgramError {
@@ -18,4 +110,7 @@ impl<T> DecodeError<T> for TokenitisError {
     }
 }
 
-pub const MAX_STRING_SIZE: u64 = 300;
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 500;
+pub const MAX_FEE_RECIPIENTS: usize = 5;
+// bounds the compute of a reveal
+pub const MAX_PACK_PICKS: u64 = 32;
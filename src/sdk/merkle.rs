@@ -0,0 +1,58 @@
+use crate::tokenitis_instruction::execute_transform::AllowlistProof;
+use crate::util::merkle::{allowlist_leaf, hash_pair};
+use solana_program::pubkey::Pubkey;
+
+// merkle tree over (wallet, max_allocation) leaves, the root is stored on the transform
+// and every wallet executes forward with its own proof
+pub struct AllowlistTree {
+    entries: Vec<(Pubkey, u64)>,
+    // leaves first, root last, a node without a sibling is carried up unchanged
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl AllowlistTree {
+    pub fn new(entries: Vec<(Pubkey, u64)>) -> Self {
+        let leaves: Vec<[u8; 32]> = entries
+            .iter()
+            .map(|(wallet, max_allocation)| allowlist_leaf(wallet, *max_allocation))
+            .collect();
+
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        AllowlistTree { entries, levels }
+    }
+
+    pub fn root(&self) -> Option<[u8; 32]> {
+        self.levels.last().and_then(|level| level.first()).copied()
+    }
+
+    pub fn proof(&self, wallet: &Pubkey) -> Option<AllowlistProof> {
+        let mut index = self.entries.iter().position(|(w, _)| w == wallet)?;
+        let max_allocation = self.entries[index].1;
+
+        let mut proof: Vec<[u8; 32]> = Vec::new();
+        for level in self.levels.iter().take(self.levels.len() - 1) {
+            let sibling = index ^ 1;
+            if sibling < level.len() {
+                proof.push(level[sibling]);
+            }
+            index /= 2;
+        }
+
+        Some(AllowlistProof {
+            max_allocation,
+            proof,
+        })
+    }
+}
@@ -1,21 +1,80 @@
-use crate::state::{Token, Tokenitis};
+use crate::state::{Token, TokenMode, Tokenitis};
 use crate::tokenitis_instruction::create_transform::CreateTransformArgs;
-use crate::tokenitis_instruction::execute_transform::ExecuteTransformArgs;
+use crate::tokenitis_instruction::execute_route::{ExecuteRouteArgs, RouteHop};
+use crate::tokenitis_instruction::execute_transform::{ExecuteTransformArgs, Permit};
+use crate::tokenitis_instruction::initialize_tokenitis::InitializeTokenitisArgs;
+use crate::tokenitis_instruction::place_bid::PlaceBidArgs;
+use crate::tokenitis_instruction::propose_creator::ProposeCreatorArgs;
+use crate::tokenitis_instruction::rebalance::RebalanceArgs;
+use crate::tokenitis_instruction::redeem_fractions::RedeemFractionsArgs;
+use crate::tokenitis_instruction::set_protocol_fee::SetProtocolFeeArgs;
+use crate::tokenitis_instruction::set_transform_status::SetTransformStatusArgs;
+use crate::tokenitis_instruction::update_tokenitis_config::UpdateTokenitisConfigArgs;
+use crate::tokenitis_instruction::update_transform::UpdateTransformArgs;
+use crate::tokenitis_instruction::withdraw_protocol_fees::WithdrawProtocolFeesArgs;
 use crate::tokenitis_instruction::TokenitisInstructionType;
 
 use crate::Result;
 use borsh::BorshSerialize;
 
+use crate::util::token::{associated_token_address, is_native_mint, token_2022, token_instruction};
 use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_option::COption;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::system_instruction;
-use spl_token::instruction::{initialize_account, initialize_mint, mint_to_checked, AuthorityType};
+use spl_token::instruction::{AuthorityType, TokenInstruction};
 use spl_token::state::{Account, Mint};
 use std::collections::BTreeMap;
 
+pub mod merkle;
+
 pub struct InstructionBuilder;
 impl InstructionBuilder {
+    // payer has to be the upgrade authority of the program
+    pub fn initialize_tokenitis(
+        program_id: Pubkey,
+        payer: &Pubkey,
+        args: InitializeTokenitisArgs,
+    ) -> Result<Vec<Instruction>> {
+        let (tokenitis, _nonce) = Tokenitis::find_tokenitis_address(&program_id);
+        let (program_data, _nonce) = Pubkey::find_program_address(
+            &[program_id.as_ref()],
+            &solana_program::bpf_loader_upgradeable::id(),
+        );
+        let accounts = vec![
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new(tokenitis, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(program_data, false),
+        ];
+
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::InitializeTokenitis(args).try_to_vec()?,
+        }])
+    }
+
+    pub fn update_tokenitis_config(
+        program_id: Pubkey,
+        admin: &Pubkey,
+        args: UpdateTokenitisConfigArgs,
+    ) -> Result<Vec<Instruction>> {
+        let (tokenitis, _nonce) = Tokenitis::find_tokenitis_address(&program_id);
+        let accounts = vec![
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new(tokenitis, false),
+            AccountMeta::new(*admin, true),
+        ];
+
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::UpdateTokenitisConfig(args).try_to_vec()?,
+        }])
+    }
+
     pub fn create_transform_input_accounts(
         initializer: &Pubkey,
         spl_token_rent: u64,
@@ -23,8 +82,14 @@ impl InstructionBuilder {
     ) -> Result<Vec<Instruction>> {
         let mut instructions: Vec<Instruction> = Vec::new();
         for (mint, tok) in args.inputs.iter() {
+            // burnt inputs are never held by the transform
+            if tok.mode == TokenMode::Burn {
+                continue;
+            }
+
             let program_input_account = &tok.account;
             Self::create_spl_token_account(
+                &tok.token_program.id(),
                 mint,
                 program_input_account,
                 initializer,
@@ -37,25 +102,37 @@ impl InstructionBuilder {
         Ok(instructions)
     }
 
+    // creates an associated token account per input mint for every fee recipient to withdraw
+    // fees to, the transform creator is the only recipient if none are set
     pub fn create_transform_fee_accounts(
         funding_acc: &Pubkey,
         transform_creator: &Pubkey,
         args: CreateTransformArgs,
     ) -> Result<Vec<Instruction>> {
+        let mut recipients: Vec<Pubkey> = args.fee_recipients.iter().map(|r| r.owner).collect();
+        if recipients.is_empty() {
+            recipients.push(*transform_creator);
+        }
+
         let mut instructions: Vec<Instruction> = Vec::new();
-        args.inputs.iter().for_each(|(mint, _)| {
-            let ix = spl_associated_token_account::create_associated_token_account(
-                funding_acc,
-                transform_creator,
-                mint,
-            );
-            instructions.push(ix);
+        args.inputs.iter().for_each(|(mint, tok)| {
+            for recipient in recipients.iter() {
+                let ix = Self::create_associated_token_account(
+                    funding_acc,
+                    recipient,
+                    mint,
+                    &tok.token_program.id(),
+                );
+                instructions.push(ix);
+            }
         });
 
         Ok(instructions)
     }
 
-    // TODO add fixed supply
+    // output_supply is only required for outputs held in escrow, minted outputs
+    // are minted by the transform on demand. native sol outputs are funded with
+    // output_supply lamports of the initializer instead of a new mint
     pub fn create_transform_output_accounts(
         initializer: &Pubkey,
         spl_token_rent: u64,
@@ -65,39 +142,81 @@ impl InstructionBuilder {
     ) -> Result<Vec<Instruction>> {
         let mut instructions: Vec<Instruction> = Vec::new();
         for (mint, tok) in args.outputs.iter() {
-            let program_output_account = &tok.account;
-            Self::create_spl_token_mint(mint, initializer, None, 0, spl_mint_rent)?
+            let token_program = tok.token_program.id();
+            if is_native_mint(mint) {
+                Self::create_spl_token_account(
+                    &token_program,
+                    mint,
+                    &tok.account,
+                    initializer,
+                    spl_token_rent,
+                )?
                 .iter()
                 .for_each(|i| instructions.push(i.clone()));
-            Self::create_spl_token_account(
+                Self::wrap_sol(
+                    &token_program,
+                    initializer,
+                    &tok.account,
+                    *output_supply
+                        .get(mint)
+                        .ok_or(format!("could not get supply for mint - {}", mint.clone()))?,
+                )
+                .iter()
+                .for_each(|i| instructions.push(i.clone()));
+                continue;
+            }
+
+            Self::create_spl_token_mint(
+                &token_program,
                 mint,
-                program_output_account,
                 initializer,
-                spl_token_rent,
+                None,
+                tok.decimals,
+                spl_mint_rent,
             )?
             .iter()
             .for_each(|i| instructions.push(i.clone()));
-            let mint_entire_supply = mint_to_checked(
-                &spl_token::id(),
+            if tok.mode == TokenMode::Mint {
+                continue;
+            }
+
+            let program_output_account = &tok.account;
+            Self::create_spl_token_account(
+                &token_program,
                 mint,
                 program_output_account,
                 initializer,
-                &[initializer],
-                *output_supply
-                    .get(mint)
-                    .ok_or(format!("could not get supply for mint - {}", mint.clone()))?,
-                0,
-            )?;
-            let make_fixed_supply = spl_token::instruction::set_authority(
-                &spl_token::id(),
-                mint,
-                None,
-                AuthorityType::MintTokens,
-                initializer,
-                &[initializer],
-            )?;
+                spl_token_rent,
+            )?
+            .iter()
+            .for_each(|i| instructions.push(i.clone()));
+            let mint_entire_supply = token_instruction(
+                &token_program,
+                TokenInstruction::MintToChecked {
+                    amount: *output_supply
+                        .get(mint)
+                        .ok_or(format!("could not get supply for mint - {}", mint.clone()))?,
+                    decimals: tok.decimals,
+                },
+                vec![
+                    AccountMeta::new(*mint, false),
+                    AccountMeta::new(*program_output_account, false),
+                    AccountMeta::new_readonly(*initializer, true),
+                ],
+            );
+            let make_fixed_supply = token_instruction(
+                &token_program,
+                TokenInstruction::SetAuthority {
+                    authority_type: AuthorityType::MintTokens,
+                    new_authority: COption::None,
+                },
+                vec![
+                    AccountMeta::new(*mint, false),
+                    AccountMeta::new_readonly(*initializer, true),
+                ],
+            );
             instructions.push(mint_entire_supply);
-            // instructions.push(make_fixed_supply);
+            instructions.push(make_fixed_supply);
         }
 
         Ok(instructions)
@@ -115,6 +234,8 @@ impl InstructionBuilder {
         let accounts = vec![
             AccountMeta::new_readonly(solana_program::system_program::id(), false),
             AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
             AccountMeta::new(tokenitis, false),
             AccountMeta::new(transform, false),
             AccountMeta::new(*creator, true),
@@ -123,9 +244,18 @@ impl InstructionBuilder {
         input_args.sort();
         let mut input_mints: Vec<AccountMeta> = Vec::new();
         let mut inputs: Vec<AccountMeta> = Vec::new();
+        let mut treasuries: Vec<AccountMeta> = Vec::new();
+        let mut fee_vaults: Vec<AccountMeta> = Vec::new();
         for (mint, tok) in input_args {
             input_mints.push(AccountMeta::new_readonly(mint, false));
-            inputs.push(AccountMeta::new(tok.account, false))
+            inputs.push(AccountMeta::new(tok.account, false));
+            let (treasury, _nonce) = Tokenitis::find_treasury_address(&program_id, &mint);
+            treasuries.push(AccountMeta::new(treasury, false));
+            if args.has_fee() {
+                let (fee_vault, _nonce) =
+                    Tokenitis::find_fee_vault_address(&program_id, transform_num, &mint);
+                fee_vaults.push(AccountMeta::new(fee_vault, false))
+            }
         }
 
         let mut output_args: Vec<(Pubkey, Token)> = args.outputs.clone().into_iter().collect();
@@ -133,10 +263,20 @@ impl InstructionBuilder {
         let mut output_mints: Vec<AccountMeta> = Vec::new();
         let mut outputs: Vec<AccountMeta> = Vec::new();
         for (mint, tok) in output_args {
-            output_mints.push(AccountMeta::new_readonly(mint, false));
+            // minted outputs are the same account as their mint
+            output_mints.push(AccountMeta::new(mint, false));
             outputs.push(AccountMeta::new(tok.account, false))
         }
-        let accounts = vec![accounts, input_mints, inputs, output_mints, outputs].concat();
+        let accounts = vec![
+            accounts,
+            input_mints,
+            inputs,
+            output_mints,
+            outputs,
+            treasuries,
+            fee_vaults,
+        ]
+        .concat();
 
         Ok(vec![Instruction {
             program_id,
@@ -145,31 +285,102 @@ impl InstructionBuilder {
         }])
     }
 
+    // transfer hook accounts of the inputs and outputs have to be appended to the instruction.
+    // native_sol maps native mints to the lamports of the caller wrapped before executing,
+    // a caller account passed for the mint is reused, otherwise its associated account is
+    // created before and closed after executing, which unwraps the remaining and received sol
+    // back to the caller
     pub fn execute_transform(
         program_id: Pubkey,
         caller: &Pubkey,
         transform_state: crate::state::Transform,
         args: ExecuteTransformArgs,
-        user_inputs: BTreeMap<Pubkey, Pubkey>,
-        user_outputs: BTreeMap<Pubkey, Pubkey>,
+        mut user_inputs: BTreeMap<Pubkey, Pubkey>,
+        mut user_outputs: BTreeMap<Pubkey, Pubkey>,
+        native_sol: BTreeMap<Pubkey, u64>,
     ) -> Result<Vec<Instruction>> {
+        let mut instructions: Vec<Instruction> = Vec::new();
+        let mut unwrap_instructions: Vec<Instruction> = Vec::new();
+        for (mint, lamports) in native_sol.iter() {
+            if !is_native_mint(mint) {
+                return Err(format!("mint - {} is not a native mint", mint).into());
+            }
+            let tok = transform_state
+                .inputs
+                .get(mint)
+                .or_else(|| transform_state.outputs.get(mint))
+                .ok_or(format!("transform does not hold mint - {}", mint))?;
+            let token_program = tok.token_program.id();
+            let provided = user_inputs
+                .get(mint)
+                .or_else(|| user_outputs.get(mint))
+                .copied();
+            let caller_account = match provided {
+                Some(caller_account) => caller_account,
+                None => {
+                    let caller_account = associated_token_address(caller, mint, &token_program);
+                    instructions.push(Self::create_associated_token_account(
+                        caller,
+                        caller,
+                        mint,
+                        &token_program,
+                    ));
+                    unwrap_instructions.push(token_instruction(
+                        &token_program,
+                        TokenInstruction::CloseAccount,
+                        vec![
+                            AccountMeta::new(caller_account, false),
+                            AccountMeta::new(*caller, false),
+                            AccountMeta::new_readonly(*caller, true),
+                        ],
+                    ));
+                    caller_account
+                }
+            };
+            user_inputs.insert(*mint, caller_account);
+            user_outputs.insert(*mint, caller_account);
+
+            instructions.extend(Self::wrap_sol(
+                &token_program,
+                caller,
+                &caller_account,
+                *lamports,
+            ));
+        }
+
+        let (tokenitis, _nonce) = Tokenitis::find_tokenitis_address(&program_id);
         let (transform, _nonce) =
             Tokenitis::find_transform_address(&program_id, transform_state.id);
+        let (wallet, _nonce) =
+            Tokenitis::find_wallet_address(&program_id, transform_state.id, caller);
         let mut accounts = vec![
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
             AccountMeta::new_readonly(spl_token::ID, false),
-            AccountMeta::new_readonly(transform, false),
-            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new_readonly(tokenitis, false),
+            AccountMeta::new(transform, false),
+            AccountMeta::new(*caller, true),
+            AccountMeta::new(wallet, false),
         ];
 
+        let has_fee = transform_state.has_fee();
         let mut inputs = transform_state
             .inputs
             .into_iter()
             .collect::<Vec<(Pubkey, Token)>>();
         inputs.sort();
+        let mut input_mints: Vec<AccountMeta> = Vec::new();
         let mut caller_inputs: Vec<AccountMeta> = Vec::new();
         let mut program_inputs: Vec<AccountMeta> = Vec::new();
-        let mut fee_accounts: Vec<AccountMeta> = Vec::new();
+        let mut treasuries: Vec<AccountMeta> = Vec::new();
+        let mut fee_vaults: Vec<AccountMeta> = Vec::new();
         for (mint, tok) in inputs.iter() {
+            // burnt inputs change the supply of the mint
+            input_mints.push(AccountMeta {
+                pubkey: *mint,
+                is_signer: false,
+                is_writable: tok.mode == TokenMode::Burn,
+            });
             caller_inputs.push(AccountMeta::new(
                 *user_inputs.get(mint).ok_or(format!(
                     "could not find caller token account for mint - {}",
@@ -178,24 +389,42 @@ impl InstructionBuilder {
                 false,
             ));
             program_inputs.push(AccountMeta::new(tok.account, false));
+            let (treasury, _nonce) = Tokenitis::find_treasury_address(&program_id, mint);
+            treasuries.push(AccountMeta::new(treasury, false));
 
-            if transform_state.fee.is_some() {
-                let fee_account = spl_associated_token_account::get_associated_token_address(
-                    &transform_state.creator,
-                    mint,
-                );
-                fee_accounts.push(AccountMeta::new(fee_account, false))
+            if has_fee {
+                let (fee_vault, _nonce) =
+                    Tokenitis::find_fee_vault_address(&program_id, transform_state.id, mint);
+                fee_vaults.push(AccountMeta::new(fee_vault, false))
             }
         }
 
-        let mut outputs = transform_state
-            .outputs
-            .into_iter()
-            .collect::<Vec<(Pubkey, Token)>>();
+        // outputs of pack transforms are paid out on reveal, their escrows are only read
+        let pack_outputs: Vec<AccountMeta> = match transform_state.pack {
+            Some(_) => transform_state
+                .outputs
+                .values()
+                .map(|tok| AccountMeta::new_readonly(tok.account, false))
+                .collect(),
+            None => vec![],
+        };
+        let mut outputs = match transform_state.pack {
+            Some(_) => vec![],
+            None => transform_state
+                .outputs
+                .into_iter()
+                .collect::<Vec<(Pubkey, Token)>>(),
+        };
         outputs.sort();
+        let mut output_mints: Vec<AccountMeta> = Vec::new();
         let mut caller_outputs: Vec<AccountMeta> = Vec::new();
         let mut program_outputs: Vec<AccountMeta> = Vec::new();
         for (mint, tok) in outputs.iter() {
+            output_mints.push(AccountMeta {
+                pubkey: *mint,
+                is_signer: false,
+                is_writable: tok.mode == TokenMode::Mint,
+            });
             caller_outputs.push(AccountMeta::new(
                 *user_outputs.get(mint).ok_or(format!(
                     "could not find caller token account for mint - {}",
@@ -207,27 +436,732 @@ impl InstructionBuilder {
         }
 
         for acc in vec![
+            input_mints,
             caller_inputs,
             program_inputs,
+            output_mints,
             caller_outputs,
             program_outputs,
-            fee_accounts,
+            treasuries,
+            fee_vaults,
         ]
         .concat()
         {
             accounts.push(acc)
         }
 
-        let instructions = vec![Instruction {
+        // gate mint is expected to be held in the associated token account of the caller
+        if let Some(gate) = transform_state.gate {
+            let gate_account =
+                associated_token_address(caller, &gate.mint, &gate.token_program.id());
+            accounts.push(AccountMeta::new_readonly(gate_account, false))
+        }
+
+        // permit is verified by an ed25519 instruction, see permit_instruction
+        if transform_state.permit_signer.is_some() {
+            let permit = args
+                .permit
+                .as_ref()
+                .ok_or("transform requires a permit to execute")?;
+            let (permit_nonce, _nonce) =
+                Tokenitis::find_permit_address(&program_id, transform_state.id, permit.nonce);
+            accounts.push(AccountMeta::new_readonly(
+                solana_program::sysvar::instructions::id(),
+                false,
+            ));
+            accounts.push(AccountMeta::new(permit_nonce, false));
+        }
+
+        if transform_state.pack.is_some() {
+            let (pack_commitment, _nonce) =
+                Tokenitis::find_pack_address(&program_id, transform_state.id, caller);
+            accounts.push(AccountMeta::new(pack_commitment, false));
+            accounts.extend(pack_outputs);
+        }
+
+        instructions.push(Instruction {
             program_id,
             accounts,
             data: TokenitisInstructionType::ExecuteTransform(args).try_to_vec()?,
+        });
+        instructions.extend(unwrap_instructions);
+
+        Ok(instructions)
+    }
+
+    // reveals the pack committed to by caller, has to be sent in a later slot than the execution,
+    // expired packs pay out the fallback output
+    pub fn reveal_pack(
+        program_id: Pubkey,
+        revealer: &Pubkey,
+        caller: &Pubkey,
+        transform_state: crate::state::Transform,
+        caller_outputs: BTreeMap<Pubkey, Pubkey>,
+    ) -> Result<Vec<Instruction>> {
+        let (transform, _nonce) =
+            Tokenitis::find_transform_address(&program_id, transform_state.id);
+        let (pack_commitment, _nonce) =
+            Tokenitis::find_pack_address(&program_id, transform_state.id, caller);
+        let mut accounts = vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new_readonly(solana_program::sysvar::slot_hashes::id(), false),
+            AccountMeta::new(transform, false),
+            AccountMeta::new(pack_commitment, false),
+            AccountMeta::new(*caller, false),
+            AccountMeta::new_readonly(*revealer, true),
+        ];
+
+        let mut output_mints: Vec<AccountMeta> = Vec::new();
+        let mut caller_output_accounts: Vec<AccountMeta> = Vec::new();
+        let mut program_outputs: Vec<AccountMeta> = Vec::new();
+        for (mint, tok) in transform_state.outputs.iter() {
+            output_mints.push(AccountMeta {
+                pubkey: *mint,
+                is_signer: false,
+                is_writable: tok.mode == TokenMode::Mint,
+            });
+            caller_output_accounts.push(AccountMeta::new(
+                *caller_outputs.get(mint).ok_or(format!(
+                    "could not find caller token account for mint - {}",
+                    mint
+                ))?,
+                false,
+            ));
+            program_outputs.push(AccountMeta::new(tok.account, false))
+        }
+        accounts.extend(output_mints);
+        accounts.extend(caller_output_accounts);
+        accounts.extend(program_outputs);
+
+        let instructions = vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::RevealPack.try_to_vec()?,
+        }];
+
+        Ok(instructions)
+    }
+
+    // creator returns the escrowed inputs of an expired pack to the caller
+    pub fn cancel_pack(
+        program_id: Pubkey,
+        creator: &Pubkey,
+        caller: &Pubkey,
+        transform_state: crate::state::Transform,
+        caller_inputs: BTreeMap<Pubkey, Pubkey>,
+    ) -> Result<Vec<Instruction>> {
+        let (transform, _nonce) =
+            Tokenitis::find_transform_address(&program_id, transform_state.id);
+        let (pack_commitment, _nonce) =
+            Tokenitis::find_pack_address(&program_id, transform_state.id, caller);
+        let mut accounts = vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new(transform, false),
+            AccountMeta::new(pack_commitment, false),
+            AccountMeta::new(*caller, false),
+            AccountMeta::new_readonly(*creator, true),
+        ];
+
+        let mut input_mints: Vec<AccountMeta> = Vec::new();
+        let mut caller_input_accounts: Vec<AccountMeta> = Vec::new();
+        let mut program_inputs: Vec<AccountMeta> = Vec::new();
+        for (mint, tok) in transform_state.inputs.iter() {
+            input_mints.push(AccountMeta::new_readonly(*mint, false));
+            caller_input_accounts.push(AccountMeta::new(
+                *caller_inputs.get(mint).ok_or(format!(
+                    "could not find caller token account for mint - {}",
+                    mint
+                ))?,
+                false,
+            ));
+            program_inputs.push(AccountMeta::new(tok.account, false))
+        }
+        accounts.extend(input_mints);
+        accounts.extend(caller_input_accounts);
+        accounts.extend(program_inputs);
+
+        let instructions = vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::CancelPack.try_to_vec()?,
+        }];
+
+        Ok(instructions)
+    }
+
+    // previous bidder quote account is refunded, it is required for every bid after the first
+    pub fn place_bid(
+        program_id: Pubkey,
+        bidder: &Pubkey,
+        transform_state: crate::state::Transform,
+        bidder_quote: &Pubkey,
+        previous_bidder_quote: Option<&Pubkey>,
+        args: PlaceBidArgs,
+    ) -> Result<Vec<Instruction>> {
+        let buyout_config = transform_state
+            .buyout
+            .ok_or("transform cannot be bought out")?;
+        let input = transform_state
+            .inputs
+            .values()
+            .next()
+            .ok_or("transform has no input")?;
+        let (transform, _nonce) =
+            Tokenitis::find_transform_address(&program_id, transform_state.id);
+        let (buyout, _nonce) = Tokenitis::find_buyout_address(&program_id, transform_state.id);
+        let (buyout_vault, _nonce) =
+            Tokenitis::find_buyout_vault_address(&program_id, transform_state.id);
+        let mut accounts = vec![
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            AccountMeta::new(transform, false),
+            AccountMeta::new(buyout, false),
+            AccountMeta::new(buyout_vault, false),
+            AccountMeta::new_readonly(buyout_config.quote_mint, false),
+            AccountMeta::new_readonly(input.account, false),
+            AccountMeta::new(*bidder, true),
+            AccountMeta::new(*bidder_quote, false),
+        ];
+        if let Some(previous_bidder_quote) = previous_bidder_quote {
+            accounts.push(AccountMeta::new(*previous_bidder_quote, false));
+        }
+
+        let instructions = vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::PlaceBid(args).try_to_vec()?,
+        }];
+
+        Ok(instructions)
+    }
+
+    // input is sent to the associated token account of the highest bidder
+    pub fn claim_buyout(
+        program_id: Pubkey,
+        bidder: &Pubkey,
+        transform_state: crate::state::Transform,
+    ) -> Result<Vec<Instruction>> {
+        let (input_mint, input) = transform_state
+            .inputs
+            .iter()
+            .next()
+            .ok_or("transform has no input")?;
+        let (fraction_mint, fractions) = transform_state
+            .outputs
+            .iter()
+            .next()
+            .ok_or("transform has no output")?;
+        let (transform, _nonce) =
+            Tokenitis::find_transform_address(&program_id, transform_state.id);
+        let (buyout, _nonce) = Tokenitis::find_buyout_address(&program_id, transform_state.id);
+        let mut accounts = vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new_readonly(transform, false),
+            AccountMeta::new(buyout, false),
+            AccountMeta::new_readonly(*input_mint, false),
+            AccountMeta::new(input.account, false),
+            AccountMeta::new(
+                associated_token_address(bidder, input_mint, &input.token_program.id()),
+                false,
+            ),
+            AccountMeta::new_readonly(*fraction_mint, false),
+        ];
+        if fractions.mode == TokenMode::Escrow {
+            accounts.push(AccountMeta::new_readonly(fractions.account, false));
+        }
+
+        let instructions = vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::ClaimBuyout.try_to_vec()?,
+        }];
+
+        Ok(instructions)
+    }
+
+    pub fn redeem_fractions(
+        program_id: Pubkey,
+        holder: &Pubkey,
+        transform_state: crate::state::Transform,
+        holder_fractions: &Pubkey,
+        holder_quote: &Pubkey,
+        args: RedeemFractionsArgs,
+    ) -> Result<Vec<Instruction>> {
+        let buyout_config = transform_state
+            .buyout
+            .ok_or("transform cannot be bought out")?;
+        let fraction_mint = transform_state
+            .outputs
+            .keys()
+            .next()
+            .ok_or("transform has no output")?;
+        let (transform, _nonce) =
+            Tokenitis::find_transform_address(&program_id, transform_state.id);
+        let (buyout, _nonce) = Tokenitis::find_buyout_address(&program_id, transform_state.id);
+        let (buyout_vault, _nonce) =
+            Tokenitis::find_buyout_vault_address(&program_id, transform_state.id);
+        let accounts = vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new_readonly(transform, false),
+            AccountMeta::new(buyout, false),
+            AccountMeta::new(buyout_vault, false),
+            AccountMeta::new_readonly(buyout_config.quote_mint, false),
+            AccountMeta::new(*fraction_mint, false),
+            AccountMeta::new_readonly(*holder, true),
+            AccountMeta::new(*holder_fractions, false),
+            AccountMeta::new(*holder_quote, false),
+        ];
+
+        let instructions = vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::RedeemFractions(args).try_to_vec()?,
+        }];
+
+        Ok(instructions)
+    }
+
+    // counterparty and price authority have to sign as well,
+    // counterparty settles the difference of every input amount
+    pub fn rebalance(
+        program_id: Pubkey,
+        rebalancer: &Pubkey,
+        counterparty: &Pubkey,
+        transform_state: crate::state::Transform,
+        counterparty_inputs: BTreeMap<Pubkey, Pubkey>,
+        args: RebalanceArgs,
+    ) -> Result<Vec<Instruction>> {
+        let (transform, _nonce) =
+            Tokenitis::find_transform_address(&program_id, transform_state.id);
+        let index = transform_state
+            .index
+            .as_ref()
+            .ok_or("transform is not an index")?;
+        let mut accounts = vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new(transform, false),
+            AccountMeta::new_readonly(*rebalancer, true),
+            AccountMeta::new_readonly(*counterparty, true),
+            AccountMeta::new_readonly(index.config.price_authority, true),
+        ];
+
+        let mut input_mints: Vec<AccountMeta> = Vec::new();
+        let mut program_inputs: Vec<AccountMeta> = Vec::new();
+        let mut counterparty_accounts: Vec<AccountMeta> = Vec::new();
+        for (mint, tok) in transform_state.inputs.iter() {
+            input_mints.push(AccountMeta::new_readonly(*mint, false));
+            program_inputs.push(AccountMeta::new(tok.account, false));
+            counterparty_accounts.push(AccountMeta::new(
+                *counterparty_inputs.get(mint).ok_or(format!(
+                    "could not find counterparty token account for mint - {}",
+                    mint
+                ))?,
+                false,
+            ));
+        }
+        accounts.extend(input_mints);
+        accounts.extend(program_inputs);
+        accounts.extend(counterparty_accounts);
+
+        let instructions = vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::Rebalance(args).try_to_vec()?,
         }];
 
         Ok(instructions)
     }
 
+    // message the permit signer has to sign for the caller to execute with the given args
+    pub fn permit_message(
+        transform_id: u64,
+        caller: &Pubkey,
+        args: &ExecuteTransformArgs,
+    ) -> Result<Vec<u8>> {
+        let permit =
+            Permit::new(transform_id, *caller, args).ok_or("args do not contain a permit")?;
+        Ok(permit.try_to_vec()?)
+    }
+
+    // ed25519 program instruction verifying the permit signature,
+    // has to be placed right before the execute transform instruction
+    pub fn permit_instruction(
+        signer: &Pubkey,
+        signature: &[u8; 64],
+        message: &[u8],
+    ) -> Instruction {
+        const DATA_START: usize = 16;
+        let public_key_offset = DATA_START;
+        let signature_offset = public_key_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = vec![1u8, 0];
+        for value in [
+            signature_offset,
+            u16::MAX as usize,
+            public_key_offset,
+            u16::MAX as usize,
+            message_offset,
+            message.len(),
+            u16::MAX as usize,
+        ] {
+            data.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(signature);
+        data.extend_from_slice(message);
+
+        Instruction {
+            program_id: solana_program::ed25519_program::id(),
+            accounts: vec![],
+            data,
+        }
+    }
+
+    // returns the rent of an expired permit nonce to the caller that used the permit
+    pub fn close_permit(
+        program_id: Pubkey,
+        caller: &Pubkey,
+        transform_num: u64,
+        nonce: u64,
+    ) -> Result<Vec<Instruction>> {
+        let (permit_nonce, _nonce) =
+            Tokenitis::find_permit_address(&program_id, transform_num, nonce);
+        let accounts = vec![
+            AccountMeta::new(permit_nonce, false),
+            AccountMeta::new(*caller, false),
+        ];
+
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::ClosePermit.try_to_vec()?,
+        }])
+    }
+
+    // caller token accounts are shared between hops, so the output of a hop
+    // is the input of the next one, transfer hook accounts of every hop are appended after output
+    pub fn execute_route(
+        program_id: Pubkey,
+        caller: &Pubkey,
+        hops: Vec<(crate::state::Transform, RouteHop)>,
+        caller_accounts: BTreeMap<Pubkey, Pubkey>,
+        output: &Pubkey,
+        min_output: u64,
+    ) -> Result<Vec<Instruction>> {
+        let mut accounts: Vec<AccountMeta> = Vec::new();
+        let mut route_hops: Vec<RouteHop> = Vec::new();
+        for (transform_state, hop) in hops.into_iter() {
+            let hop_instructions = Self::execute_transform(
+                program_id,
+                caller,
+                transform_state,
+                hop.args(),
+                caller_accounts.clone(),
+                caller_accounts.clone(),
+                BTreeMap::new(),
+            )?;
+            for instruction in hop_instructions {
+                accounts.extend(instruction.accounts);
+            }
+            route_hops.push(hop);
+        }
+        accounts.push(AccountMeta::new_readonly(*output, false));
+
+        let instructions = vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::ExecuteRoute(ExecuteRouteArgs {
+                hops: route_hops,
+                min_output,
+            })
+            .try_to_vec()?,
+        }];
+
+        Ok(instructions)
+    }
+
+    pub fn update_transform(
+        program_id: Pubkey,
+        creator: &Pubkey,
+        transform_num: u64,
+        args: UpdateTransformArgs,
+    ) -> Result<Vec<Instruction>> {
+        let (tokenitis, _nonce) = Tokenitis::find_tokenitis_address(&program_id);
+        let (transform, _nonce) = Tokenitis::find_transform_address(&program_id, transform_num);
+        let accounts = vec![
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(tokenitis, false),
+            AccountMeta::new(transform, false),
+            AccountMeta::new(*creator, true),
+        ];
+
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::UpdateTransform(args).try_to_vec()?,
+        }])
+    }
+
+    pub fn set_transform_status(
+        program_id: Pubkey,
+        creator: &Pubkey,
+        transform_num: u64,
+        args: SetTransformStatusArgs,
+    ) -> Result<Vec<Instruction>> {
+        let (transform, _nonce) = Tokenitis::find_transform_address(&program_id, transform_num);
+        let accounts = vec![
+            AccountMeta::new(transform, false),
+            AccountMeta::new_readonly(*creator, true),
+        ];
+
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::SetTransformStatus(args).try_to_vec()?,
+        }])
+    }
+
+    pub fn propose_creator(
+        program_id: Pubkey,
+        creator: &Pubkey,
+        transform_num: u64,
+        args: ProposeCreatorArgs,
+    ) -> Result<Vec<Instruction>> {
+        let (transform, _nonce) = Tokenitis::find_transform_address(&program_id, transform_num);
+        let accounts = vec![
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new(transform, false),
+            AccountMeta::new(*creator, true),
+        ];
+
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::ProposeCreator(args).try_to_vec()?,
+        }])
+    }
+
+    // fee vaults have to be withdrawn by the previous creator first
+    pub fn accept_creator(
+        program_id: Pubkey,
+        new_creator: &Pubkey,
+        transform_state: crate::state::Transform,
+    ) -> Result<Vec<Instruction>> {
+        let (transform, _nonce) =
+            Tokenitis::find_transform_address(&program_id, transform_state.id);
+        let mut accounts = vec![
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new(transform, false),
+            AccountMeta::new(*new_creator, true),
+        ];
+        if transform_state.has_fee() {
+            for mint in transform_state.inputs.keys() {
+                let (fee_vault, _nonce) =
+                    Tokenitis::find_fee_vault_address(&program_id, transform_state.id, mint);
+                accounts.push(AccountMeta::new_readonly(fee_vault, false));
+            }
+        }
+
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::AcceptCreator.try_to_vec()?,
+        }])
+    }
+
+    // creator accounts are only required for tokens held in escrow
+    pub fn close_transform(
+        program_id: Pubkey,
+        creator: &Pubkey,
+        transform_state: crate::state::Transform,
+        creator_inputs: BTreeMap<Pubkey, Pubkey>,
+        creator_outputs: BTreeMap<Pubkey, Pubkey>,
+    ) -> Result<Vec<Instruction>> {
+        let (transform, _nonce) =
+            Tokenitis::find_transform_address(&program_id, transform_state.id);
+        let mut accounts = vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new(transform, false),
+            AccountMeta::new(*creator, true),
+        ];
+
+        // withheld transfer fees are harvested to the mints before escrow accounts are closed
+        let mut input_mints: Vec<AccountMeta> = Vec::new();
+        let mut program_inputs: Vec<AccountMeta> = Vec::new();
+        let mut creator_input_accounts: Vec<AccountMeta> = Vec::new();
+        for (mint, tok) in transform_state.inputs.iter() {
+            input_mints.push(AccountMeta::new(*mint, false));
+            if tok.mode != TokenMode::Escrow {
+                continue;
+            }
+            program_inputs.push(AccountMeta::new(tok.account, false));
+            creator_input_accounts.push(AccountMeta::new(
+                *creator_inputs.get(mint).ok_or(format!(
+                    "could not find creator token account for mint - {}",
+                    mint.clone()
+                ))?,
+                false,
+            ));
+        }
+
+        let mut output_mints: Vec<AccountMeta> = Vec::new();
+        let mut program_outputs: Vec<AccountMeta> = Vec::new();
+        let mut creator_output_accounts: Vec<AccountMeta> = Vec::new();
+        for (mint, tok) in transform_state.outputs.iter() {
+            output_mints.push(AccountMeta::new(*mint, false));
+            if tok.mode != TokenMode::Escrow {
+                continue;
+            }
+            program_outputs.push(AccountMeta::new(tok.account, false));
+            creator_output_accounts.push(AccountMeta::new(
+                *creator_outputs.get(mint).ok_or(format!(
+                    "could not find creator token account for mint - {}",
+                    mint.clone()
+                ))?,
+                false,
+            ));
+        }
+
+        let mut fee_vaults: Vec<AccountMeta> = Vec::new();
+        if transform_state.has_fee() {
+            for mint in transform_state.inputs.keys() {
+                let (fee_vault, _nonce) =
+                    Tokenitis::find_fee_vault_address(&program_id, transform_state.id, mint);
+                fee_vaults.push(AccountMeta::new(fee_vault, false))
+            }
+        }
+
+        for acc in vec![
+            input_mints,
+            program_inputs,
+            creator_input_accounts,
+            output_mints,
+            program_outputs,
+            creator_output_accounts,
+            fee_vaults,
+        ]
+        .concat()
+        {
+            accounts.push(acc)
+        }
+
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::CloseTransform.try_to_vec()?,
+        }])
+    }
+
+    // fees are sent to the associated token accounts of the fee recipients
+    pub fn withdraw_fees(
+        program_id: Pubkey,
+        creator: &Pubkey,
+        transform_state: crate::state::Transform,
+    ) -> Result<Vec<Instruction>> {
+        let (transform, _nonce) =
+            Tokenitis::find_transform_address(&program_id, transform_state.id);
+        let mut accounts = vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new_readonly(transform, false),
+            AccountMeta::new_readonly(*creator, true),
+        ];
+
+        let fee_recipients = transform_state.fee_splits();
+        let mut input_mints: Vec<AccountMeta> = Vec::new();
+        let mut fee_vaults: Vec<AccountMeta> = Vec::new();
+        let mut recipient_accounts: Vec<AccountMeta> = Vec::new();
+        for (mint, tok) in transform_state.inputs.iter() {
+            input_mints.push(AccountMeta::new_readonly(*mint, false));
+            let (fee_vault, _nonce) =
+                Tokenitis::find_fee_vault_address(&program_id, transform_state.id, mint);
+            fee_vaults.push(AccountMeta::new(fee_vault, false));
+            for recipient in fee_recipients.iter() {
+                let recipient_account =
+                    associated_token_address(&recipient.owner, mint, &tok.token_program.id());
+                recipient_accounts.push(AccountMeta::new(recipient_account, false))
+            }
+        }
+
+        for acc in vec![input_mints, fee_vaults, recipient_accounts].concat() {
+            accounts.push(acc)
+        }
+
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::WithdrawFees.try_to_vec()?,
+        }])
+    }
+
+    pub fn set_protocol_fee(
+        program_id: Pubkey,
+        admin: &Pubkey,
+        args: SetProtocolFeeArgs,
+    ) -> Result<Vec<Instruction>> {
+        let (tokenitis, _nonce) = Tokenitis::find_tokenitis_address(&program_id);
+        let accounts = vec![
+            AccountMeta::new(tokenitis, false),
+            AccountMeta::new_readonly(*admin, true),
+        ];
+
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::SetProtocolFee(args).try_to_vec()?,
+        }])
+    }
+
+    // destinations maps every mint to withdraw to the token account receiving it
+    pub fn withdraw_protocol_fees(
+        program_id: Pubkey,
+        admin: &Pubkey,
+        destinations: BTreeMap<Pubkey, Pubkey>,
+    ) -> Result<Vec<Instruction>> {
+        let (tokenitis, _nonce) = Tokenitis::find_tokenitis_address(&program_id);
+        let mut accounts = vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_2022::ID, false),
+            AccountMeta::new_readonly(tokenitis, false),
+            AccountMeta::new_readonly(*admin, true),
+        ];
+
+        let mut mints: Vec<AccountMeta> = Vec::new();
+        let mut treasuries: Vec<AccountMeta> = Vec::new();
+        let mut destination_accounts: Vec<AccountMeta> = Vec::new();
+        for (mint, destination) in destinations.iter() {
+            mints.push(AccountMeta::new_readonly(*mint, false));
+            let (treasury, _nonce) = Tokenitis::find_treasury_address(&program_id, mint);
+            treasuries.push(AccountMeta::new(treasury, false));
+            destination_accounts.push(AccountMeta::new(*destination, false));
+        }
+
+        for acc in vec![mints, treasuries, destination_accounts].concat() {
+            accounts.push(acc)
+        }
+
+        let args = WithdrawProtocolFeesArgs {
+            mints: destinations.into_keys().collect(),
+        };
+        Ok(vec![Instruction {
+            program_id,
+            accounts,
+            data: TokenitisInstructionType::WithdrawProtocolFees(args).try_to_vec()?,
+        }])
+    }
+
+    // token 2022 mints and accounts are created without extensions
     pub fn create_spl_token_mint(
+        token_program: &Pubkey,
         mint: &Pubkey,
         mint_authority: &Pubkey,
         freeze_authority: Option<&Pubkey>,
@@ -240,19 +1174,27 @@ impl InstructionBuilder {
                 mint,
                 spl_mint_rent,
                 Mint::LEN as u64,
-                &spl_token::ID,
+                token_program,
+            ),
+            token_instruction(
+                token_program,
+                TokenInstruction::InitializeMint {
+                    mint_authority: *mint_authority,
+                    freeze_authority: freeze_authority.cloned().into(),
+                    decimals,
+                },
+                vec![
+                    AccountMeta::new(*mint, false),
+                    AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+                ],
             ),
-            initialize_mint(
-                &spl_token::ID,
-                mint,
-                mint_authority,
-                freeze_authority,
-                decimals,
-            )?,
         ];
         Ok(instructions)
     }
+
+    // token 2022 mints with a transfer fee or transfer hook need larger accounts
     pub fn create_spl_token_account(
+        token_program: &Pubkey,
         mint: &Pubkey,
         token_account: &Pubkey,
         authority: &Pubkey,
@@ -264,10 +1206,63 @@ impl InstructionBuilder {
                 token_account,
                 rent,
                 Account::LEN as u64,
-                &spl_token::ID,
+                token_program,
+            ),
+            token_instruction(
+                token_program,
+                TokenInstruction::InitializeAccount,
+                vec![
+                    AccountMeta::new(*token_account, false),
+                    AccountMeta::new_readonly(*mint, false),
+                    AccountMeta::new_readonly(*authority, false),
+                    AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+                ],
             ),
-            initialize_account(&spl_token::ID, token_account, mint, authority)?,
         ];
         Ok(instructions)
     }
+
+    // idempotent, does nothing if the account already exists
+    pub fn create_associated_token_account(
+        funding_account: &Pubkey,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Instruction {
+        Instruction {
+            program_id: spl_associated_token_account::id(),
+            accounts: vec![
+                AccountMeta::new(*funding_account, true),
+                AccountMeta::new(associated_token_address(wallet, mint, token_program), false),
+                AccountMeta::new_readonly(*wallet, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(solana_program::system_program::id(), false),
+                AccountMeta::new_readonly(*token_program, false),
+                AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+            ],
+            data: vec![1],
+        }
+    }
+
+    // moves lamports into a native token account and syncs its amount, the rent exempt
+    // reserve of the account is not counted in the amount
+    pub fn wrap_sol(
+        token_program: &Pubkey,
+        funding_account: &Pubkey,
+        token_account: &Pubkey,
+        lamports: u64,
+    ) -> Vec<Instruction> {
+        if lamports == 0 {
+            return vec![];
+        }
+
+        vec![
+            system_instruction::transfer(funding_account, token_account, lamports),
+            token_instruction(
+                token_program,
+                TokenInstruction::SyncNative,
+                vec![AccountMeta::new(*token_account, false)],
+            ),
+        ]
+    }
 }
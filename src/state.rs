@@ -1,6 +1,10 @@
+use crate::errors::TokenitisError;
 use crate::tokenitis_instruction::create_transform::CreateTransformArgs;
+use crate::tokenitis_instruction::execute_transform::Direction;
+use crate::util::BPS_DENOMINATOR;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use std::collections::BTreeMap;
@@ -8,10 +12,54 @@ use std::collections::BTreeMap;
 // pda seed for the account that stores global state
 const TOKENITIS_PDA: &[u8] = b"tokenitis";
 const TRANSFORM_PREFIX: &str = "transform";
+const FEE_VAULT_PREFIX: &str = "fee-vault";
+const TREASURY_PDA: &[u8] = b"treasury";
+const WALLET_PREFIX: &str = "wallet";
+const PERMIT_PREFIX: &str = "permit";
+const PACK_PREFIX: &str = "pack";
+const BUYOUT_PREFIX: &str = "buyout";
+const BUYOUT_VAULT_PREFIX: &str = "buyout-vault";
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 pub struct Tokenitis {
     pub num_transforms: u64,
+    // can update the config and set and withdraw the protocol fee
+    pub admin: Pubkey,
+    // charged on every execution on top of the transform fee, paid into the treasury
+    pub protocol_fee_bps: u16,
+    pub config: TokenitisConfig,
+}
+
+// tokenitis accounts created by create transform before initialize tokenitis existed
+// only held the transform count
+const LEGACY_TOKENITIS_LEN: usize = 8;
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct TokenitisConfig {
+    pub max_string_size: u64,
+    // range of the transform fee in basis points
+    pub min_fee_bps: u16,
+    pub max_fee_bps: u16,
+    pub max_inputs: u64,
+    pub max_outputs: u64,
+    // transforms cannot be created or executed while paused
+    pub paused: bool,
+    // mints that cannot be used as an input or output
+    pub denied_mints: Vec<Pubkey>,
+}
+
+impl Default for TokenitisConfig {
+    fn default() -> Self {
+        TokenitisConfig {
+            max_string_size: 300,
+            min_fee_bps: 1,
+            max_fee_bps: 1_000,
+            max_inputs: 8,
+            max_outputs: 8,
+            paused: false,
+            denied_mints: vec![],
+        }
+    }
 }
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
@@ -19,13 +67,100 @@ pub struct Transform {
     pub initialized: bool,
     pub id: u64,
     pub creator: Pubkey,
+    // set by the creator, becomes the creator once it accepts
+    pub pending_creator: Option<Pubkey>,
     pub metadata: TransformMetadata,
-    pub fee: Option<u64>,
+    pub forward: DirectionPolicy,
+    pub reverse: DirectionPolicy,
+    // creator receives all fees if no recipients are set
+    pub fee_recipients: Vec<FeeRecipient>,
+    // total fees collected per input mint since the transform was created
+    pub fees_collected: BTreeMap<Pubkey, u64>,
     pub inputs: BTreeMap<Pubkey, Token>,
     pub outputs: BTreeMap<Pubkey, Token>,
+    pub close_policy: ClosePolicy,
+    pub status: TransformStatus,
+    pub limits: ExecutionLimits,
+    // only holders of the gate mint can execute forward
+    pub gate: Option<Gate>,
+    // merkle root of the wallets allowed to execute forward and their max allocation
+    pub allowlist: Option<[u8; 32]>,
+    // every execution needs a permit signed off-chain by this key
+    pub permit_signer: Option<PermitSigner>,
+    // outputs are picked at random on reveal instead of all being paid out on execute
+    pub pack: Option<PackConfig>,
+    // anyone can bid for the input of a fractionalized transform
+    pub buyout: Option<BuyoutConfig>,
+    // input amounts can be rebalanced after creation
+    pub index: Option<IndexState>,
+    // units executed forward since the transform was created
+    pub total_executions: u64,
+    // packs committed to that have not been revealed yet, their outputs are still owed
+    pub pending_packs: u64,
+    // escrowed outputs held back for the worst case picks of the pending packs
+    pub reserved_outputs: BTreeMap<Pubkey, u64>,
+    // outputs paid out minus outputs returned per output mint, tokens of the mint that
+    // never left the transform are not counted
+    pub outstanding_outputs: BTreeMap<Pubkey, u64>,
+}
+
+// executions per wallet, only created for transforms that track wallets
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug, Default)]
+pub struct TransformWallet {
+    // units executed forward by the wallet
+    pub executions: u64,
+    pub last_execution_slot: u64,
+}
+
+// picks committed to by a forward execution of a pack transform, closed on reveal
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct PackCommitment {
+    pub transform_id: u64,
+    pub picks: u64,
+    // outputs are picked with the hash of this slot, so they are unknown until the slot is over
+    pub commit_slot: u64,
+    // forward amount of every output per pick when the pack was committed to
+    pub amounts: BTreeMap<Pubkey, u64>,
+    // escrowed inputs paid for the pack, returned if the pack is cancelled
+    pub inputs: BTreeMap<Pubkey, u64>,
+}
+
+// marks a permit nonce as used, can be closed once the permit has expired since it cannot
+// be executed again after that
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct PermitNonce {
+    pub transform_id: u64,
+    pub nonce: u64,
+    pub expiry: i64,
+    // paid the rent of the account, refunded on close
+    pub payer: Pubkey,
+}
+
+// created on the first bid, status of the buyout auction of a transform
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug, Default)]
+pub struct Buyout {
+    pub transform_id: u64,
+    // highest bid, held in the buyout vault
+    pub bidder: Pubkey,
+    pub bid: u64,
+    // decimals of the quote mint, read once on the first bid
+    pub quote_decimals: u8,
+    // bidder can claim the input once this passes without a higher bid
+    pub end_unix_timestamp: i64,
+    pub claimed: bool,
+    // fractions and proceeds that have not been redeemed yet, set on claim
+    pub outstanding_fractions: u64,
+    pub proceeds: u64,
 }
 
 impl Tokenitis {
+    // transform count of a tokenitis account with the legacy layout
+    pub fn unpack_legacy(data: &[u8]) -> Option<u64> {
+        match data.len() {
+            LEGACY_TOKENITIS_LEN => u64::try_from_slice(data).ok(),
+            _ => None,
+        }
+    }
     pub fn find_tokenitis_address(program_id: &Pubkey) -> (Pubkey, u8) {
         Pubkey::find_program_address(&[Self::tokenitis_seed().as_slice()], program_id)
     }
@@ -35,28 +170,356 @@ impl Tokenitis {
             program_id,
         )
     }
+    // token account owned by the transform that holds fees collected for an input mint
+    pub fn find_fee_vault_address(
+        program_id: &Pubkey,
+        transform_num: u64,
+        mint: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                Self::fee_vault_seed(transform_num).as_slice(),
+                mint.as_ref(),
+            ],
+            program_id,
+        )
+    }
+    // token account owned by the tokenitis account that holds protocol fees for a mint
+    pub fn find_treasury_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::treasury_seed().as_slice(), mint.as_ref()],
+            program_id,
+        )
+    }
+    pub fn find_wallet_address(
+        program_id: &Pubkey,
+        transform_num: u64,
+        wallet: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::wallet_seed(transform_num).as_slice(), wallet.as_ref()],
+            program_id,
+        )
+    }
+    // marks a permit nonce as used
+    pub fn find_permit_address(
+        program_id: &Pubkey,
+        transform_num: u64,
+        nonce: u64,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                Self::permit_seed(transform_num).as_slice(),
+                &nonce.to_le_bytes(),
+            ],
+            program_id,
+        )
+    }
+    // pending pack commitment of a wallet, a wallet can have one pending pack per transform
+    pub fn find_pack_address(
+        program_id: &Pubkey,
+        transform_num: u64,
+        wallet: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::pack_seed(transform_num).as_slice(), wallet.as_ref()],
+            program_id,
+        )
+    }
+    pub fn find_buyout_address(program_id: &Pubkey, transform_num: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::buyout_seed(transform_num).as_slice()], program_id)
+    }
+    // token account owned by the transform that holds the highest bid
+    pub fn find_buyout_vault_address(program_id: &Pubkey, transform_num: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::buyout_vault_seed(transform_num).as_slice()],
+            program_id,
+        )
+    }
     pub fn tokenitis_seed() -> Vec<u8> {
         TOKENITIS_PDA.to_vec()
     }
     pub fn transform_seed(transform_num: u64) -> Vec<u8> {
         format!("{}-{}", TRANSFORM_PREFIX, transform_num).into_bytes()
     }
+    pub fn fee_vault_seed(transform_num: u64) -> Vec<u8> {
+        format!("{}-{}", FEE_VAULT_PREFIX, transform_num).into_bytes()
+    }
+    pub fn wallet_seed(transform_num: u64) -> Vec<u8> {
+        format!("{}-{}", WALLET_PREFIX, transform_num).into_bytes()
+    }
+    pub fn permit_seed(transform_num: u64) -> Vec<u8> {
+        format!("{}-{}", PERMIT_PREFIX, transform_num).into_bytes()
+    }
+    pub fn pack_seed(transform_num: u64) -> Vec<u8> {
+        format!("{}-{}", PACK_PREFIX, transform_num).into_bytes()
+    }
+    pub fn buyout_seed(transform_num: u64) -> Vec<u8> {
+        format!("{}-{}", BUYOUT_PREFIX, transform_num).into_bytes()
+    }
+    pub fn buyout_vault_seed(transform_num: u64) -> Vec<u8> {
+        format!("{}-{}", BUYOUT_VAULT_PREFIX, transform_num).into_bytes()
+    }
+    pub fn treasury_seed() -> Vec<u8> {
+        TREASURY_PDA.to_vec()
+    }
+
+    // rounds down so that together with a creator fee it never takes a whole returned unit
+    pub fn protocol_fee(&self) -> Fee {
+        Fee {
+            bps: self.protocol_fee_bps,
+            rounding: Rounding::Floor,
+        }
+    }
 }
 
 impl Transform {
     pub fn transform_len(args: CreateTransformArgs) -> core::result::Result<usize, ProgramError> {
+        let index = args
+            .index
+            .map(|config| IndexState::new(config, &args.inputs, u64::MAX));
+        let reserved_outputs = args.outputs.keys().map(|mint| (*mint, 0)).collect();
         Ok(Transform {
             id: u64::MAX,
             initialized: true,
             metadata: args.metadata.clone(),
-            fee: None,
+            forward: args.forward.clone(),
+            reverse: args.reverse.clone(),
+            fee_recipients: args.fee_recipients.clone(),
+            fees_collected: args.inputs.keys().map(|mint| (*mint, 0)).collect(),
             inputs: args.inputs.clone(),
+            outstanding_outputs: args.outputs.keys().map(|mint| (*mint, 0)).collect(),
             outputs: args.outputs,
+            close_policy: args.close_policy,
+            status: TransformStatus::Active,
+            limits: args.limits,
+            gate: args.gate,
+            allowlist: args.allowlist,
+            permit_signer: args.permit_signer,
+            pack: args.pack,
+            buyout: args.buyout,
+            index,
+            total_executions: u64::MAX,
+            pending_packs: u64::MAX,
+            reserved_outputs,
             creator: Default::default(),
+            pending_creator: None,
         }
         .try_to_vec()?
         .len())
     }
+
+    pub fn policy(&self, direction: &Direction) -> &DirectionPolicy {
+        match direction {
+            Direction::Forward => &self.forward,
+            Direction::Reverse => &self.reverse,
+        }
+    }
+
+    // amount of the token moved per unit in the given direction
+    pub fn amount(&self, direction: &Direction, mint: &Pubkey, token: &Token) -> u64 {
+        *self
+            .policy(direction)
+            .amounts
+            .get(mint)
+            .unwrap_or(&token.amount)
+    }
+
+    pub fn has_fee(&self) -> bool {
+        self.forward.fee.is_some() || self.reverse.fee.is_some()
+    }
+
+    pub fn permit_signer(&self) -> Option<Pubkey> {
+        match self.permit_signer? {
+            PermitSigner::Creator => Some(self.creator),
+            PermitSigner::Key(key) => Some(key),
+        }
+    }
+
+    // wallet accounts are only created if executions have to be tracked per wallet
+    pub fn tracks_wallets(&self) -> bool {
+        self.limits.max_executions_per_wallet.is_some()
+            || self.limits.cooldown_slots.is_some()
+            || self.allowlist.is_some()
+    }
+
+    // escrowed outputs of a pack commitment, every pick can be any output so each escrow
+    // has to cover all of the picks
+    pub fn pack_reservation(
+        &self,
+        commitment: &PackCommitment,
+    ) -> core::result::Result<BTreeMap<Pubkey, u64>, ProgramError> {
+        let mut reservation = BTreeMap::new();
+        for (mint, token) in self.outputs.iter() {
+            if token.mode != TokenMode::Escrow {
+                continue;
+            }
+            let amount = commitment.amounts.get(mint).copied().unwrap_or(0);
+            reservation.insert(
+                *mint,
+                amount
+                    .checked_mul(commitment.picks)
+                    .ok_or(TokenitisError::ArithmeticOverflow)?,
+            );
+        }
+        Ok(reservation)
+    }
+
+    // reservation is released when the pack is revealed or cancelled
+    pub fn release_pack_outputs(&mut self, commitment: &PackCommitment) -> ProgramResult {
+        for (mint, amount) in self.pack_reservation(commitment)? {
+            let reserved = self.reserved_outputs.entry(mint).or_insert(0);
+            *reserved = reserved
+                .checked_sub(amount)
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn fee_splits(&self) -> Vec<FeeRecipient> {
+        if self.fee_recipients.is_empty() {
+            vec![FeeRecipient {
+                owner: self.creator,
+                share_bps: BPS_DENOMINATOR as u16,
+            }]
+        } else {
+            self.fee_recipients.clone()
+        }
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct DirectionPolicy {
+    pub enabled: bool,
+    // paid on top of the inputs on forward and withheld from the inputs on reverse
+    pub fee: Option<Fee>,
+    // overrides Token.amount per mint, allows a spread between the directions
+    pub amounts: BTreeMap<Pubkey, u64>,
+    // direction can only be executed within the window, either end is open if not set
+    pub start_unix_timestamp: Option<i64>,
+    pub end_unix_timestamp: Option<i64>,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct Fee {
+    // basis points of the amount, 1 bps = 0.01%
+    pub bps: u16,
+    pub rounding: Rounding,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct FeeRecipient {
+    pub owner: Pubkey,
+    // share of the fee in basis points, shares of all recipients add up to 100%
+    pub share_bps: u16,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum PermitSigner {
+    // follows the creator when it is transferred
+    Creator,
+    Key(Pubkey),
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct PackConfig {
+    // chance of an output being picked is its weight over the sum of all weights
+    pub weights: BTreeMap<Pubkey, u64>,
+    // outputs picked per unit, each pick pays out the forward amount of the output
+    pub picks: u64,
+}
+
+// transform has to take a single escrowed input and give a single output, the fractions
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct BuyoutConfig {
+    // mint bids are paid in
+    pub quote_mint: Pubkey,
+    pub reserve_price: u64,
+    // seconds without a higher bid until the highest bidder can claim the input
+    pub duration: i64,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct IndexConfig {
+    // can rebalance besides the creator
+    pub rebalancer: Option<Pubkey>,
+    // has to sign every rebalance, vouching for the prices the nav is checked with
+    pub price_authority: Pubkey,
+    // input amounts can change by at most this much within an epoch
+    pub max_change_bps: u16,
+    // nav per unit can drop by at most this much on a rebalance
+    pub nav_tolerance_bps: u16,
+}
+
+// inputs of an index transform are always escrowed, so the escrow holds
+// the input amount for every outstanding unit
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct IndexState {
+    pub config: IndexConfig,
+    // units executed forward minus units executed reverse
+    pub outstanding_units: u64,
+    // epoch of the last rebalance
+    pub epoch: u64,
+    // input amounts at the start of the epoch, changes within the epoch are limited against these
+    pub epoch_amounts: BTreeMap<Pubkey, u64>,
+}
+
+impl IndexState {
+    pub fn new(config: IndexConfig, inputs: &BTreeMap<Pubkey, Token>, epoch: u64) -> Self {
+        IndexState {
+            config,
+            outstanding_units: 0,
+            epoch,
+            epoch_amounts: inputs
+                .iter()
+                .map(|(mint, token)| (*mint, token.amount))
+                .collect(),
+        }
+    }
+}
+
+// holding is checked but not consumed
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct Gate {
+    pub mint: Pubkey,
+    pub min_amount: u64,
+    // program owning the gate mint, token accounts of the gate mint are owned by it
+    pub token_program: TokenProgram,
+}
+
+// limits only apply to forward executions, holders can always reverse
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug, Default)]
+pub struct ExecutionLimits {
+    // units that can be executed across all wallets
+    pub max_total_executions: Option<u64>,
+    pub max_executions_per_wallet: Option<u64>,
+    // slots a wallet has to wait between executions
+    pub cooldown_slots: Option<u64>,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum ClosePolicy {
+    // transform can only be closed once every output has been returned to it
+    NoOutstanding,
+    // creator can close the transform at any time, unless outstanding outputs could still be
+    // reversed for the escrowed inputs
+    Anytime,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub enum TransformStatus {
+    Active,
+    Paused,
+    // only reverse can be executed so holders can always exit
+    ReverseOnly,
+    // set on the first bid, the transform can no longer be executed or closed
+    Buyout,
 }
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
@@ -67,6 +530,28 @@ pub struct TransformMetadata {
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug, Ord, PartialOrd, Eq)]
 pub struct Token {
+    // escrow token account, or the mint itself when the transform does not hold the token
     pub account: Pubkey,
     pub amount: u64,
+    pub mode: TokenMode,
+    // program that owns the mint, validated against the mint on create
+    pub token_program: TokenProgram,
+    // decimals of the mint, validated against the mint on create
+    pub decimals: u8,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug, Ord, PartialOrd, Eq)]
+pub enum TokenMode {
+    // tokens are held by the transform in an escrow account, outputs have a fixed supply
+    Escrow,
+    // transform is the mint authority of the output, minted on forward and burnt on reverse
+    Mint,
+    // inputs are burnt from the caller instead of being held, transform cannot be reversed
+    Burn,
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Debug, Ord, PartialOrd, Eq)]
+pub enum TokenProgram {
+    Token,
+    Token2022,
 }
@@ -0,0 +1,55 @@
+use crate::errors::TokenitisError;
+use crate::state::{FeeRecipient, Transform};
+use crate::tokenitis_instruction::accept_creator::AcceptCreator;
+use crate::util::resize_pda;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+
+impl AcceptCreator<'_> {
+    // fees are paid to the accounts of the new creator from now on, including the share
+    // of the previous creator when fee recipients are set
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let new_creator = *accounts.new_creator.key;
+        let mut fee_recipients: Vec<FeeRecipient> = vec![];
+        for recipient in transform_state.fee_recipients.iter() {
+            let owner = if recipient.owner == transform_state.creator {
+                new_creator
+            } else {
+                recipient.owner
+            };
+            // shares are merged if the new creator already was a recipient
+            match fee_recipients
+                .iter_mut()
+                .find(|merged| merged.owner == owner)
+            {
+                Some(merged) => {
+                    merged.share_bps = merged
+                        .share_bps
+                        .checked_add(recipient.share_bps)
+                        .ok_or(TokenitisError::ArithmeticOverflow)?
+                }
+                None => fee_recipients.push(FeeRecipient {
+                    owner,
+                    share_bps: recipient.share_bps,
+                }),
+            }
+        }
+        transform_state.fee_recipients = fee_recipients;
+        transform_state.creator = new_creator;
+        transform_state.pending_creator = None;
+
+        resize_pda(
+            transform_state.try_to_vec()?.len(),
+            accounts.new_creator,
+            accounts.transform,
+            accounts.system_program,
+        )?;
+        transform_state.serialize(&mut &mut accounts.transform.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::state::Transform;
+use crate::tokenitis_instruction::TokenitisInstruction;
+
+use borsh::BorshDeserialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct AcceptCreator<'a> {
+    program_id: Pubkey,
+    accounts: AcceptCreatorAccounts<'a>,
+}
+
+// fee vaults are only passed if the transform charges a fee
+struct AcceptCreatorAccounts<'a> {
+    system_program: &'a AccountInfo<'a>,
+    transform: &'a AccountInfo<'a>,
+    new_creator: &'a AccountInfo<'a>,
+    fee_vaults: Vec<&'a AccountInfo<'a>>,
+}
+
+impl<'a> AcceptCreator<'a> {
+    pub fn new(program_id: Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let system_program = next_account_info(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let new_creator = next_account_info(accounts)?;
+
+        let transform_state = Transform::deserialize(&mut &**transform.data.borrow())?;
+        let mut fee_vaults: Vec<&AccountInfo> = Vec::new();
+        if transform_state.has_fee() {
+            for _ in 0..transform_state.inputs.len() {
+                fee_vaults.push(next_account_info(accounts)?)
+            }
+        }
+
+        Ok(AcceptCreator {
+            program_id,
+            accounts: AcceptCreatorAccounts {
+                system_program,
+                transform,
+                new_creator,
+                fee_vaults,
+            },
+        })
+    }
+}
+
+impl TokenitisInstruction for AcceptCreator<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
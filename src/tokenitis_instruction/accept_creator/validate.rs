@@ -0,0 +1,75 @@
+use crate::errors::TokenitisError;
+use crate::state::{Tokenitis, Transform};
+use crate::tokenitis_instruction::accept_creator::AcceptCreator;
+use crate::util::token::unpack_account;
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+use std::ops::Index;
+
+impl AcceptCreator<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        if *accounts.system_program.key != solana_program::system_program::id() {
+            msg!("invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if transform_state.pending_creator != Some(*accounts.new_creator.key) {
+            msg!(
+                "invalid new creator account, expected - {:?}, got - {}",
+                transform_state.pending_creator,
+                accounts.new_creator.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if !accounts.new_creator.is_signer {
+            msg!("new creator has to sign the transaction");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // fees collected so far belong to the current recipients, the previous creator
+        // withdraws them first, possibly in the same transaction
+        for (i, mint) in transform_state
+            .inputs
+            .keys()
+            .enumerate()
+            .take(accounts.fee_vaults.len())
+        {
+            let fee_vault = accounts.fee_vaults.index(i);
+            let (fee_vault_addr, _) =
+                Tokenitis::find_fee_vault_address(&self.program_id, transform_state.id, mint);
+            if *fee_vault.key != fee_vault_addr {
+                msg!(
+                    "invalid fee vault at index - {}, expected - {}, got - {}",
+                    i,
+                    fee_vault_addr,
+                    fee_vault.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let amount = unpack_account(fee_vault)?.amount;
+            if amount != 0 {
+                msg!(
+                    "fee vault at index - {} holds {} that has not been withdrawn",
+                    i,
+                    amount
+                );
+                return Err(TokenitisError::FeesNotWithdrawn.into());
+            }
+        }
+
+        Ok(())
+    }
+}
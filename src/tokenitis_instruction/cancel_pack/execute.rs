@@ -0,0 +1,49 @@
+use crate::errors::TokenitisError;
+use crate::state::{PackCommitment, Tokenitis, Transform};
+use crate::tokenitis_instruction::cancel_pack::CancelPack;
+use crate::util::{close_pda, transfer};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+use std::ops::Index;
+
+impl CancelPack<'_> {
+    // Return the escrowed inputs of an expired pack to the caller and release its outputs,
+    // fees and burnt inputs are not returned
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (_, nonce) = Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        let transform_seed = Tokenitis::transform_seed(transform_state.id);
+        let signer_seeds: &[&[u8]] = &[transform_seed.as_slice(), &[nonce]];
+
+        let commitment =
+            PackCommitment::deserialize(&mut &**accounts.pack_commitment.data.borrow())?;
+        for (i, (mint, token)) in transform_state.inputs.iter().enumerate() {
+            let amount = commitment.inputs.get(mint).copied().unwrap_or(0);
+            if amount == 0 {
+                continue;
+            }
+            transfer(
+                &accounts.token_programs,
+                accounts.inputs.index(i),
+                accounts.input_mints.index(i),
+                accounts.caller_inputs.index(i),
+                accounts.transform,
+                amount,
+                token.decimals,
+                &[signer_seeds],
+            )?;
+        }
+
+        transform_state.release_pack_outputs(&commitment)?;
+        transform_state.pending_packs = transform_state
+            .pending_packs
+            .checked_sub(1)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        transform_state.serialize(&mut &mut accounts.transform.data.borrow_mut()[..])?;
+
+        close_pda(accounts.pack_commitment, accounts.caller)
+    }
+}
@@ -0,0 +1,90 @@
+use crate::state::Transform;
+use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
+
+use borsh::BorshDeserialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct CancelPack<'a> {
+    program_id: Pubkey,
+    accounts: CancelPackAccounts<'a>,
+}
+
+// only the creator can cancel a pack, the caller would otherwise get to choose between
+// the fallback outputs and its inputs
+struct CancelPackAccounts<'a> {
+    token_programs: TokenPrograms<'a>,
+    transform: &'a AccountInfo<'a>,
+    pack_commitment: &'a AccountInfo<'a>,
+    // wallet that executed the transform, receives the inputs and the rent of the commitment
+    caller: &'a AccountInfo<'a>,
+    creator: &'a AccountInfo<'a>,
+    input_mints: Vec<&'a AccountInfo<'a>>,
+    caller_inputs: Vec<&'a AccountInfo<'a>>,
+    inputs: Vec<&'a AccountInfo<'a>>,
+}
+
+impl<'a> CancelPack<'a> {
+    pub fn new(program_id: Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let mut token_programs = TokenPrograms::new(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let pack_commitment = next_account_info(accounts)?;
+        let caller = next_account_info(accounts)?;
+        let creator = next_account_info(accounts)?;
+
+        let transform_state = Transform::deserialize(&mut &**transform.data.borrow())?;
+
+        let mut input_mints: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            input_mints.push(next_account_info(accounts)?)
+        }
+
+        let mut caller_inputs: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            caller_inputs.push(next_account_info(accounts)?)
+        }
+
+        let mut inputs: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            inputs.push(next_account_info(accounts)?)
+        }
+
+        // remaining accounts are transfer hook accounts
+        token_programs.hook_accounts = accounts.collect();
+
+        Ok(CancelPack {
+            program_id,
+            accounts: CancelPackAccounts {
+                token_programs,
+                transform,
+                pack_commitment,
+                caller,
+                creator,
+                input_mints,
+                caller_inputs,
+                inputs,
+            },
+        })
+    }
+}
+
+impl TokenitisInstruction for CancelPack<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
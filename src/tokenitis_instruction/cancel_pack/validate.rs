@@ -0,0 +1,96 @@
+use crate::errors::TokenitisError;
+use crate::state::{PackCommitment, Tokenitis, Transform};
+use crate::tokenitis_instruction::cancel_pack::CancelPack;
+use crate::util::token::unpack_account;
+use crate::util::validate_creator;
+
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::slot_hashes::MAX_ENTRIES;
+use solana_program::sysvar::Sysvar;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+use std::ops::Index;
+
+impl CancelPack<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        accounts.token_programs.validate()?;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        validate_creator(&transform_state, accounts.creator)?;
+
+        let (pack_commitment_addr, _) =
+            Tokenitis::find_pack_address(&self.program_id, transform_state.id, accounts.caller.key);
+        if *accounts.pack_commitment.key != pack_commitment_addr {
+            msg!(
+                "invalid pack commitment account, expected - {}, got - {}",
+                pack_commitment_addr,
+                accounts.pack_commitment.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if accounts.pack_commitment.data_len() == 0 {
+            msg!("caller has no pack to cancel");
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        // a pack can be revealed as long as the hash of its commit slot is available
+        let commitment =
+            PackCommitment::deserialize(&mut &**accounts.pack_commitment.data.borrow())?;
+        let slot = Clock::get()?.slot;
+        if slot <= commitment.commit_slot.saturating_add(MAX_ENTRIES as u64) {
+            msg!(
+                "pack committed in slot - {} can only be cancelled after slot - {}, now - {}",
+                commitment.commit_slot,
+                commitment.commit_slot.saturating_add(MAX_ENTRIES as u64),
+                slot
+            );
+            return Err(TokenitisError::PackNotExpired.into());
+        }
+
+        for (i, (mint, token)) in transform_state.inputs.iter().enumerate() {
+            let input_mint = accounts.input_mints.index(i);
+            if *input_mint.key != *mint {
+                msg!(
+                    "invalid input mint at index - {}, expected - {}, got - {}",
+                    i,
+                    mint,
+                    input_mint.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let input_account = accounts.inputs.index(i);
+            if *input_account.key != token.account {
+                msg!(
+                    "invalid input at index - {}, unexpected program account, expected - {}, got - {}",
+                    i,
+                    token.account,
+                    input_account.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let caller_input_account_info = unpack_account(accounts.caller_inputs.index(i))?;
+            if caller_input_account_info.mint != *mint {
+                msg!("invalid input at index - {}, unexpected mint of caller_input, expected - {}, got - {}",i, mint,caller_input_account_info.mint);
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            if caller_input_account_info.owner != *accounts.caller.key {
+                msg!("invalid input at index - {}, unexpected owner of caller_input, expected - {}, got - {}",i, accounts.caller.key,caller_input_account_info.owner);
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        Ok(())
+    }
+}
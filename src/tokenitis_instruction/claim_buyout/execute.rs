@@ -0,0 +1,57 @@
+use crate::errors::TokenitisError;
+use crate::state::{Buyout, Tokenitis, Transform};
+use crate::tokenitis_instruction::claim_buyout::ClaimBuyout;
+use crate::util::token::{unpack_account, unpack_mint};
+use crate::util::transfer;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+
+impl ClaimBuyout<'_> {
+    // Send the input to the highest bidder and record the fractions
+    // the proceeds are split between, if holders burned every fraction
+    // nothing is outstanding and the proceeds stay in the buyout vault
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (_, nonce) = Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        let transform_seed = Tokenitis::transform_seed(transform_state.id);
+        let signer_seeds: &[&[u8]] = &[transform_seed.as_slice(), &[nonce]];
+
+        // buyout transforms escrow a single input
+        let input = transform_state
+            .inputs
+            .values()
+            .next()
+            .ok_or(ProgramError::InvalidArgument)?;
+        let input_amount = unpack_account(accounts.input)?.amount;
+        transfer(
+            &accounts.token_programs,
+            accounts.input,
+            accounts.input_mint,
+            accounts.bidder_input,
+            accounts.transform,
+            input_amount,
+            input.decimals,
+            &[signer_seeds],
+        )?;
+
+        let supply = unpack_mint(accounts.fraction_mint)?.supply;
+        let held = match accounts.fraction_escrow {
+            Some(fraction_escrow) => unpack_account(fraction_escrow)?.amount,
+            None => 0,
+        };
+
+        let mut buyout_state = Buyout::deserialize(&mut &**accounts.buyout.data.borrow())?;
+        buyout_state.claimed = true;
+        buyout_state.outstanding_fractions = supply
+            .checked_sub(held)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        buyout_state.proceeds = buyout_state.bid;
+        buyout_state.serialize(&mut &mut accounts.buyout.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use crate::state::{TokenMode, Transform};
+use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
+
+use borsh::BorshDeserialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct ClaimBuyout<'a> {
+    program_id: Pubkey,
+    accounts: ClaimBuyoutAccounts<'a>,
+}
+
+// anyone can claim an ended buyout, the input is always sent to the highest bidder
+struct ClaimBuyoutAccounts<'a> {
+    token_programs: TokenPrograms<'a>,
+    transform: &'a AccountInfo<'a>,
+    buyout: &'a AccountInfo<'a>,
+    input_mint: &'a AccountInfo<'a>,
+    input: &'a AccountInfo<'a>,
+    bidder_input: &'a AccountInfo<'a>,
+    fraction_mint: &'a AccountInfo<'a>,
+    // fractions held by the transform are not outstanding, only passed for escrowed fractions
+    fraction_escrow: Option<&'a AccountInfo<'a>>,
+}
+
+impl<'a> ClaimBuyout<'a> {
+    pub fn new(program_id: Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let mut token_programs = TokenPrograms::new(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let buyout = next_account_info(accounts)?;
+        let input_mint = next_account_info(accounts)?;
+        let input = next_account_info(accounts)?;
+        let bidder_input = next_account_info(accounts)?;
+        let fraction_mint = next_account_info(accounts)?;
+
+        let transform_state = Transform::deserialize(&mut &**transform.data.borrow())?;
+        let fraction_escrow = if transform_state
+            .outputs
+            .values()
+            .any(|token| token.mode == TokenMode::Escrow)
+        {
+            Some(next_account_info(accounts)?)
+        } else {
+            None
+        };
+
+        // remaining accounts are transfer hook accounts
+        token_programs.hook_accounts = accounts.collect();
+
+        Ok(ClaimBuyout {
+            program_id,
+            accounts: ClaimBuyoutAccounts {
+                token_programs,
+                transform,
+                buyout,
+                input_mint,
+                input,
+                bidder_input,
+                fraction_mint,
+                fraction_escrow,
+            },
+        })
+    }
+}
+
+impl TokenitisInstruction for ClaimBuyout<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,120 @@
+use crate::errors::TokenitisError;
+use crate::state::{Buyout, Tokenitis, Transform};
+use crate::tokenitis_instruction::claim_buyout::ClaimBuyout;
+use crate::util::token::unpack_account;
+
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::sysvar::Sysvar;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl ClaimBuyout<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        accounts.token_programs.validate()?;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (buyout_addr, _) = Tokenitis::find_buyout_address(&self.program_id, transform_state.id);
+        if *accounts.buyout.key != buyout_addr {
+            msg!(
+                "invalid buyout account, expected - {}, got - {}",
+                buyout_addr,
+                accounts.buyout.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if accounts.buyout.data_len() == 0 {
+            msg!("transform has no bids");
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        let buyout_state = Buyout::deserialize(&mut &**accounts.buyout.data.borrow())?;
+        if buyout_state.claimed {
+            msg!("buyout has already been claimed");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        if now < buyout_state.end_unix_timestamp {
+            msg!(
+                "buyout ends at - {}, now - {}",
+                buyout_state.end_unix_timestamp,
+                now
+            );
+            return Err(TokenitisError::BuyoutNotEnded.into());
+        }
+
+        let (input_mint, input) = transform_state
+            .inputs
+            .iter()
+            .next()
+            .ok_or(ProgramError::InvalidArgument)?;
+        if *accounts.input_mint.key != *input_mint {
+            msg!(
+                "invalid input mint, expected - {}, got - {}",
+                input_mint,
+                accounts.input_mint.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if *accounts.input.key != input.account {
+            msg!(
+                "invalid input account, expected - {}, got - {}",
+                input.account,
+                accounts.input.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let bidder_input_info = unpack_account(accounts.bidder_input)?;
+        if bidder_input_info.owner != buyout_state.bidder {
+            msg!(
+                "invalid bidder input account, unexpected owner, expected - {}, got - {}",
+                buyout_state.bidder,
+                bidder_input_info.owner
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if bidder_input_info.mint != *input_mint {
+            msg!(
+                "invalid bidder input account, unexpected mint, expected - {}, got - {}",
+                input_mint,
+                bidder_input_info.mint
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (fraction_mint, fractions) = transform_state
+            .outputs
+            .iter()
+            .next()
+            .ok_or(ProgramError::InvalidArgument)?;
+        if *accounts.fraction_mint.key != *fraction_mint {
+            msg!(
+                "invalid fraction mint, expected - {}, got - {}",
+                fraction_mint,
+                accounts.fraction_mint.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if let Some(fraction_escrow) = accounts.fraction_escrow {
+            if *fraction_escrow.key != fractions.account {
+                msg!(
+                    "invalid fraction escrow account, expected - {}, got - {}",
+                    fractions.account,
+                    fraction_escrow.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+use crate::tokenitis_instruction::close_permit::ClosePermit;
+use crate::util::close_pda;
+
+use solana_program::entrypoint::ProgramResult;
+
+impl ClosePermit<'_> {
+    // anyone can close an expired permit nonce, the rent goes back to whoever paid it
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        close_pda(accounts.permit_nonce, accounts.payer)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::tokenitis_instruction::TokenitisInstruction;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct ClosePermit<'a> {
+    program_id: Pubkey,
+    accounts: ClosePermitAccounts<'a>,
+}
+
+struct ClosePermitAccounts<'a> {
+    permit_nonce: &'a AccountInfo<'a>,
+    // receives the rent of the permit nonce account
+    payer: &'a AccountInfo<'a>,
+}
+
+impl<'a> ClosePermit<'a> {
+    pub fn new(program_id: Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let permit_nonce = next_account_info(accounts)?;
+        let payer = next_account_info(accounts)?;
+
+        Ok(ClosePermit {
+            program_id,
+            accounts: ClosePermitAccounts {
+                permit_nonce,
+                payer,
+            },
+        })
+    }
+}
+
+impl TokenitisInstruction for ClosePermit<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
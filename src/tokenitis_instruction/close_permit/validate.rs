@@ -0,0 +1,57 @@
+use crate::state::{PermitNonce, Tokenitis};
+use crate::tokenitis_instruction::close_permit::ClosePermit;
+
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::sysvar::Sysvar;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl ClosePermit<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        if *accounts.permit_nonce.owner != self.program_id || accounts.permit_nonce.data_len() == 0
+        {
+            msg!("permit nonce account has not been created");
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        let permit_nonce_state =
+            PermitNonce::deserialize(&mut &**accounts.permit_nonce.data.borrow())?;
+        let (permit_nonce_addr, _) = Tokenitis::find_permit_address(
+            &self.program_id,
+            permit_nonce_state.transform_id,
+            permit_nonce_state.nonce,
+        );
+        if *accounts.permit_nonce.key != permit_nonce_addr {
+            msg!(
+                "invalid permit nonce account, expected - {}, got - {}",
+                permit_nonce_addr,
+                accounts.permit_nonce.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if *accounts.payer.key != permit_nonce_state.payer {
+            msg!(
+                "invalid payer account, expected - {}, got - {}",
+                permit_nonce_state.payer,
+                accounts.payer.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // permit can still be executed until it expires, the nonce has to stay used until then
+        let now = Clock::get()?.unix_timestamp;
+        if now <= permit_nonce_state.expiry {
+            msg!(
+                "permit expires at - {}, cannot be closed before, now - {}",
+                permit_nonce_state.expiry,
+                now
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use crate::state::{TokenMode, Tokenitis, Transform};
+use crate::tokenitis_instruction::close_transform::CloseTransform;
+use crate::util::token::unpack_account;
+use crate::util::{close_pda, close_token_account, set_authority, transfer};
+
+use borsh::BorshDeserialize;
+use solana_program::entrypoint::ProgramResult;
+use spl_token::instruction::AuthorityType;
+use std::ops::Index;
+
+impl CloseTransform<'_> {
+    // Return the remaining output supply to the creator, close the escrow accounts
+    // and the transform account and send the rent back to the creator, inputs left in
+    // escrow no longer back outstanding outputs once the transform can be closed
+    // and are returned to the creator as well
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (_, nonce) = Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        let transform_seed = Tokenitis::transform_seed(transform_state.id);
+        let signer_seeds: &[&[u8]] = &[transform_seed.as_slice(), &[nonce]];
+
+        // escrow accounts are passed in the order of the escrowed tokens
+        let escrow_mints = transform_state
+            .inputs
+            .values()
+            .zip(accounts.input_mints.iter())
+            .filter(|(token, _)| token.mode == TokenMode::Escrow)
+            .chain(
+                transform_state
+                    .outputs
+                    .values()
+                    .zip(accounts.output_mints.iter())
+                    .filter(|(token, _)| token.mode == TokenMode::Escrow),
+            )
+            .map(|(token, mint)| (*mint, token.decimals));
+        let escrow_accounts = accounts
+            .inputs
+            .iter()
+            .zip(accounts.creator_inputs.iter())
+            .chain(accounts.outputs.iter().zip(accounts.creator_outputs.iter()));
+        for ((mint, decimals), (escrow_account, creator_account)) in
+            escrow_mints.zip(escrow_accounts)
+        {
+            let amount = unpack_account(escrow_account)?.amount;
+            if amount != 0 {
+                transfer(
+                    &accounts.token_programs,
+                    escrow_account,
+                    mint,
+                    creator_account,
+                    accounts.transform,
+                    amount,
+                    decimals,
+                    &[signer_seeds],
+                )?;
+            }
+            close_token_account(
+                &accounts.token_programs,
+                escrow_account,
+                mint,
+                accounts.creator,
+                accounts.transform,
+                &[signer_seeds],
+            )?;
+        }
+
+        for (fee_vault, mint) in accounts.fee_vaults.iter().zip(accounts.input_mints.iter()) {
+            close_token_account(
+                &accounts.token_programs,
+                fee_vault,
+                mint,
+                accounts.creator,
+                accounts.transform,
+                &[signer_seeds],
+            )?;
+        }
+
+        // hand minted outputs back to the creator
+        for (i, token) in transform_state.outputs.values().enumerate() {
+            if token.mode != TokenMode::Mint {
+                continue;
+            }
+
+            let output_mint = *accounts.output_mints.index(i);
+            set_authority(
+                accounts.token_programs.program_of(output_mint)?,
+                output_mint,
+                accounts.creator.key,
+                AuthorityType::MintTokens,
+                accounts.transform,
+                &[signer_seeds],
+            )?;
+        }
+
+        close_pda(accounts.transform, accounts.creator)
+    }
+}
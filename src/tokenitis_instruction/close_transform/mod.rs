@@ -0,0 +1,123 @@
+use crate::state::{TokenMode, Transform};
+use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
+
+use borsh::BorshDeserialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct CloseTransform<'a> {
+    program_id: Pubkey,
+    accounts: CloseTransformAccounts<'a>,
+}
+
+// only escrow accounts are passed for inputs and outputs,
+// mints are passed for every input and output, fee vaults only if the transform has a fee
+struct CloseTransformAccounts<'a> {
+    token_programs: TokenPrograms<'a>,
+    transform: &'a AccountInfo<'a>,
+    creator: &'a AccountInfo<'a>,
+    input_mints: Vec<&'a AccountInfo<'a>>,
+    inputs: Vec<&'a AccountInfo<'a>>,
+    creator_inputs: Vec<&'a AccountInfo<'a>>,
+    output_mints: Vec<&'a AccountInfo<'a>>,
+    outputs: Vec<&'a AccountInfo<'a>>,
+    creator_outputs: Vec<&'a AccountInfo<'a>>,
+    fee_vaults: Vec<&'a AccountInfo<'a>>,
+}
+
+impl<'a> CloseTransform<'a> {
+    pub fn new(program_id: Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let mut token_programs = TokenPrograms::new(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let creator = next_account_info(accounts)?;
+
+        let transform_state = Transform::deserialize(&mut &**transform.data.borrow())?;
+        let escrow_inputs = transform_state
+            .inputs
+            .values()
+            .filter(|token| token.mode == TokenMode::Escrow)
+            .count();
+        let escrow_outputs = transform_state
+            .outputs
+            .values()
+            .filter(|token| token.mode == TokenMode::Escrow)
+            .count();
+
+        let mut input_mints: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            input_mints.push(next_account_info(accounts)?)
+        }
+
+        let mut inputs: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..escrow_inputs {
+            inputs.push(next_account_info(accounts)?)
+        }
+
+        let mut creator_inputs: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..escrow_inputs {
+            creator_inputs.push(next_account_info(accounts)?)
+        }
+
+        let mut output_mints: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.outputs.len() {
+            output_mints.push(next_account_info(accounts)?)
+        }
+
+        let mut outputs: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..escrow_outputs {
+            outputs.push(next_account_info(accounts)?)
+        }
+
+        let mut creator_outputs: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..escrow_outputs {
+            creator_outputs.push(next_account_info(accounts)?)
+        }
+
+        let mut fee_vaults: Vec<&AccountInfo> = Vec::new();
+        if transform_state.has_fee() {
+            for _ in 0..transform_state.inputs.len() {
+                fee_vaults.push(next_account_info(accounts)?)
+            }
+        }
+
+        // remaining accounts are transfer hook accounts
+        token_programs.hook_accounts = accounts.collect();
+
+        Ok(CloseTransform {
+            program_id,
+            accounts: CloseTransformAccounts {
+                token_programs,
+                transform,
+                creator,
+                input_mints,
+                inputs,
+                creator_inputs,
+                output_mints,
+                outputs,
+                creator_outputs,
+                fee_vaults,
+            },
+        })
+    }
+}
+
+impl TokenitisInstruction for CloseTransform<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
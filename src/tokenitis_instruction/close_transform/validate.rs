@@ -0,0 +1,174 @@
+use crate::errors::TokenitisError;
+use crate::state::{ClosePolicy, TokenMode, Tokenitis, Transform, TransformStatus};
+use crate::tokenitis_instruction::close_transform::CloseTransform;
+use crate::util::token::unpack_account;
+use crate::util::validate_creator;
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+use std::ops::Index;
+
+impl CloseTransform<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        accounts.token_programs.validate()?;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        validate_creator(&transform_state, accounts.creator)?;
+
+        if transform_state.status == TransformStatus::Buyout {
+            msg!("transform is being bought out and cannot be closed");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // outputs of unrevealed packs are paid out of the escrow on reveal
+        if transform_state.pending_packs > 0 {
+            msg!(
+                "transform has {} unrevealed packs and cannot be closed",
+                transform_state.pending_packs
+            );
+            return Err(TokenitisError::PackPending.into());
+        }
+
+        for (i, mint) in transform_state.inputs.keys().enumerate() {
+            let input_mint = accounts.input_mints.index(i);
+            if *input_mint.key != *mint {
+                msg!(
+                    "invalid input mint at index - {}, expected - {}, got - {}",
+                    i,
+                    mint,
+                    input_mint.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        let escrow_inputs = transform_state
+            .inputs
+            .iter()
+            .filter(|(_, token)| token.mode == TokenMode::Escrow);
+        for (i, (mint, token)) in escrow_inputs.enumerate() {
+            let input_account = accounts.inputs.index(i);
+            if *input_account.key != token.account {
+                msg!(
+                    "invalid input at index - {}, unexpected program account, expected - {}, got - {}",
+                    i,
+                    token.account,
+                    input_account.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let creator_input_account_info = unpack_account(accounts.creator_inputs.index(i))?;
+            if creator_input_account_info.mint != *mint {
+                msg!("invalid input at index - {}, unexpected mint of creator_input, expected - {}, got - {}",i, mint,creator_input_account_info.mint);
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        // escrowed inputs back the outputs of reversible and index transforms, outstanding
+        // outputs have to stay redeemable whatever the close policy
+        let inputs_back_outputs =
+            transform_state.reverse.enabled || transform_state.index.is_some();
+        let mut escrow_index = 0;
+        for (i, (mint, token)) in transform_state.outputs.iter().enumerate() {
+            let output_mint = accounts.output_mints.index(i);
+            if *output_mint.key != *mint {
+                msg!(
+                    "invalid output mint at index - {}, expected - {}, got - {}",
+                    i,
+                    mint,
+                    output_mint.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            if token.mode == TokenMode::Escrow {
+                let output_account = accounts.outputs.index(escrow_index);
+                if *output_account.key != token.account {
+                    msg!(
+                            "invalid output at index - {}, unexpected program account, expected - {}, got - {}",
+                            i,
+                            token.account,
+                            output_account.key
+                        );
+                    return Err(ProgramError::InvalidArgument);
+                }
+
+                let creator_output_account_info =
+                    unpack_account(accounts.creator_outputs.index(escrow_index))?;
+                if creator_output_account_info.mint != *mint {
+                    msg!("invalid output at index - {}, unexpected mint of creator_output, expected - {}, got - {}",i, mint,creator_output_account_info.mint);
+                    return Err(ProgramError::InvalidArgument);
+                }
+                escrow_index += 1;
+            }
+
+            let outstanding = transform_state
+                .outstanding_outputs
+                .get(mint)
+                .copied()
+                .unwrap_or(0);
+            if outstanding == 0 {
+                continue;
+            }
+            if transform_state.close_policy == ClosePolicy::NoOutstanding {
+                msg!(
+                    "output at index - {} has {} tokens outstanding, transform cannot be closed",
+                    i,
+                    outstanding
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+            if inputs_back_outputs {
+                msg!(
+                    "output at index - {} has {} tokens outstanding that can be reversed for the escrowed inputs, transform cannot be closed",
+                    i,
+                    outstanding
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        // fees belong to the fee recipients and have to be withdrawn first
+        for (i, mint) in transform_state
+            .inputs
+            .keys()
+            .enumerate()
+            .take(accounts.fee_vaults.len())
+        {
+            let fee_vault = accounts.fee_vaults.index(i);
+            let (fee_vault_addr, _) =
+                Tokenitis::find_fee_vault_address(&self.program_id, transform_state.id, mint);
+            if *fee_vault.key != fee_vault_addr {
+                msg!(
+                    "invalid fee vault at index - {}, expected - {}, got - {}",
+                    i,
+                    fee_vault_addr,
+                    fee_vault.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let fee_vault_info = unpack_account(fee_vault)?;
+            if fee_vault_info.amount != 0 {
+                msg!(
+                    "fee vault at index - {} holds {} tokens, fees have to be withdrawn before closing",
+                    i,
+                    fee_vault_info.amount
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        Ok(())
+    }
+}
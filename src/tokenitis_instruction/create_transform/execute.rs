@@ -1,13 +1,20 @@
+use crate::errors::TokenitisError;
+use crate::state::IndexState;
+use crate::state::TokenMode;
 use crate::state::Tokenitis;
 use crate::state::Transform;
+use crate::state::TransformStatus;
 
-use crate::util::create_pda;
+use crate::util::{create_pda, create_token_account_pda, set_authority};
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::tokenitis_instruction::create_transform::CreateTransform;
 
-use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, program::invoke};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::sysvar::Sysvar;
 use spl_token::instruction::AuthorityType;
+use std::ops::Index;
 
 impl CreateTransform<'_> {
     // input account should be empty token account
@@ -16,35 +23,41 @@ impl CreateTransform<'_> {
         let accounts = &self.accounts;
         let args = self.args.clone();
 
-        let mut tokenitis = if accounts.tokenitis.data.borrow().len() > 0 {
-            Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?
-        } else {
-            let space = Tokenitis {
-                num_transforms: u64::MAX,
-            }
-            .try_to_vec()?
-            .len();
-            create_pda(
-                &self.program_id,
-                space,
-                accounts.creator,
-                accounts.tokenitis,
-                accounts.system_program,
-                Tokenitis::tokenitis_seed().as_slice(),
-            )?;
-            Tokenitis { num_transforms: 0 }
-        };
-        tokenitis.num_transforms += 1;
+        let mut tokenitis = Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?;
+        tokenitis.num_transforms = tokenitis
+            .num_transforms
+            .checked_add(1)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
         tokenitis.serialize(&mut &mut accounts.tokenitis.data.borrow_mut()[..])?;
 
+        let epoch = Clock::get()?.epoch;
         let transform = Transform {
             initialized: true,
             id: tokenitis.num_transforms,
             creator: *accounts.creator.key,
+            pending_creator: None,
             metadata: args.metadata,
-            fee: args.fee,
+            forward: args.forward,
+            reverse: args.reverse,
+            fee_recipients: args.fee_recipients,
+            fees_collected: args.inputs.keys().map(|mint| (*mint, 0)).collect(),
             inputs: args.inputs.into_iter().collect(),
+            outstanding_outputs: args.outputs.keys().map(|mint| (*mint, 0)).collect(),
             outputs: args.outputs.into_iter().collect(),
+            close_policy: args.close_policy,
+            status: TransformStatus::Active,
+            limits: args.limits,
+            gate: args.gate,
+            allowlist: args.allowlist,
+            permit_signer: args.permit_signer,
+            pack: args.pack,
+            buyout: args.buyout,
+            index: args
+                .index
+                .map(|config| IndexState::new(config, &self.args.inputs, epoch)),
+            total_executions: 0,
+            pending_packs: 0,
+            reserved_outputs: self.args.outputs.keys().map(|mint| (*mint, 0)).collect(),
         };
         create_pda(
             &self.program_id,
@@ -52,31 +65,79 @@ impl CreateTransform<'_> {
             accounts.creator,
             accounts.transform,
             accounts.system_program,
-            Tokenitis::transform_seed(transform.id).as_slice(),
+            &[Tokenitis::transform_seed(transform.id).as_slice()],
         )?;
         transform.serialize(&mut &mut accounts.transform.data.borrow_mut()[..])?;
 
-        let token_accounts: Vec<&&AccountInfo> = accounts
+        // treasuries are shared by every transform with the same input mint
+        for (i, mint) in self.args.inputs.keys().enumerate() {
+            let treasury = *accounts.treasuries.index(i);
+            if treasury.data_len() > 0 {
+                continue;
+            }
+            let (_, treasury_nonce) = Tokenitis::find_treasury_address(&self.program_id, mint);
+            let treasury_seed = Tokenitis::treasury_seed();
+            create_token_account_pda(
+                &accounts.token_programs,
+                accounts.system_program,
+                accounts.rent_sysvar,
+                accounts.creator,
+                treasury,
+                accounts.input_mints.index(i),
+                accounts.tokenitis,
+                &[treasury_seed.as_slice(), mint.as_ref(), &[treasury_nonce]],
+            )?;
+        }
+
+        // fee vaults are owned by the transform, fees stay there until they are withdrawn
+        for (i, mint) in self
+            .args
             .inputs
-            .iter()
-            .chain(accounts.outputs.iter())
-            .collect();
-        for token_account in token_accounts {
-            let change_authority_ix = spl_token::instruction::set_authority(
-                accounts.token_program.key,
-                token_account.key,
-                Some(accounts.transform.key),
-                AuthorityType::AccountOwner,
-                accounts.creator.key,
-                &[accounts.creator.key],
+            .keys()
+            .enumerate()
+            .take(accounts.fee_vaults.len())
+        {
+            let (_, vault_nonce) =
+                Tokenitis::find_fee_vault_address(&self.program_id, transform.id, mint);
+            let fee_vault_seed = Tokenitis::fee_vault_seed(transform.id);
+            create_token_account_pda(
+                &accounts.token_programs,
+                accounts.system_program,
+                accounts.rent_sysvar,
+                accounts.creator,
+                accounts.fee_vaults.index(i),
+                accounts.input_mints.index(i),
+                accounts.transform,
+                &[fee_vault_seed.as_slice(), mint.as_ref(), &[vault_nonce]],
             )?;
-            invoke(
-                &change_authority_ix,
-                &[
-                    (**token_account).clone(),
-                    accounts.creator.clone(),
-                    accounts.token_program.clone(),
-                ],
+        }
+
+        let tokens = self
+            .args
+            .inputs
+            .values()
+            .zip(accounts.input_mints.iter().zip(accounts.inputs.iter()))
+            .chain(
+                self.args
+                    .outputs
+                    .values()
+                    .zip(accounts.output_mints.iter().zip(accounts.outputs.iter())),
+            );
+        for (token, (mint, token_account)) in tokens {
+            // escrow accounts are owned by the transform, minted outputs have the transform
+            // as their mint authority, burnt inputs are never held by the transform
+            let authority_type = match token.mode {
+                TokenMode::Escrow => AuthorityType::AccountOwner,
+                TokenMode::Mint => AuthorityType::MintTokens,
+                TokenMode::Burn => continue,
+            };
+            set_authority(
+                accounts.token_programs.program_of(mint)?,
+                token_account,
+                accounts.transform.key,
+                authority_type,
+                accounts.creator,
+                &[],
             )?;
         }
 
@@ -1,5 +1,9 @@
-use crate::state::{Token, TransformMetadata};
+use crate::state::{
+    BuyoutConfig, ClosePolicy, DirectionPolicy, ExecutionLimits, FeeRecipient, Gate, IndexConfig,
+    PackConfig, PermitSigner, Token, TransformMetadata,
+};
 use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
@@ -24,16 +28,33 @@ pub struct CreateTransform<'a> {
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 pub struct CreateTransformArgs {
     pub metadata: TransformMetadata,
-    pub fee: Option<u64>,
+    pub forward: DirectionPolicy,
+    pub reverse: DirectionPolicy,
+    pub fee_recipients: Vec<FeeRecipient>,
     pub inputs: BTreeMap<Pubkey, Token>,
     pub outputs: BTreeMap<Pubkey, Token>,
+    pub close_policy: ClosePolicy,
+    pub limits: ExecutionLimits,
+    pub gate: Option<Gate>,
+    pub allowlist: Option<[u8; 32]>,
+    pub permit_signer: Option<PermitSigner>,
+    pub pack: Option<PackConfig>,
+    pub buyout: Option<BuyoutConfig>,
+    pub index: Option<IndexConfig>,
+}
+
+impl CreateTransformArgs {
+    pub fn has_fee(&self) -> bool {
+        self.forward.fee.is_some() || self.reverse.fee.is_some()
+    }
 }
 
 // deserialize accounts instead of storing as account info
 #[derive(Debug)]
 struct CreateTransformAccounts<'a> {
     system_program: &'a AccountInfo<'a>,
-    token_program: &'a AccountInfo<'a>,
+    token_programs: TokenPrograms<'a>,
+    rent_sysvar: &'a AccountInfo<'a>,
     tokenitis: &'a AccountInfo<'a>,
     transform: &'a AccountInfo<'a>,
     creator: &'a AccountInfo<'a>,
@@ -41,6 +62,10 @@ struct CreateTransformAccounts<'a> {
     inputs: Vec<&'a AccountInfo<'a>>,
     output_mints: Vec<&'a AccountInfo<'a>>,
     outputs: Vec<&'a AccountInfo<'a>>,
+    // protocol fee accounts, one per input, created if they do not exist yet
+    treasuries: Vec<&'a AccountInfo<'a>>,
+    // only passed if the transform has a fee, one per input
+    fee_vaults: Vec<&'a AccountInfo<'a>>,
 }
 
 impl<'a> CreateTransform<'a> {
@@ -52,7 +77,8 @@ impl<'a> CreateTransform<'a> {
         let accounts = &mut accounts.iter();
 
         let system_program = next_account_info(accounts)?;
-        let token_program = next_account_info(accounts)?;
+        let token_programs = TokenPrograms::new(accounts)?;
+        let rent_sysvar = next_account_info(accounts)?;
         let tokenitis = next_account_info(accounts)?;
         let transform = next_account_info(accounts)?;
         let creator = next_account_info(accounts)?;
@@ -77,11 +103,24 @@ impl<'a> CreateTransform<'a> {
             outputs.push(next_account_info(accounts)?)
         }
 
+        let mut treasuries: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..(args.inputs.len()) {
+            treasuries.push(next_account_info(accounts)?)
+        }
+
+        let mut fee_vaults: Vec<&AccountInfo> = Vec::new();
+        if args.has_fee() {
+            for _ in 0..(args.inputs.len()) {
+                fee_vaults.push(next_account_info(accounts)?)
+            }
+        }
+
         Ok(CreateTransform {
             program_id,
             accounts: CreateTransformAccounts {
                 system_program,
-                token_program,
+                token_programs,
+                rent_sysvar,
                 tokenitis,
                 transform,
                 creator,
@@ -89,6 +128,8 @@ impl<'a> CreateTransform<'a> {
                 inputs,
                 output_mints,
                 outputs,
+                treasuries,
+                fee_vaults,
             },
             args,
         })
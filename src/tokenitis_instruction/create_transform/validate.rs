@@ -1,46 +1,37 @@
-use crate::state::{Token, Tokenitis};
+use crate::state::{Token, TokenMode, Tokenitis};
 
 use borsh::BorshDeserialize;
 
 use crate::errors;
+use crate::errors::TokenitisError;
 use crate::tokenitis_instruction::create_transform::CreateTransform;
+use crate::util::token::{
+    is_native_mint, unpack_account, unpack_mint, validate_escrow_extensions,
+    validate_mint_extensions,
+};
+use crate::util::{validate_against_config, validate_metadata, BPS_DENOMINATOR};
 use solana_program::program_option::COption;
-use solana_program::program_pack::Pack;
 use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError, pubkey::Pubkey};
 
-use spl_token::state::{Account, AccountState, Mint};
+use spl_token::state::AccountState;
 
+use std::collections::BTreeSet;
 use std::ops::Index;
 
 impl CreateTransform<'_> {
     pub(crate) fn validate_instruction(&self) -> ProgramResult {
         let args = &self.args;
         let accounts = &self.accounts;
-        if args.metadata.name.len() as u64 > errors::MAX_STRING_SIZE {
-            msg!(
-                "transform name too large - expected - {}, got - {}",
-                errors::MAX_STRING_SIZE,
-                args.metadata.name.len()
-            );
-            return Err(ProgramError::InvalidInstructionData);
-        }
-
-        if args.metadata.image.len() as u64 > errors::MAX_STRING_SIZE {
-            msg!(
-                "transform image too large - expected - {}, got - {}",
-                errors::MAX_STRING_SIZE,
-                args.metadata.image.len()
-            );
-            return Err(ProgramError::InvalidInstructionData);
-        }
 
         if *accounts.system_program.key != solana_program::system_program::id() {
             msg!("invalid system program account");
             return Err(ProgramError::InvalidArgument);
         }
 
-        if *accounts.token_program.key != spl_token::id() {
-            msg!("invalid token program account");
+        accounts.token_programs.validate()?;
+
+        if *accounts.rent_sysvar.key != solana_program::sysvar::rent::id() {
+            msg!("invalid rent sysvar account");
             return Err(ProgramError::InvalidArgument);
         }
 
@@ -54,14 +45,25 @@ impl CreateTransform<'_> {
             return Err(ProgramError::InvalidArgument);
         }
 
-        let transform_num = if accounts.tokenitis.data_len() > 0 {
-            Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?.num_transforms
-        } else {
-            0
-        };
+        if accounts.tokenitis.data_len() == 0 {
+            msg!("tokenitis has not been initialized");
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let tokenitis_state = Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?;
+        validate_metadata(&args.metadata, &tokenitis_state.config)?;
+        validate_against_config(
+            &tokenitis_state.config,
+            &[&args.forward, &args.reverse],
+            &args.inputs,
+            &args.outputs,
+        )?;
 
+        let transform_num = tokenitis_state
+            .num_transforms
+            .checked_add(1)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
         let (transform_addr, _) =
-            Tokenitis::find_transform_address(&self.program_id, transform_num + 1);
+            Tokenitis::find_transform_address(&self.program_id, transform_num);
         if *accounts.transform.key != transform_addr {
             msg!(
                 "invalid transform account, expected - {}, got - {}",
@@ -71,13 +73,259 @@ impl CreateTransform<'_> {
             return Err(ProgramError::InvalidArgument);
         }
 
-        if let Some(fee) = args.fee {
-            if !(1..=10).contains(&fee) {
-                msg!("invalid fee amount - fee has to be between 1 and 10");
+        for (i, mint) in args.inputs.keys().enumerate() {
+            let (treasury_addr, _) = Tokenitis::find_treasury_address(&self.program_id, mint);
+            let treasury = accounts.treasuries.index(i);
+            if *treasury.key != treasury_addr {
+                msg!(
+                    "invalid treasury at index - {}, expected - {}, got - {}",
+                    i,
+                    treasury_addr,
+                    treasury.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        for (i, mint) in args
+            .inputs
+            .keys()
+            .enumerate()
+            .take(accounts.fee_vaults.len())
+        {
+            let (fee_vault_addr, _) =
+                Tokenitis::find_fee_vault_address(&self.program_id, transform_num, mint);
+            let fee_vault = accounts.fee_vaults.index(i);
+            if *fee_vault.key != fee_vault_addr {
+                msg!(
+                    "invalid fee vault at index - {}, expected - {}, got - {}",
+                    i,
+                    fee_vault_addr,
+                    fee_vault.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        if !args.forward.enabled && !args.reverse.enabled {
+            msg!("transform has to be enabled in at least one direction");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        for (direction, policy) in [("forward", &args.forward), ("reverse", &args.reverse)] {
+            if let (Some(start), Some(end)) =
+                (policy.start_unix_timestamp, policy.end_unix_timestamp)
+            {
+                if start >= end {
+                    msg!(
+                        "invalid {} window - start - {} has to be before end - {}",
+                        direction,
+                        start,
+                        end
+                    );
+                    return Err(ProgramError::InvalidArgument);
+                }
+            }
+
+            for (mint, amount) in policy.amounts.iter() {
+                if !args.inputs.contains_key(mint) && !args.outputs.contains_key(mint) {
+                    msg!(
+                        "invalid {} amount - mint - {} is not an input or output",
+                        direction,
+                        mint
+                    );
+                    return Err(ProgramError::InvalidArgument);
+                }
+                if *amount == 0 {
+                    msg!(
+                        "invalid {} amount - mint - {} has amount 0",
+                        direction,
+                        mint
+                    );
+                    return Err(ProgramError::InvalidArgument);
+                }
+            }
+        }
+
+        let limits = [
+            ("max total executions", args.limits.max_total_executions),
+            (
+                "max executions per wallet",
+                args.limits.max_executions_per_wallet,
+            ),
+            ("cooldown slots", args.limits.cooldown_slots),
+        ];
+        for (name, limit) in limits {
+            if limit == Some(0) {
+                msg!("invalid limit - {} has to be greater than 0", name);
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        if let Some(gate) = &args.gate {
+            if gate.min_amount == 0 {
+                msg!("invalid gate - min amount has to be greater than 0");
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        if let Some(pack) = &args.pack {
+            // picked outputs differ between wallets so they cannot be returned for the inputs
+            if args.reverse.enabled {
+                msg!("pack transforms cannot be reversed");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if pack.picks == 0 || pack.picks > errors::MAX_PACK_PICKS {
+                msg!(
+                    "invalid pack - picks have to be between 1 and {}, got - {}",
+                    errors::MAX_PACK_PICKS,
+                    pack.picks
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+            if !pack.weights.keys().eq(args.outputs.keys()) {
+                msg!("invalid pack - every output has to have a weight");
+                return Err(ProgramError::InvalidArgument);
+            }
+            for (mint, weight) in pack.weights.iter() {
+                if *weight == 0 {
+                    msg!("invalid pack - output mint - {} has weight 0", mint);
+                    return Err(ProgramError::InvalidArgument);
+                }
+            }
+            pack.weights
+                .values()
+                .try_fold(0u64, |total, weight| total.checked_add(*weight))
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+        }
+
+        if let Some(buyout) = &args.buyout {
+            let escrowed_input = args.inputs.len() == 1
+                && args
+                    .inputs
+                    .values()
+                    .all(|token| token.mode == TokenMode::Escrow);
+            if !escrowed_input || args.outputs.len() != 1 {
+                msg!("invalid buyout - transform has to escrow a single input for a single output");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if args.pack.is_some() {
+                msg!("invalid buyout - pack transforms cannot be bought out");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if buyout.reserve_price == 0 {
+                msg!("invalid buyout - reserve price has to be greater than 0");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if buyout.duration <= 0 {
+                msg!("invalid buyout - duration has to be greater than 0");
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        if let Some(index) = &args.index {
+            if args
+                .inputs
+                .values()
+                .any(|token| token.mode != TokenMode::Escrow)
+            {
+                msg!("invalid index - every input has to be escrowed");
+                return Err(ProgramError::InvalidArgument);
+            }
+            // escrow has to hold exactly the input amount for every outstanding unit
+            let overridden = args.inputs.keys().any(|mint| {
+                args.forward.amounts.contains_key(mint) || args.reverse.amounts.contains_key(mint)
+            });
+            if overridden {
+                msg!("invalid index - input amounts cannot differ between directions");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if args.pack.is_some() || args.buyout.is_some() {
+                msg!("invalid index - index transforms cannot be packs or bought out");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if index.max_change_bps as u64 > BPS_DENOMINATOR
+                || index.nav_tolerance_bps as u64 > BPS_DENOMINATOR
+            {
+                msg!(
+                    "invalid index - max change and nav tolerance cannot exceed {} bps",
+                    BPS_DENOMINATOR
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+            if index.price_authority == *accounts.creator.key
+                || Some(index.price_authority) == index.rebalancer
+            {
+                msg!("invalid index - price authority cannot be the creator or the rebalancer");
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        if !args.fee_recipients.is_empty() {
+            if args.fee_recipients.len() > errors::MAX_FEE_RECIPIENTS {
+                msg!(
+                    "too many fee recipients - expected - {}, got - {}",
+                    errors::MAX_FEE_RECIPIENTS,
+                    args.fee_recipients.len()
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let mut owners: BTreeSet<Pubkey> = BTreeSet::new();
+            let mut total_share: u64 = 0;
+            for recipient in args.fee_recipients.iter() {
+                if recipient.share_bps == 0 {
+                    msg!("fee recipient - {} has no share", recipient.owner);
+                    return Err(ProgramError::InvalidArgument);
+                }
+                if !owners.insert(recipient.owner) {
+                    msg!("fee recipient - {} is duplicated", recipient.owner);
+                    return Err(ProgramError::InvalidArgument);
+                }
+                total_share = total_share
+                    .checked_add(recipient.share_bps as u64)
+                    .ok_or(TokenitisError::ArithmeticOverflow)?;
+            }
+            if total_share != BPS_DENOMINATOR {
+                msg!(
+                    "fee recipient shares have to add up to {} bps, got - {}",
+                    BPS_DENOMINATOR,
+                    total_share
+                );
                 return Err(ProgramError::InvalidArgument);
             }
         }
 
+        if args.reverse.enabled {
+            if args
+                .inputs
+                .values()
+                .any(|token| token.mode == TokenMode::Burn)
+            {
+                msg!("transform burns its inputs and cannot be reversed");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // reverse cannot return more inputs or take back less outputs than forward,
+            // otherwise it would drain the escrow of other holders
+            for (mint, token) in args.inputs.iter() {
+                let forward = args.forward.amounts.get(mint).unwrap_or(&token.amount);
+                let reverse = args.reverse.amounts.get(mint).unwrap_or(&token.amount);
+                if reverse > forward {
+                    msg!("invalid reverse amount - input mint - {} returns {} but forward only takes {}", mint, reverse, forward);
+                    return Err(ProgramError::InvalidArgument);
+                }
+            }
+            for (mint, token) in args.outputs.iter() {
+                let forward = args.forward.amounts.get(mint).unwrap_or(&token.amount);
+                let reverse = args.reverse.amounts.get(mint).unwrap_or(&token.amount);
+                if reverse < forward {
+                    msg!("invalid reverse amount - output mint - {} takes back {} but forward gives {}", mint, reverse, forward);
+                    return Err(ProgramError::InvalidArgument);
+                }
+            }
+        }
+
         let mut inputs = args
             .inputs
             .clone()
@@ -92,7 +340,67 @@ impl CreateTransform<'_> {
                 msg!("input information does not match at index - {}, expected - ({},{}), got - ({},{})",i,mint,token.account,mint_account.key,token_account.key);
                 return Err(ProgramError::InvalidInstructionData);
             }
-            let token_account_info = Account::unpack(&**token_account.data.borrow())?;
+            if *mint_account.owner != token.token_program.id() {
+                msg!(
+                    "input mint at index - {} is not owned by the token program - {}",
+                    i,
+                    token.token_program.id()
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+            let mint_info = unpack_mint(mint_account)?;
+            if !mint_info.is_initialized {
+                msg!("input mint at index - {} is not initialized", i);
+                return Err(ProgramError::InvalidArgument);
+            }
+            if mint_info.freeze_authority != COption::None {
+                msg!("input mint at index - {} has a freeze authority", i);
+                return Err(ProgramError::InvalidArgument);
+            }
+            if mint_info.decimals != token.decimals {
+                msg!(
+                    "input mint at index - {} has {} decimals, expected - {}",
+                    i,
+                    mint_info.decimals,
+                    token.decimals
+                );
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            // wrapped sol cannot be burnt
+            if is_native_mint(mint) && token.mode != TokenMode::Escrow {
+                msg!(
+                    "input at index - {} is native sol and can only be escrowed",
+                    i
+                );
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            match token.mode {
+                TokenMode::Escrow => {}
+                TokenMode::Burn => {
+                    if *token_account.key != *mint {
+                        msg!(
+                            "input at index - {} is burnt and needs no escrow, expected program account to be mint - {}, got - {}",
+                            i,
+                            mint,
+                            token_account.key
+                        );
+                        return Err(ProgramError::InvalidInstructionData);
+                    }
+                    continue;
+                }
+                TokenMode::Mint => {
+                    msg!("input at index - {} cannot be minted", i);
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+            }
+
+            // burnt inputs are never held, escrowed inputs have to be movable by the transform
+            validate_mint_extensions(mint_account)?;
+            validate_escrow_extensions(token_account)?;
+
+            let token_account_info = unpack_account(token_account)?;
             if *mint_account.key != token_account_info.mint {
                 msg!("input token account does not match mint at index - {}, token - {}, expected - {}, got - {}",i,token_account.key,token_account_info.mint,mint_account.key);
                 return Err(ProgramError::InvalidAccountData);
@@ -112,16 +420,6 @@ impl CreateTransform<'_> {
                 msg!("input token account at index - {} has a delegate", i);
                 return Err(ProgramError::InvalidArgument);
             }
-
-            let mint_info = Mint::unpack(&**mint_account.data.borrow())?;
-            if !mint_info.is_initialized {
-                msg!("input mint at index - {} is not initialized", i);
-                return Err(ProgramError::InvalidArgument);
-            }
-            if mint_info.freeze_authority != COption::None {
-                msg!("input mint at index - {} has a freeze authority", i);
-                return Err(ProgramError::InvalidArgument);
-            }
         }
 
         let mut outputs = args
@@ -139,7 +437,15 @@ impl CreateTransform<'_> {
                 return Err(ProgramError::InvalidInstructionData);
             }
 
-            let mint_info = Mint::unpack(&**mint_account.data.borrow())?;
+            if *mint_account.owner != token.token_program.id() {
+                msg!(
+                    "output mint at index - {} is not owned by the token program - {}",
+                    i,
+                    token.token_program.id()
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+            let mint_info = unpack_mint(mint_account)?;
             if !mint_info.is_initialized {
                 msg!("output mint at index - {} is not initialized", i);
                 return Err(ProgramError::InvalidArgument);
@@ -148,15 +454,54 @@ impl CreateTransform<'_> {
                 msg!("output mint at index - {} has a freeze authority", i);
                 return Err(ProgramError::InvalidArgument);
             }
+            if mint_info.decimals != token.decimals {
+                msg!(
+                    "output mint at index - {} has {} decimals, expected - {}",
+                    i,
+                    mint_info.decimals,
+                    token.decimals
+                );
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            if token.mode == TokenMode::Burn {
+                msg!("output at index - {} cannot be burnt", i);
+                return Err(ProgramError::InvalidInstructionData);
+            }
 
-            //TODO uncomment this
+            if token.mode == TokenMode::Mint {
+                if *token_account.key != *mint {
+                    msg!(
+                        "output at index - {} is minted, expected program account to be mint - {}, got - {}",
+                        i,
+                        mint,
+                        token_account.key
+                    );
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                if mint_info.mint_authority != COption::Some(*accounts.creator.key) {
+                    msg!(
+                        "output mint at index - {} does not have creator as mint authority",
+                        i
+                    );
+                    return Err(ProgramError::InvalidArgument);
+                }
+                if mint_info.supply != 0 {
+                    msg!("output mint at index - {} has already been minted", i);
+                    return Err(ProgramError::InvalidArgument);
+                }
+                continue;
+            }
+
+            if mint_info.mint_authority != COption::None {
+                msg!("output mint at index - {} has a mint authority", i);
+                return Err(ProgramError::InvalidArgument);
+            }
 
-            // if mint_info.mint_authority != COption::None {
-            //     msg!("output mint at index - {} has a mint authority", i);
-            //     return Err(ProgramError::InvalidArgument);
-            // }
+            validate_mint_extensions(mint_account)?;
+            validate_escrow_extensions(token_account)?;
 
-            let token_account_info = Account::unpack(&**token_account.data.borrow())?;
+            let token_account_info = unpack_account(token_account)?;
             if *mint_account.key != token_account_info.mint {
                 msg!("output token account does not match mint at index - {}, token - {}, expected - {}, got - {}",i,token_account.key,token_account_info.mint,mint_account.key);
                 return Err(ProgramError::InvalidAccountData);
@@ -180,7 +525,8 @@ impl CreateTransform<'_> {
                 return Err(ProgramError::InvalidArgument);
             }
 
-            if token_account_info.amount != mint_info.supply {
+            // wrapped sol has no supply, the escrow is funded with lamports instead
+            if !is_native_mint(mint) && token_account_info.amount != mint_info.supply {
                 msg!(
                     "output token account at index - {} does not have entire supply",
                     i
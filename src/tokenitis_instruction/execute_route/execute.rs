@@ -0,0 +1,34 @@
+use crate::errors::TokenitisError;
+use crate::tokenitis_instruction::execute_route::ExecuteRoute;
+use crate::util::token::unpack_account;
+
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+
+impl ExecuteRoute<'_> {
+    // Execute every hop in order, intermediate tokens stay in the caller accounts
+    // and only the output of the whole route is checked
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &mut self.accounts;
+
+        let output_before = unpack_account(accounts.output)?.amount;
+
+        for hop in accounts.hops.iter_mut() {
+            hop.validate_instruction()?;
+            hop.execute_instruction()?;
+        }
+
+        let output_after = unpack_account(accounts.output)?.amount;
+        let output = output_after.saturating_sub(output_before);
+        if output < self.args.min_output {
+            msg!(
+                "route output - {} is below the minimum - {}",
+                output,
+                self.args.min_output
+            );
+            return Err(TokenitisError::RouteOutputTooLow.into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use crate::tokenitis_instruction::execute_transform::{
+    Direction, ExecuteTransform, ExecuteTransformArgs,
+};
+use crate::tokenitis_instruction::TokenitisInstruction;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct ExecuteRoute<'a> {
+    program_id: Pubkey,
+    accounts: ExecuteRouteAccounts<'a>,
+    args: ExecuteRouteArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct ExecuteRouteArgs {
+    pub hops: Vec<RouteHop>,
+    // minimum amount the route has to add to the output account
+    pub min_output: u64,
+}
+
+// hops are executed without allowlist proofs or permits
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct RouteHop {
+    pub transform_id: u64,
+    pub direction: Direction,
+    pub quantity: u64,
+}
+
+impl RouteHop {
+    pub fn args(&self) -> ExecuteTransformArgs {
+        ExecuteTransformArgs {
+            direction: self.direction.clone(),
+            quantity: self.quantity,
+            allowlist_proof: None,
+            permit: None,
+        }
+    }
+}
+
+// accounts of every hop in order as they are passed to execute transform without hook accounts,
+// followed by the caller token account the min output is checked against and the hook accounts
+struct ExecuteRouteAccounts<'a> {
+    hops: Vec<ExecuteTransform<'a>>,
+    output: &'a AccountInfo<'a>,
+}
+
+impl<'a> ExecuteRoute<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: ExecuteRouteArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let mut hops: Vec<ExecuteTransform> = Vec::new();
+        for hop in args.hops.iter() {
+            hops.push(ExecuteTransform::from_accounts(
+                program_id,
+                accounts,
+                hop.args(),
+            )?)
+        }
+
+        let output = next_account_info(accounts)?;
+
+        // remaining accounts are transfer hook accounts, shared by every hop
+        let hook_accounts: Vec<&AccountInfo> = accounts.collect();
+        for hop in hops.iter_mut() {
+            hop.set_hook_accounts(hook_accounts.clone());
+        }
+
+        Ok(ExecuteRoute {
+            program_id,
+            accounts: ExecuteRouteAccounts { hops, output },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for ExecuteRoute<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,76 @@
+use crate::state::{Tokenitis, Transform};
+use crate::tokenitis_instruction::execute_route::ExecuteRoute;
+use crate::util::token::unpack_account;
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl ExecuteRoute<'_> {
+    // hops are validated one by one right before they are executed, since a hop
+    // can depend on the balances left by the previous hop
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let caller = match accounts.hops.first() {
+            Some(hop) => hop.caller(),
+            None => {
+                msg!("route has to have at least one hop");
+                return Err(ProgramError::InvalidArgument);
+            }
+        };
+
+        for (i, (hop, hop_args)) in accounts.hops.iter().zip(self.args.hops.iter()).enumerate() {
+            if hop.caller().key != caller.key {
+                msg!(
+                    "invalid hop at index - {}, every hop has to be executed by the same caller",
+                    i
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let transform_state = Transform::deserialize(&mut &**hop.transform().data.borrow())?;
+            let (transform_addr, _) =
+                Tokenitis::find_transform_address(&self.program_id, hop_args.transform_id);
+            if *hop.transform().key != transform_addr {
+                msg!(
+                    "invalid hop at index - {}, expected transform - {} with id - {}, got - {}",
+                    i,
+                    transform_addr,
+                    hop_args.transform_id,
+                    hop.transform().key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // pack outputs are only paid out on reveal, so nothing can be routed through them
+            if transform_state.pack.is_some() {
+                msg!(
+                    "invalid hop at index - {}, pack transforms cannot be routed",
+                    i
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // hops carry no allowlist proofs or permits, so gated transforms cannot be routed
+            if transform_state.allowlist.is_some() || transform_state.permit_signer.is_some() {
+                msg!(
+                    "invalid hop at index - {}, allowlisted or permit transforms cannot be routed",
+                    i
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        let output_info = unpack_account(accounts.output)?;
+        if output_info.owner != *caller.key {
+            msg!(
+                "invalid output account, unexpected owner, expected - {}, got - {}",
+                caller.key,
+                output_info.owner
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+}
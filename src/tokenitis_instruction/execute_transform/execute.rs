@@ -1,144 +1,363 @@
-use crate::state::{Tokenitis, Transform};
+use crate::errors::TokenitisError;
+use crate::state::{PackCommitment, PermitNonce, TokenMode, Tokenitis, Transform, TransformWallet};
 use crate::tokenitis_instruction::execute_transform::{
     Direction, ExecuteTransform, ExecuteTransformAccounts,
 };
 
-use crate::util::calculate_fee;
-use borsh::BorshDeserialize;
-use solana_program::program_pack::Pack;
-use solana_program::{
-    account_info::AccountInfo,
-    entrypoint::ProgramResult,
-    program::{invoke, invoke_signed},
-    program_error::ProgramError,
-};
-use spl_token::state::Account;
-use std::ops::{Index};
+use crate::util::token::{gross_amount, net_amount};
+use crate::util::{burn, calculate_fee, create_pda, mint_to, scale_amount, transfer};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use std::collections::BTreeMap;
+use std::ops::Index;
 
 impl ExecuteTransform<'_> {
     // Transfer funds from caller's input token accounts to smart contract
     // and retrieve funds from smart contract to caller's output token account
     pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
         let accounts = &self.accounts;
+        let direction = &self.args.direction;
 
         let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
-        let (transform_addr, nonce) =
-            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        let (_, nonce) = Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        let transform_seed = Tokenitis::transform_seed(transform_state.id);
+        let signer_seeds: &[&[u8]] = &[transform_seed.as_slice(), &[nonce]];
+        let policy = transform_state.policy(direction);
+        let tokenitis_state = Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?;
+        let protocol_fee = tokenitis_state.protocol_fee();
+        let mut fees_collected = transform_state.fees_collected.clone();
+        let mut outstanding_outputs = transform_state.outstanding_outputs.clone();
 
-        if self.args.direction == Direction::Forward {
-            collect_fees(accounts, transform_state.clone())?;
+        // inputs and outputs are iterated in the same sorted order as the accounts,
+        // transfers into program accounts include the transfer fee of the mint
+        for (i, (mint, token)) in transform_state.inputs.iter().enumerate() {
+            let mint_account = *accounts.input_mints.index(i);
+            let caller_account = *accounts.caller_inputs.index(i);
+            let program_account = *accounts.inputs.index(i);
+            let amount = scale_amount(
+                transform_state.amount(direction, mint, token),
+                self.args.quantity,
+            )?;
+            let fee_amount = match &policy.fee {
+                Some(fee) => calculate_fee(amount, fee)?,
+                None => 0,
+            };
+            let protocol_fee_amount = calculate_fee(amount, &protocol_fee)?;
+            if protocol_fee_amount != 0 {
+                collect_fee(
+                    accounts,
+                    i,
+                    token.decimals,
+                    direction,
+                    accounts.treasuries.index(i),
+                    protocol_fee_amount,
+                    signer_seeds,
+                )?;
+            }
+            if fee_amount != 0 {
+                let received = collect_fee(
+                    accounts,
+                    i,
+                    token.decimals,
+                    direction,
+                    accounts.fee_vaults.index(i),
+                    fee_amount,
+                    signer_seeds,
+                )?;
+                let fees_collected = fees_collected.entry(*mint).or_insert(0);
+                *fees_collected = fees_collected
+                    .checked_add(received)
+                    .ok_or(TokenitisError::ArithmeticOverflow)?;
+            }
+
+            match (token.mode, direction) {
+                (TokenMode::Burn, Direction::Forward) => burn(
+                    &accounts.token_programs,
+                    caller_account,
+                    mint_account,
+                    accounts.caller,
+                    amount,
+                    token.decimals,
+                    &[],
+                )?,
+                (TokenMode::Escrow, Direction::Forward) => transfer(
+                    &accounts.token_programs,
+                    caller_account,
+                    mint_account,
+                    program_account,
+                    accounts.caller,
+                    gross_amount(mint_account, amount)?,
+                    token.decimals,
+                    &[],
+                )?,
+                (TokenMode::Escrow, Direction::Reverse) => transfer(
+                    &accounts.token_programs,
+                    program_account,
+                    mint_account,
+                    caller_account,
+                    accounts.transform,
+                    amount
+                        .checked_sub(fee_amount)
+                        .and_then(|amount| amount.checked_sub(protocol_fee_amount))
+                        .ok_or(TokenitisError::ArithmeticOverflow)?,
+                    token.decimals,
+                    &[signer_seeds],
+                )?,
+                _ => return Err(ProgramError::InvalidArgument),
+            }
         }
 
-        let mut transfer_params: Vec<(&AccountInfo, &AccountInfo, &AccountInfo, u64)> = Vec::new();
-        for i in 0..accounts.caller_inputs.len() {
-            let src = *accounts.caller_inputs.index(i);
-            let dst = *accounts.inputs.index(i);
-            let authority = accounts.caller;
-            let mint = Account::unpack(&**src.data.borrow())?.mint;
-            let amount = transform_state
-                .inputs
-                .get(&mint)
-                .ok_or(ProgramError::InvalidArgument)?
-                .amount;
-            transfer_params.push((src, dst, authority, amount));
+        // output accounts are not passed for pack transforms
+        for (i, (mint, token)) in transform_state
+            .outputs
+            .iter()
+            .enumerate()
+            .take(accounts.outputs.len())
+        {
+            let mint_account = *accounts.output_mints.index(i);
+            let caller_account = *accounts.caller_outputs.index(i);
+            let program_account = *accounts.outputs.index(i);
+            let amount = scale_amount(
+                transform_state.amount(direction, mint, token),
+                self.args.quantity,
+            )?;
+            match (token.mode, direction) {
+                (TokenMode::Escrow, Direction::Forward) => transfer(
+                    &accounts.token_programs,
+                    program_account,
+                    mint_account,
+                    caller_account,
+                    accounts.transform,
+                    amount,
+                    token.decimals,
+                    &[signer_seeds],
+                )?,
+                (TokenMode::Escrow, Direction::Reverse) => transfer(
+                    &accounts.token_programs,
+                    caller_account,
+                    mint_account,
+                    program_account,
+                    accounts.caller,
+                    gross_amount(mint_account, amount)?,
+                    token.decimals,
+                    &[],
+                )?,
+                (TokenMode::Mint, Direction::Forward) => mint_to(
+                    &accounts.token_programs,
+                    mint_account,
+                    caller_account,
+                    accounts.transform,
+                    amount,
+                    token.decimals,
+                    &[signer_seeds],
+                )?,
+                (TokenMode::Mint, Direction::Reverse) => burn(
+                    &accounts.token_programs,
+                    caller_account,
+                    mint_account,
+                    accounts.caller,
+                    amount,
+                    token.decimals,
+                    &[],
+                )?,
+                (TokenMode::Burn, _) => return Err(ProgramError::InvalidArgument),
+            }
+
+            // a spread between the directions can return more than was paid out
+            let outstanding = outstanding_outputs.entry(*mint).or_insert(0);
+            *outstanding = match direction {
+                Direction::Forward => outstanding
+                    .checked_add(amount)
+                    .ok_or(TokenitisError::ArithmeticOverflow)?,
+                Direction::Reverse => outstanding.saturating_sub(amount),
+            };
+        }
+
+        // escrow of an index transform holds the inputs of every outstanding unit
+        let mut index = transform_state.index.clone();
+        if let Some(index) = index.as_mut() {
+            index.outstanding_units = match direction {
+                Direction::Forward => index.outstanding_units.checked_add(self.args.quantity),
+                Direction::Reverse => index.outstanding_units.checked_sub(self.args.quantity),
+            }
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
         }
 
-        for i in 0..accounts.caller_outputs.len() {
-            let src = *accounts.outputs.index(i);
-            let dst = *accounts.caller_outputs.index(i);
-            let authority = accounts.transform;
-            let mint = Account::unpack(&**src.data.borrow())?.mint;
-            let amount = transform_state
-                .outputs
-                .get(&mint)
-                .ok_or(ProgramError::InvalidArgument)?
-                .amount;
-            transfer_params.push((src, dst, authority, amount));
+        let mut total_executions = transform_state.total_executions;
+        if *direction == Direction::Forward {
+            total_executions = total_executions
+                .checked_add(self.args.quantity)
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+            if transform_state.tracks_wallets() {
+                self.record_wallet_execution(transform_state.id)?;
+            }
         }
 
-        for (mut src, mut dst, mut authority, amount) in transfer_params {
-            if self.args.direction == Direction::Reverse {
-                std::mem::swap(&mut src, &mut dst);
-                if authority.key.eq(&transform_addr) {
-                    authority = accounts.caller;
-                } else {
-                    authority = accounts.transform;
+        let mut pending_packs = transform_state.pending_packs;
+        let mut reserved_outputs = transform_state.reserved_outputs.clone();
+        if let (Some(pack), Some(pack_commitment)) =
+            (&transform_state.pack, accounts.pack_commitment)
+        {
+            pending_packs = pending_packs
+                .checked_add(1)
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+            let mut inputs = BTreeMap::new();
+            for (mint, token) in transform_state.inputs.iter() {
+                if token.mode == TokenMode::Escrow {
+                    let amount = scale_amount(
+                        transform_state.amount(direction, mint, token),
+                        self.args.quantity,
+                    )?;
+                    inputs.insert(*mint, amount);
                 }
             }
+            let commitment = PackCommitment {
+                transform_id: transform_state.id,
+                picks: pack
+                    .picks
+                    .checked_mul(self.args.quantity)
+                    .ok_or(TokenitisError::ArithmeticOverflow)?,
+                commit_slot: Clock::get()?.slot,
+                amounts: transform_state
+                    .outputs
+                    .iter()
+                    .map(|(mint, token)| (*mint, transform_state.amount(direction, mint, token)))
+                    .collect(),
+                inputs,
+            };
+            // escrowed outputs are held back until the pack is revealed or cancelled
+            for (mint, amount) in transform_state.pack_reservation(&commitment)? {
+                let reserved = reserved_outputs.entry(mint).or_insert(0);
+                *reserved = reserved
+                    .checked_add(amount)
+                    .ok_or(TokenitisError::ArithmeticOverflow)?;
+            }
+            create_pda(
+                &self.program_id,
+                commitment.try_to_vec()?.len(),
+                accounts.caller,
+                pack_commitment,
+                accounts.system_program,
+                &[
+                    Tokenitis::pack_seed(transform_state.id).as_slice(),
+                    accounts.caller.key.as_ref(),
+                ],
+            )?;
+            commitment.serialize(&mut &mut pack_commitment.data.borrow_mut()[..])?;
+        }
 
-            let transfer_ix = spl_token::instruction::transfer(
-                accounts.token_program.key,
-                src.key,
-                dst.key,
-                authority.key,
-                &[authority.key],
-                amount,
+        // permit nonce account marks the nonce as used until the permit expires
+        if let (Some(permit), Some(permit_nonce)) = (&self.args.permit, accounts.permit_nonce) {
+            let permit_nonce_state = PermitNonce {
+                transform_id: transform_state.id,
+                nonce: permit.nonce,
+                expiry: permit.expiry,
+                payer: *accounts.caller.key,
+            };
+            create_pda(
+                &self.program_id,
+                permit_nonce_state.try_to_vec()?.len(),
+                accounts.caller,
+                permit_nonce,
+                accounts.system_program,
+                &[
+                    Tokenitis::permit_seed(transform_state.id).as_slice(),
+                    &permit.nonce.to_le_bytes(),
+                ],
             )?;
-            if !authority.key.eq(&transform_addr) {
-                invoke(
-                    &transfer_ix,
-                    &[
-                        src.clone(),
-                        dst.clone(),
-                        authority.clone(),
-                        accounts.token_program.clone(),
-                    ],
-                )?;
-            } else {
-                invoke_signed(
-                    &transfer_ix,
-                    &[
-                        src.clone(),
-                        dst.clone(),
-                        authority.clone(),
-                        accounts.token_program.clone(),
-                    ],
-                    &[&[
-                        Tokenitis::transform_seed(transform_state.id).as_slice(),
-                        &[nonce],
-                    ]],
-                )?;
-            }
+            permit_nonce_state.serialize(&mut &mut permit_nonce.data.borrow_mut()[..])?;
         }
 
+        let transform_state = Transform {
+            fees_collected,
+            outstanding_outputs,
+            total_executions,
+            index,
+            pending_packs,
+            reserved_outputs,
+            ..transform_state
+        };
+        transform_state.serialize(&mut &mut accounts.transform.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    // creates the wallet account on the first execution, caller pays the rent
+    fn record_wallet_execution(&self, transform_id: u64) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut wallet_state = if accounts.wallet.data_len() > 0 {
+            TransformWallet::deserialize(&mut &**accounts.wallet.data.borrow())?
+        } else {
+            let wallet_state = TransformWallet::default();
+            create_pda(
+                &self.program_id,
+                wallet_state.try_to_vec()?.len(),
+                accounts.caller,
+                accounts.wallet,
+                accounts.system_program,
+                &[
+                    Tokenitis::wallet_seed(transform_id).as_slice(),
+                    accounts.caller.key.as_ref(),
+                ],
+            )?;
+            wallet_state
+        };
+
+        wallet_state.executions = wallet_state
+            .executions
+            .checked_add(self.args.quantity)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        wallet_state.last_execution_slot = Clock::get()?.slot;
+        wallet_state.serialize(&mut &mut accounts.wallet.data.borrow_mut()[..])?;
+
         Ok(())
     }
 }
 
-fn collect_fees(accounts: &ExecuteTransformAccounts, transform_state: Transform) -> ProgramResult {
-    if let Some(fee_percent) = transform_state.fee {
-        for i in 0..accounts.caller_inputs.len() {
-            let src = *accounts.caller_inputs.index(i);
-            let dst = *accounts.fee_accounts.index(i);
-
-            let mint = Account::unpack(&**src.data.borrow())?.mint;
-            let amount = transform_state
-                .inputs
-                .get(&mint)
-                .ok_or(ProgramError::InvalidArgument)?
-                .amount;
-            let fee_amount = calculate_fee(amount, fee_percent);
-            if fee_amount != 0 {
-                let ix = spl_token::instruction::transfer(
-                    accounts.token_program.key,
-                    src.key,
-                    dst.key,
-                    accounts.caller.key,
-                    &[accounts.caller.key],
-                    fee_amount,
-                )?;
-                invoke(
-                    &ix,
-                    &[
-                        src.clone(),
-                        dst.clone(),
-                        accounts.caller.clone(),
-                        accounts.token_program.clone(),
-                    ],
-                )?;
-            }
+// fees are paid by the caller on forward and withheld from the returned inputs on reverse,
+// returns the amount received by the destination
+fn collect_fee<'a>(
+    accounts: &ExecuteTransformAccounts<'a>,
+    input_index: usize,
+    decimals: u8,
+    direction: &Direction,
+    destination: &AccountInfo<'a>,
+    fee_amount: u64,
+    signer_seeds: &[&[u8]],
+) -> Result<u64, ProgramError> {
+    let mint = accounts.input_mints.index(input_index);
+    match direction {
+        Direction::Forward => {
+            transfer(
+                &accounts.token_programs,
+                accounts.caller_inputs.index(input_index),
+                mint,
+                destination,
+                accounts.caller,
+                gross_amount(mint, fee_amount)?,
+                decimals,
+                &[],
+            )?;
+            Ok(fee_amount)
+        }
+        // the escrow only holds the inputs, the transfer fee comes out of the fee
+        Direction::Reverse => {
+            transfer(
+                &accounts.token_programs,
+                accounts.inputs.index(input_index),
+                mint,
+                destination,
+                accounts.transform,
+                fee_amount,
+                decimals,
+                &[signer_seeds],
+            )?;
+            net_amount(mint, fee_amount)
         }
     }
-
-    Ok(())
 }
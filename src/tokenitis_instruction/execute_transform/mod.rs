@@ -1,5 +1,6 @@
 use crate::state::Transform;
 use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use solana_program::{
@@ -8,6 +9,7 @@ use solana_program::{
     program_error::ProgramError,
     pubkey::Pubkey,
 };
+use std::slice::Iter;
 
 pub mod execute;
 pub mod validate;
@@ -21,6 +23,50 @@ pub struct ExecuteTransform<'a> {
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 pub struct ExecuteTransformArgs {
     pub direction: Direction,
+    // number of units to transform, every token amount is scaled by this
+    pub quantity: u64,
+    // required to execute forward if the transform has an allowlist
+    pub allowlist_proof: Option<AllowlistProof>,
+    // required if the transform has a permit signer
+    pub permit: Option<PermitArgs>,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct PermitArgs {
+    pub expiry: i64,
+    pub nonce: u64,
+}
+
+// message signed by the permit signer, verified through an ed25519 program instruction
+// placed right before the execute instruction
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct Permit {
+    pub transform_id: u64,
+    pub caller: Pubkey,
+    pub direction: Direction,
+    pub quantity: u64,
+    pub expiry: i64,
+    pub nonce: u64,
+}
+
+impl Permit {
+    pub fn new(transform_id: u64, caller: Pubkey, args: &ExecuteTransformArgs) -> Option<Self> {
+        let permit = args.permit.as_ref()?;
+        Some(Permit {
+            transform_id,
+            caller,
+            direction: args.direction.clone(),
+            quantity: args.quantity,
+            expiry: permit.expiry,
+            nonce: permit.nonce,
+        })
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct AllowlistProof {
+    pub max_allocation: u64,
+    pub proof: Vec<[u8; 32]>,
 }
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
@@ -30,14 +76,31 @@ pub enum Direction {
 }
 
 struct ExecuteTransformAccounts<'a> {
-    token_program: &'a AccountInfo<'a>,
+    system_program: &'a AccountInfo<'a>,
+    token_programs: TokenPrograms<'a>,
+    tokenitis: &'a AccountInfo<'a>,
     transform: &'a AccountInfo<'a>,
     caller: &'a AccountInfo<'a>,
+    // per wallet execution state of the caller, created on first execution
+    wallet: &'a AccountInfo<'a>,
+    input_mints: Vec<&'a AccountInfo<'a>>,
     caller_inputs: Vec<&'a AccountInfo<'a>>,
     inputs: Vec<&'a AccountInfo<'a>>,
+    // not passed for pack transforms, outputs are paid out on reveal
+    output_mints: Vec<&'a AccountInfo<'a>>,
     caller_outputs: Vec<&'a AccountInfo<'a>>,
     outputs: Vec<&'a AccountInfo<'a>>,
-    fee_accounts: Vec<&'a AccountInfo<'a>>,
+    treasuries: Vec<&'a AccountInfo<'a>>,
+    fee_vaults: Vec<&'a AccountInfo<'a>>,
+    // token account of the caller holding the gate mint, only passed for gated transforms
+    gate: Option<&'a AccountInfo<'a>>,
+    // only passed for transforms with a permit signer
+    instructions_sysvar: Option<&'a AccountInfo<'a>>,
+    permit_nonce: Option<&'a AccountInfo<'a>>,
+    // only passed for pack transforms
+    pack_commitment: Option<&'a AccountInfo<'a>>,
+    // program accounts of the outputs of a pack, they have to cover every pick
+    pack_outputs: Vec<&'a AccountInfo<'a>>,
 }
 
 impl<'a> ExecuteTransform<'a> {
@@ -47,13 +110,34 @@ impl<'a> ExecuteTransform<'a> {
         args: ExecuteTransformArgs,
     ) -> Result<Self, ProgramError> {
         let accounts = &mut accounts.iter();
+        let mut execute_transform = Self::from_accounts(program_id, accounts, args)?;
+        // remaining accounts are transfer hook accounts
+        execute_transform.set_hook_accounts(accounts.collect());
+
+        Ok(execute_transform)
+    }
 
-        let token_program = next_account_info(accounts)?;
+    // parses the accounts of a single execution, routes parse every hop from the same accounts
+    // and pass the hook accounts after every hop
+    pub(crate) fn from_accounts(
+        program_id: Pubkey,
+        accounts: &mut Iter<'a, AccountInfo<'a>>,
+        args: ExecuteTransformArgs,
+    ) -> Result<Self, ProgramError> {
+        let system_program = next_account_info(accounts)?;
+        let token_programs = TokenPrograms::new(accounts)?;
+        let tokenitis = next_account_info(accounts)?;
         let transform = next_account_info(accounts)?;
         let caller = next_account_info(accounts)?;
+        let wallet = next_account_info(accounts)?;
 
         let transform_state = Transform::deserialize(&mut &**transform.data.borrow())?;
 
+        let mut input_mints: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            input_mints.push(next_account_info(accounts)?)
+        }
+
         let mut caller_inputs: Vec<&AccountInfo> = Vec::new();
         for _ in 0..transform_state.inputs.len() {
             caller_inputs.push(next_account_info(accounts)?)
@@ -64,38 +148,101 @@ impl<'a> ExecuteTransform<'a> {
             inputs.push(next_account_info(accounts)?)
         }
 
+        let num_outputs = match transform_state.pack {
+            Some(_) => 0,
+            None => transform_state.outputs.len(),
+        };
+
+        let mut output_mints: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..num_outputs {
+            output_mints.push(next_account_info(accounts)?)
+        }
+
         let mut caller_outputs: Vec<&AccountInfo> = Vec::new();
-        for _ in 0..transform_state.outputs.len() {
+        for _ in 0..num_outputs {
             caller_outputs.push(next_account_info(accounts)?)
         }
 
         let mut outputs: Vec<&AccountInfo> = Vec::new();
-        for _ in 0..transform_state.outputs.len() {
+        for _ in 0..num_outputs {
             outputs.push(next_account_info(accounts)?)
         }
 
-        let mut fee_accounts: Vec<&AccountInfo> = Vec::new();
-        if transform_state.fee.is_some() {
+        let mut treasuries: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            treasuries.push(next_account_info(accounts)?)
+        }
+
+        let mut fee_vaults: Vec<&AccountInfo> = Vec::new();
+        if transform_state.has_fee() {
             for _ in 0..transform_state.inputs.len() {
-                fee_accounts.push(next_account_info(accounts)?)
+                fee_vaults.push(next_account_info(accounts)?)
+            }
+        }
+
+        let gate = match transform_state.gate {
+            Some(_) => Some(next_account_info(accounts)?),
+            None => None,
+        };
+
+        let (instructions_sysvar, permit_nonce) = match transform_state.permit_signer {
+            Some(_) => (
+                Some(next_account_info(accounts)?),
+                Some(next_account_info(accounts)?),
+            ),
+            None => (None, None),
+        };
+
+        let pack_commitment = match transform_state.pack {
+            Some(_) => Some(next_account_info(accounts)?),
+            None => None,
+        };
+
+        let mut pack_outputs: Vec<&AccountInfo> = Vec::new();
+        if transform_state.pack.is_some() {
+            for _ in 0..transform_state.outputs.len() {
+                pack_outputs.push(next_account_info(accounts)?)
             }
         }
 
         Ok(ExecuteTransform {
             program_id,
             accounts: ExecuteTransformAccounts {
-                token_program,
+                system_program,
+                token_programs,
+                tokenitis,
                 transform,
                 caller,
+                wallet,
+                input_mints,
                 caller_inputs,
                 inputs,
+                output_mints,
                 caller_outputs,
                 outputs,
-                fee_accounts,
+                treasuries,
+                fee_vaults,
+                gate,
+                instructions_sysvar,
+                permit_nonce,
+                pack_commitment,
+                pack_outputs,
             },
             args,
         })
     }
+
+    pub(crate) fn transform(&self) -> &'a AccountInfo<'a> {
+        self.accounts.transform
+    }
+
+    pub(crate) fn caller(&self) -> &'a AccountInfo<'a> {
+        self.accounts.caller
+    }
+
+    pub(crate) fn set_hook_accounts(&mut self, hook_accounts: Vec<&'a AccountInfo<'a>>) {
+        self.accounts.token_programs.hook_accounts = hook_accounts;
+    }
 }
 
 impl TokenitisInstruction for ExecuteTransform<'_> {
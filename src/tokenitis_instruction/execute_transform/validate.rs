@@ -1,18 +1,41 @@
-use crate::state::{Token, Tokenitis, Transform};
-use crate::tokenitis_instruction::execute_transform::ExecuteTransform;
+use crate::errors::TokenitisError;
+use crate::errors::MAX_PACK_PICKS;
+use crate::state::{Token, TokenMode, Tokenitis, Transform, TransformStatus, TransformWallet};
+use crate::tokenitis_instruction::execute_transform::{Direction, ExecuteTransform, Permit};
+use crate::util::token::unpack_account;
+use crate::util::{
+    merkle, validate_against_config, validate_ed25519_signature, validate_not_paused,
+};
 
-use borsh::BorshDeserialize;
-use solana_program::program_pack::{IsInitialized, Pack};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::clock::Clock;
+use solana_program::sysvar::Sysvar;
 use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError, pubkey::Pubkey};
-use spl_token::state::Account;
 use std::ops::Index;
 
 impl ExecuteTransform<'_> {
     pub(crate) fn validate_instruction(&self) -> ProgramResult {
         let accounts = &self.accounts;
 
-        if *accounts.token_program.key != spl_token::id() {
-            msg!("invalid token program account");
+        if *accounts.system_program.key != solana_program::system_program::id() {
+            msg!("invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        accounts.token_programs.validate()?;
+
+        if self.args.quantity == 0 {
+            msg!("invalid quantity - quantity has to be greater than 0");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (tokenitis_addr, _) = Tokenitis::find_tokenitis_address(&self.program_id);
+        if *accounts.tokenitis.key != tokenitis_addr {
+            msg!(
+                "invalid tokenitis account, expected - {}, got - {}",
+                tokenitis_addr,
+                accounts.tokenitis.key
+            );
             return Err(ProgramError::InvalidArgument);
         }
 
@@ -24,6 +47,87 @@ impl ExecuteTransform<'_> {
             return Err(ProgramError::InvalidArgument);
         }
 
+        match (transform_state.status, &self.args.direction) {
+            (TransformStatus::Active, _) | (TransformStatus::ReverseOnly, Direction::Reverse) => {}
+            (status, direction) => {
+                msg!(
+                    "transform status is {:?}, cannot execute {:?}",
+                    status,
+                    direction
+                );
+                return Err(TokenitisError::TransformPaused.into());
+            }
+        }
+
+        let tokenitis_state = Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?;
+        // forward executions are checked against the current config like a new transform,
+        // holders can still reverse out of a transform the config no longer allows
+        validate_not_paused(&tokenitis_state.config)?;
+        if self.args.direction == Direction::Forward {
+            validate_against_config(
+                &tokenitis_state.config,
+                &[&transform_state.forward, &transform_state.reverse],
+                &transform_state.inputs,
+                &transform_state.outputs,
+            )?;
+        }
+
+        let policy = transform_state.policy(&self.args.direction);
+        if !policy.enabled {
+            msg!(
+                "transform cannot be executed in {:?} direction",
+                self.args.direction
+            );
+            return Err(TokenitisError::DirectionDisabled.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(start) = policy.start_unix_timestamp {
+            if now < start {
+                msg!(
+                    "transform cannot be executed in {:?} direction before - {}, now - {}",
+                    self.args.direction,
+                    start,
+                    now
+                );
+                return Err(TokenitisError::TransformNotStarted.into());
+            }
+        }
+        if let Some(end) = policy.end_unix_timestamp {
+            if now >= end {
+                msg!(
+                    "transform cannot be executed in {:?} direction after - {}, now - {}",
+                    self.args.direction,
+                    end,
+                    now
+                );
+                return Err(TokenitisError::TransformEnded.into());
+            }
+        }
+
+        let (wallet_addr, _) = Tokenitis::find_wallet_address(
+            &self.program_id,
+            transform_state.id,
+            accounts.caller.key,
+        );
+        if *accounts.wallet.key != wallet_addr {
+            msg!(
+                "invalid wallet account, expected - {}, got - {}",
+                wallet_addr,
+                accounts.wallet.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if self.args.direction == Direction::Forward {
+            self.validate_limits(&transform_state)?;
+            self.validate_gate(&transform_state)?;
+            self.validate_allowlist(&transform_state)?;
+        }
+        self.validate_permit(&transform_state)?;
+        self.validate_pack_commitment(&transform_state)?;
+
+        let has_fee = transform_state.has_fee();
         let mut inputs: Vec<(Pubkey, Token)> = transform_state
             .inputs
             .into_iter()
@@ -31,6 +135,17 @@ impl ExecuteTransform<'_> {
         inputs.sort();
         for i in 0..inputs.len() {
             let (mint, token) = inputs.index(i);
+            let input_mint = accounts.input_mints.index(i);
+            if *input_mint.key != *mint {
+                msg!(
+                    "invalid input mint at index - {}, expected - {}, got - {}",
+                    i,
+                    mint,
+                    input_mint.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
             let caller_input_account = accounts.caller_inputs.index(i);
             let input_account = accounts.inputs.index(i);
             if *input_account.key != token.account {
@@ -43,7 +158,7 @@ impl ExecuteTransform<'_> {
                 return Err(ProgramError::InvalidArgument);
             }
 
-            let caller_input_account_info = Account::unpack(&**caller_input_account.data.borrow())?;
+            let caller_input_account_info = unpack_account(caller_input_account)?;
             if caller_input_account_info.mint != *mint {
                 msg!("invalid input at index - {}, unexpected mint of caller_input, expected - {}, got - {}",i, mint,caller_input_account_info.mint);
                 return Err(ProgramError::InvalidArgument);
@@ -54,20 +169,24 @@ impl ExecuteTransform<'_> {
                 return Err(ProgramError::InvalidArgument);
             }
 
-            if transform_state.fee.is_some() {
-                let fee_account = accounts.fee_accounts.index(i);
-                let expected_fee_account =
-                    spl_associated_token_account::get_associated_token_address(
-                        &transform_state.creator,
-                        mint,
-                    );
-                if *fee_account.key != expected_fee_account {
-                    msg!("invalid input at index - {}, unexpected fee account, expected - {}, got - {}",i, expected_fee_account,fee_account.key);
-                    return Err(ProgramError::InvalidArgument);
-                }
-                let fee_account = spl_token::state::Account::unpack(&**fee_account.data.borrow())?;
-                if !fee_account.is_initialized() {
-                    msg!("invalid input at index - {}, unexpected fee account, expected initialized, got uninitialized",i);
+            let treasury = accounts.treasuries.index(i);
+            let (expected_treasury, _) = Tokenitis::find_treasury_address(&self.program_id, mint);
+            if *treasury.key != expected_treasury {
+                msg!(
+                    "invalid input at index - {}, unexpected treasury, expected - {}, got - {}",
+                    i,
+                    expected_treasury,
+                    treasury.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            if has_fee {
+                let fee_vault = accounts.fee_vaults.index(i);
+                let (expected_fee_vault, _) =
+                    Tokenitis::find_fee_vault_address(&self.program_id, transform_state.id, mint);
+                if *fee_vault.key != expected_fee_vault {
+                    msg!("invalid input at index - {}, unexpected fee vault, expected - {}, got - {}",i, expected_fee_vault,fee_vault.key);
                     return Err(ProgramError::InvalidArgument);
                 }
             }
@@ -78,8 +197,19 @@ impl ExecuteTransform<'_> {
             .into_iter()
             .collect::<Vec<(Pubkey, Token)>>();
         outputs.sort();
-        for i in 0..outputs.len() {
+        for i in 0..accounts.outputs.len() {
             let (mint, token) = outputs.index(i);
+            let output_mint = accounts.output_mints.index(i);
+            if *output_mint.key != *mint {
+                msg!(
+                    "invalid output mint at index - {}, expected - {}, got - {}",
+                    i,
+                    mint,
+                    output_mint.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
             let caller_output_account = accounts.caller_outputs.index(i);
             let output_account = accounts.outputs.index(i);
             if *output_account.key != token.account {
@@ -92,8 +222,7 @@ impl ExecuteTransform<'_> {
                 return Err(ProgramError::InvalidArgument);
             }
 
-            let caller_output_account_info =
-                Account::unpack(&**caller_output_account.data.borrow())?;
+            let caller_output_account_info = unpack_account(caller_output_account)?;
             if caller_output_account_info.mint != *mint {
                 msg!("invalid output at index - {}, unexpected mint of caller_output, expected - {}, got - {}",i, mint,caller_output_account_info.mint);
                 return Err(ProgramError::InvalidArgument);
@@ -107,4 +236,288 @@ impl ExecuteTransform<'_> {
 
         Ok(())
     }
+
+    fn validate_gate(&self, transform_state: &Transform) -> ProgramResult {
+        let (gate, gate_account) = match (&transform_state.gate, self.accounts.gate) {
+            (Some(gate), Some(gate_account)) => (gate, gate_account),
+            _ => return Ok(()),
+        };
+
+        // anyone can create an account with the layout of a token account holding the gate mint
+        if *gate_account.owner != gate.token_program.id() {
+            msg!(
+                "invalid gate account, unexpected account owner, expected - {}, got - {}",
+                gate.token_program.id(),
+                gate_account.owner
+            );
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let gate_account_info = unpack_account(gate_account)?;
+        if gate_account_info.owner != *self.accounts.caller.key {
+            msg!(
+                "invalid gate account, unexpected owner, expected - {}, got - {}",
+                self.accounts.caller.key,
+                gate_account_info.owner
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if gate_account_info.mint != gate.mint {
+            msg!(
+                "invalid gate account, unexpected mint, expected - {}, got - {}",
+                gate.mint,
+                gate_account_info.mint
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if gate_account_info.amount < gate.min_amount {
+            msg!(
+                "caller has to hold at least {} of mint - {}, got - {}",
+                gate.min_amount,
+                gate.mint,
+                gate_account_info.amount
+            );
+            return Err(TokenitisError::GateNotMet.into());
+        }
+
+        Ok(())
+    }
+
+    fn validate_pack_commitment(&self, transform_state: &Transform) -> ProgramResult {
+        let pack_commitment = match self.accounts.pack_commitment {
+            Some(pack_commitment) => pack_commitment,
+            None => return Ok(()),
+        };
+
+        let (pack_commitment_addr, _) = Tokenitis::find_pack_address(
+            &self.program_id,
+            transform_state.id,
+            self.accounts.caller.key,
+        );
+        if *pack_commitment.key != pack_commitment_addr {
+            msg!(
+                "invalid pack commitment account, expected - {}, got - {}",
+                pack_commitment_addr,
+                pack_commitment.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if pack_commitment.data_len() > 0 {
+            msg!("previous pack has to be revealed before executing again");
+            return Err(TokenitisError::PackPending.into());
+        }
+
+        let pack = transform_state
+            .pack
+            .as_ref()
+            .ok_or(ProgramError::InvalidArgument)?;
+        let picks = pack
+            .picks
+            .checked_mul(self.args.quantity)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        if picks > MAX_PACK_PICKS {
+            msg!(
+                "invalid quantity - pack can have at most {} picks, got - {}",
+                MAX_PACK_PICKS,
+                picks
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // every pick could be the same output, so each escrow has to cover all of them
+        for (i, (mint, token)) in transform_state.outputs.iter().enumerate() {
+            let output_account = self.accounts.pack_outputs.index(i);
+            if *output_account.key != token.account {
+                msg!(
+                    "invalid pack output at index - {}, expected - {}, got - {}",
+                    i,
+                    token.account,
+                    output_account.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+            if token.mode != TokenMode::Escrow {
+                continue;
+            }
+
+            let required = transform_state
+                .amount(&Direction::Forward, mint, token)
+                .checked_mul(picks)
+                .and_then(|amount| {
+                    amount.checked_add(
+                        transform_state
+                            .reserved_outputs
+                            .get(mint)
+                            .copied()
+                            .unwrap_or(0),
+                    )
+                })
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+            let available = unpack_account(output_account)?.amount;
+            if available < required {
+                msg!(
+                    "output - {} cannot cover every pick, required - {}, available - {}",
+                    mint,
+                    required,
+                    available
+                );
+                return Err(TokenitisError::PackOutputsUnavailable.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_permit(&self, transform_state: &Transform) -> ProgramResult {
+        let accounts = &self.accounts;
+        let (signer, instructions_sysvar, permit_nonce) = match (
+            transform_state.permit_signer(),
+            accounts.instructions_sysvar,
+            accounts.permit_nonce,
+        ) {
+            (Some(signer), Some(instructions_sysvar), Some(permit_nonce)) => {
+                (signer, instructions_sysvar, permit_nonce)
+            }
+            _ => return Ok(()),
+        };
+
+        let permit = match Permit::new(transform_state.id, *accounts.caller.key, &self.args) {
+            Some(permit) => permit,
+            None => {
+                msg!("transform requires a permit");
+                return Err(TokenitisError::InvalidPermit.into());
+            }
+        };
+
+        if *instructions_sysvar.key != solana_program::sysvar::instructions::id() {
+            msg!("invalid instructions sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        if now > permit.expiry {
+            msg!("permit expired at - {}, now - {}", permit.expiry, now);
+            return Err(TokenitisError::PermitExpired.into());
+        }
+
+        let (permit_nonce_addr, _) =
+            Tokenitis::find_permit_address(&self.program_id, transform_state.id, permit.nonce);
+        if *permit_nonce.key != permit_nonce_addr {
+            msg!(
+                "invalid permit nonce account, expected - {}, got - {}",
+                permit_nonce_addr,
+                permit_nonce.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if permit_nonce.data_len() > 0 {
+            msg!("permit nonce - {} has already been used", permit.nonce);
+            return Err(TokenitisError::PermitUsed.into());
+        }
+
+        validate_ed25519_signature(instructions_sysvar, &signer, &permit.try_to_vec()?)
+    }
+
+    fn validate_allowlist(&self, transform_state: &Transform) -> ProgramResult {
+        let accounts = &self.accounts;
+        let root = match &transform_state.allowlist {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+
+        let allowlist_proof = match &self.args.allowlist_proof {
+            Some(allowlist_proof) => allowlist_proof,
+            None => {
+                msg!("transform has an allowlist, proof is required");
+                return Err(TokenitisError::NotAllowlisted.into());
+            }
+        };
+        let leaf = merkle::allowlist_leaf(accounts.caller.key, allowlist_proof.max_allocation);
+        if !merkle::verify_proof(leaf, &allowlist_proof.proof, root) {
+            msg!(
+                "wallet - {} with allocation - {} is not on the allowlist",
+                accounts.caller.key,
+                allowlist_proof.max_allocation
+            );
+            return Err(TokenitisError::NotAllowlisted.into());
+        }
+
+        let claimed = if accounts.wallet.data_len() > 0 {
+            TransformWallet::deserialize(&mut &**accounts.wallet.data.borrow())?.executions
+        } else {
+            0
+        };
+        let claimed_after = claimed
+            .checked_add(self.args.quantity)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        if claimed_after > allowlist_proof.max_allocation {
+            msg!(
+                "allowlist allocation exceeded - allocation - {}, claimed - {}",
+                allowlist_proof.max_allocation,
+                claimed
+            );
+            return Err(TokenitisError::AllocationExceeded.into());
+        }
+
+        Ok(())
+    }
+
+    fn validate_limits(&self, transform_state: &Transform) -> ProgramResult {
+        let accounts = &self.accounts;
+        let limits = &transform_state.limits;
+
+        let total_executions = transform_state
+            .total_executions
+            .checked_add(self.args.quantity)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        if let Some(max_total_executions) = limits.max_total_executions {
+            if total_executions > max_total_executions {
+                msg!(
+                    "transform execution limit reached - limit - {}, executed - {}",
+                    max_total_executions,
+                    transform_state.total_executions
+                );
+                return Err(TokenitisError::ExecutionLimitReached.into());
+            }
+        }
+
+        // wallet is created on its first execution
+        let wallet_state = if accounts.wallet.data_len() == 0 {
+            TransformWallet::default()
+        } else {
+            TransformWallet::deserialize(&mut &**accounts.wallet.data.borrow())?
+        };
+
+        let wallet_executions = wallet_state
+            .executions
+            .checked_add(self.args.quantity)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        if let Some(max_executions_per_wallet) = limits.max_executions_per_wallet {
+            if wallet_executions > max_executions_per_wallet {
+                msg!(
+                    "wallet execution limit reached - limit - {}, executed - {}",
+                    max_executions_per_wallet,
+                    wallet_state.executions
+                );
+                return Err(TokenitisError::WalletLimitReached.into());
+            }
+        }
+
+        if let Some(cooldown_slots) = limits.cooldown_slots {
+            let slot = Clock::get()?.slot;
+            let next_slot = wallet_state
+                .last_execution_slot
+                .checked_add(cooldown_slots)
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+            if wallet_state.executions > 0 && slot < next_slot {
+                msg!(
+                    "wallet is cooling down - can execute again at slot - {}, now - {}",
+                    next_slot,
+                    slot
+                );
+                return Err(TokenitisError::CooldownActive.into());
+            }
+        }
+
+        Ok(())
+    }
 }
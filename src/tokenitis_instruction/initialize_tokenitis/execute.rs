@@ -0,0 +1,43 @@
+use crate::state::Tokenitis;
+use crate::tokenitis_instruction::initialize_tokenitis::InitializeTokenitis;
+use crate::util::{create_pda, resize_pda};
+
+use borsh::BorshSerialize;
+use solana_program::entrypoint::ProgramResult;
+
+impl InitializeTokenitis<'_> {
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        // the count of a legacy account is kept so that new transforms are not created at
+        // the addresses of transforms created before the upgrade
+        let legacy = Tokenitis::unpack_legacy(&accounts.tokenitis.data.borrow());
+        let tokenitis = Tokenitis {
+            num_transforms: legacy.unwrap_or(0),
+            admin: self.args.admin,
+            protocol_fee_bps: 0,
+            config: self.args.config.clone(),
+        };
+        let space = tokenitis.try_to_vec()?.len();
+        if legacy.is_some() {
+            resize_pda(
+                space,
+                accounts.payer,
+                accounts.tokenitis,
+                accounts.system_program,
+            )?;
+        } else {
+            create_pda(
+                &self.program_id,
+                space,
+                accounts.payer,
+                accounts.tokenitis,
+                accounts.system_program,
+                &[Tokenitis::tokenitis_seed().as_slice()],
+            )?;
+        }
+        tokenitis.serialize(&mut &mut accounts.tokenitis.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use crate::state::TokenitisConfig;
+use crate::tokenitis_instruction::TokenitisInstruction;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct InitializeTokenitis<'a> {
+    program_id: Pubkey,
+    accounts: InitializeTokenitisAccounts<'a>,
+    args: InitializeTokenitisArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct InitializeTokenitisArgs {
+    pub admin: Pubkey,
+    pub config: TokenitisConfig,
+}
+
+struct InitializeTokenitisAccounts<'a> {
+    system_program: &'a AccountInfo<'a>,
+    tokenitis: &'a AccountInfo<'a>,
+    // has to be the upgrade authority of the program so tokenitis cannot be initialized by
+    // anyone else after deployment
+    payer: &'a AccountInfo<'a>,
+    program_data: &'a AccountInfo<'a>,
+}
+
+impl<'a> InitializeTokenitis<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: InitializeTokenitisArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let system_program = next_account_info(accounts)?;
+        let tokenitis = next_account_info(accounts)?;
+        let payer = next_account_info(accounts)?;
+        let program_data = next_account_info(accounts)?;
+
+        Ok(InitializeTokenitis {
+            program_id,
+            accounts: InitializeTokenitisAccounts {
+                system_program,
+                tokenitis,
+                payer,
+                program_data,
+            },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for InitializeTokenitis<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,82 @@
+use crate::state::Tokenitis;
+use crate::tokenitis_instruction::initialize_tokenitis::InitializeTokenitis;
+use crate::util::validate_config;
+
+use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_program::program_utils::limited_deserialize;
+use solana_program::pubkey::Pubkey;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl InitializeTokenitis<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        if *accounts.system_program.key != solana_program::system_program::id() {
+            msg!("invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (tokenitis_addr, _) = Tokenitis::find_tokenitis_address(&self.program_id);
+        if *accounts.tokenitis.key != tokenitis_addr {
+            msg!(
+                "invalid tokenitis account, expected - {}, got - {}",
+                tokenitis_addr,
+                accounts.tokenitis.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // accounts with a legacy layout are migrated
+        if accounts.tokenitis.data_len() > 0
+            && (*accounts.tokenitis.owner != self.program_id
+                || Tokenitis::unpack_legacy(&accounts.tokenitis.data.borrow()).is_none())
+        {
+            msg!("tokenitis has already been initialized");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        if !accounts.payer.is_signer {
+            msg!("payer has to sign the transaction");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let (program_data_addr, _) = Pubkey::find_program_address(
+            &[self.program_id.as_ref()],
+            &bpf_loader_upgradeable::id(),
+        );
+        if *accounts.program_data.key != program_data_addr
+            || *accounts.program_data.owner != bpf_loader_upgradeable::id()
+        {
+            msg!(
+                "invalid program data account, expected - {}, got - {}",
+                program_data_addr,
+                accounts.program_data.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let upgrade_authority = match limited_deserialize(
+            &accounts.program_data.data.borrow(),
+            accounts.program_data.data_len() as u64,
+        ) {
+            Ok(UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address,
+                ..
+            }) => upgrade_authority_address,
+            _ => {
+                msg!("program data account could not be deserialized");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        };
+        if upgrade_authority != Some(*accounts.payer.key) {
+            msg!(
+                "payer has to be the upgrade authority of the program, expected - {:?}, got - {}",
+                upgrade_authority,
+                accounts.payer.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        validate_config(&self.args.config)
+    }
+}
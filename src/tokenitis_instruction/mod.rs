@@ -1,11 +1,40 @@
 use crate::tokenitis_instruction::create_transform::CreateTransformArgs;
+use crate::tokenitis_instruction::execute_route::ExecuteRouteArgs;
 use crate::tokenitis_instruction::execute_transform::ExecuteTransformArgs;
+use crate::tokenitis_instruction::initialize_tokenitis::InitializeTokenitisArgs;
+use crate::tokenitis_instruction::place_bid::PlaceBidArgs;
+use crate::tokenitis_instruction::propose_creator::ProposeCreatorArgs;
+use crate::tokenitis_instruction::rebalance::RebalanceArgs;
+use crate::tokenitis_instruction::redeem_fractions::RedeemFractionsArgs;
+use crate::tokenitis_instruction::set_protocol_fee::SetProtocolFeeArgs;
+use crate::tokenitis_instruction::set_transform_status::SetTransformStatusArgs;
+use crate::tokenitis_instruction::update_tokenitis_config::UpdateTokenitisConfigArgs;
+use crate::tokenitis_instruction::update_transform::UpdateTransformArgs;
+use crate::tokenitis_instruction::withdraw_protocol_fees::WithdrawProtocolFeesArgs;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::entrypoint::ProgramResult;
 
+pub mod accept_creator;
+pub mod cancel_pack;
+pub mod claim_buyout;
+pub mod close_permit;
+pub mod close_transform;
 pub mod create_transform;
+pub mod execute_route;
 pub mod execute_transform;
+pub mod initialize_tokenitis;
+pub mod place_bid;
+pub mod propose_creator;
+pub mod rebalance;
+pub mod redeem_fractions;
+pub mod reveal_pack;
+pub mod set_protocol_fee;
+pub mod set_transform_status;
+pub mod update_tokenitis_config;
+pub mod update_transform;
+pub mod withdraw_fees;
+pub mod withdraw_protocol_fees;
 
 pub trait TokenitisInstruction {
     fn validate(&self) -> ProgramResult;
@@ -16,4 +45,22 @@ pub trait TokenitisInstruction {
 pub enum TokenitisInstructionType {
     CreateTransform(CreateTransformArgs),
     ExecuteTransform(ExecuteTransformArgs),
+    UpdateTransform(UpdateTransformArgs),
+    CloseTransform,
+    SetTransformStatus(SetTransformStatusArgs),
+    ProposeCreator(ProposeCreatorArgs),
+    AcceptCreator,
+    WithdrawFees,
+    SetProtocolFee(SetProtocolFeeArgs),
+    WithdrawProtocolFees(WithdrawProtocolFeesArgs),
+    InitializeTokenitis(InitializeTokenitisArgs),
+    UpdateTokenitisConfig(UpdateTokenitisConfigArgs),
+    RevealPack,
+    PlaceBid(PlaceBidArgs),
+    ClaimBuyout,
+    RedeemFractions(RedeemFractionsArgs),
+    Rebalance(RebalanceArgs),
+    ExecuteRoute(ExecuteRouteArgs),
+    ClosePermit,
+    CancelPack,
 }
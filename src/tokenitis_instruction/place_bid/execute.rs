@@ -0,0 +1,101 @@
+use crate::errors::TokenitisError;
+use crate::state::{Buyout, Tokenitis, Transform, TransformStatus};
+use crate::tokenitis_instruction::place_bid::PlaceBid;
+use crate::util::token::{gross_amount, unpack_mint};
+use crate::util::{create_pda, create_token_account_pda, transfer};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+
+impl PlaceBid<'_> {
+    // Hold the bid in the buyout vault and refund the previous highest bid,
+    // every bid restarts the timer
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (_, nonce) = Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        let transform_seed = Tokenitis::transform_seed(transform_state.id);
+        let signer_seeds: &[&[u8]] = &[transform_seed.as_slice(), &[nonce]];
+        let buyout_config = transform_state
+            .buyout
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        let quote_decimals = match accounts.previous_bidder_quote {
+            Some(previous_bidder_quote) => {
+                let previous_state = Buyout::deserialize(&mut &**accounts.buyout.data.borrow())?;
+                transfer(
+                    &accounts.token_programs,
+                    accounts.buyout_vault,
+                    accounts.quote_mint,
+                    previous_bidder_quote,
+                    accounts.transform,
+                    previous_state.bid,
+                    previous_state.quote_decimals,
+                    &[signer_seeds],
+                )?;
+                previous_state.quote_decimals
+            }
+            // first bid stops the transform from being executed
+            None => {
+                let buyout_seed = Tokenitis::buyout_seed(transform_state.id);
+                create_pda(
+                    &self.program_id,
+                    Buyout::default().try_to_vec()?.len(),
+                    accounts.bidder,
+                    accounts.buyout,
+                    accounts.system_program,
+                    &[buyout_seed.as_slice()],
+                )?;
+
+                let (_, vault_nonce) =
+                    Tokenitis::find_buyout_vault_address(&self.program_id, transform_state.id);
+                let buyout_vault_seed = Tokenitis::buyout_vault_seed(transform_state.id);
+                create_token_account_pda(
+                    &accounts.token_programs,
+                    accounts.system_program,
+                    accounts.rent_sysvar,
+                    accounts.bidder,
+                    accounts.buyout_vault,
+                    accounts.quote_mint,
+                    accounts.transform,
+                    &[buyout_vault_seed.as_slice(), &[vault_nonce]],
+                )?;
+
+                transform_state.status = TransformStatus::Buyout;
+                transform_state.serialize(&mut &mut accounts.transform.data.borrow_mut()[..])?;
+                unpack_mint(accounts.quote_mint)?.decimals
+            }
+        };
+
+        // vault has to receive the whole bid to pay it out in full
+        transfer(
+            &accounts.token_programs,
+            accounts.bidder_quote,
+            accounts.quote_mint,
+            accounts.buyout_vault,
+            accounts.bidder,
+            gross_amount(accounts.quote_mint, self.args.amount)?,
+            quote_decimals,
+            &[],
+        )?;
+
+        let buyout_state = Buyout {
+            transform_id: transform_state.id,
+            bidder: *accounts.bidder.key,
+            bid: self.args.amount,
+            quote_decimals,
+            end_unix_timestamp: Clock::get()?
+                .unix_timestamp
+                .checked_add(buyout_config.duration)
+                .ok_or(TokenitisError::ArithmeticOverflow)?,
+            ..Buyout::default()
+        };
+        buyout_state.serialize(&mut &mut accounts.buyout.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct PlaceBid<'a> {
+    program_id: Pubkey,
+    accounts: PlaceBidAccounts<'a>,
+    args: PlaceBidArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct PlaceBidArgs {
+    // in the quote mint of the buyout
+    pub amount: u64,
+}
+
+struct PlaceBidAccounts<'a> {
+    system_program: &'a AccountInfo<'a>,
+    token_programs: TokenPrograms<'a>,
+    rent_sysvar: &'a AccountInfo<'a>,
+    transform: &'a AccountInfo<'a>,
+    buyout: &'a AccountInfo<'a>,
+    buyout_vault: &'a AccountInfo<'a>,
+    quote_mint: &'a AccountInfo<'a>,
+    // escrow account of the input that is being bought out
+    input: &'a AccountInfo<'a>,
+    bidder: &'a AccountInfo<'a>,
+    bidder_quote: &'a AccountInfo<'a>,
+    // quote account of the previous bidder that is refunded, only passed after the first bid
+    previous_bidder_quote: Option<&'a AccountInfo<'a>>,
+}
+
+impl<'a> PlaceBid<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: PlaceBidArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let system_program = next_account_info(accounts)?;
+        let mut token_programs = TokenPrograms::new(accounts)?;
+        let rent_sysvar = next_account_info(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let buyout = next_account_info(accounts)?;
+        let buyout_vault = next_account_info(accounts)?;
+        let quote_mint = next_account_info(accounts)?;
+        let input = next_account_info(accounts)?;
+        let bidder = next_account_info(accounts)?;
+        let bidder_quote = next_account_info(accounts)?;
+
+        let previous_bidder_quote = if buyout.data_len() > 0 {
+            Some(next_account_info(accounts)?)
+        } else {
+            None
+        };
+
+        // remaining accounts are transfer hook accounts
+        token_programs.hook_accounts = accounts.collect();
+
+        Ok(PlaceBid {
+            program_id,
+            accounts: PlaceBidAccounts {
+                system_program,
+                token_programs,
+                rent_sysvar,
+                transform,
+                buyout,
+                buyout_vault,
+                quote_mint,
+                input,
+                bidder,
+                bidder_quote,
+                previous_bidder_quote,
+            },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for PlaceBid<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,159 @@
+use crate::errors::TokenitisError;
+use crate::state::{Buyout, Tokenitis, Transform, TransformStatus};
+use crate::tokenitis_instruction::place_bid::PlaceBid;
+use crate::util::token::{unpack_account, validate_mint_extensions};
+
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::sysvar::Sysvar;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl PlaceBid<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        if *accounts.system_program.key != solana_program::system_program::id() {
+            msg!("invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        accounts.token_programs.validate()?;
+
+        if *accounts.rent_sysvar.key != solana_program::sysvar::rent::id() {
+            msg!("invalid rent sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let buyout_config = match transform_state.buyout {
+            Some(buyout_config) => buyout_config,
+            None => {
+                msg!("transform cannot be bought out");
+                return Err(ProgramError::InvalidArgument);
+            }
+        };
+
+        let (buyout_addr, _) = Tokenitis::find_buyout_address(&self.program_id, transform_state.id);
+        if *accounts.buyout.key != buyout_addr {
+            msg!(
+                "invalid buyout account, expected - {}, got - {}",
+                buyout_addr,
+                accounts.buyout.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (buyout_vault_addr, _) =
+            Tokenitis::find_buyout_vault_address(&self.program_id, transform_state.id);
+        if *accounts.buyout_vault.key != buyout_vault_addr {
+            msg!(
+                "invalid buyout vault account, expected - {}, got - {}",
+                buyout_vault_addr,
+                accounts.buyout_vault.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if *accounts.quote_mint.key != buyout_config.quote_mint {
+            msg!(
+                "invalid quote mint, expected - {}, got - {}",
+                buyout_config.quote_mint,
+                accounts.quote_mint.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        // bids are held by the transform until the buyout is claimed
+        validate_mint_extensions(accounts.quote_mint)?;
+
+        // input is only held by the transform once it has been fractionalized
+        let input = transform_state
+            .inputs
+            .values()
+            .next()
+            .ok_or(ProgramError::InvalidArgument)?;
+        if *accounts.input.key != input.account {
+            msg!(
+                "invalid input account, expected - {}, got - {}",
+                input.account,
+                accounts.input.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if unpack_account(accounts.input)?.amount == 0 {
+            msg!("transform does not hold its input, nothing to buy out");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if !accounts.bidder.is_signer {
+            msg!("bidder has to sign the transaction");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let bidder_quote_info = unpack_account(accounts.bidder_quote)?;
+        if bidder_quote_info.mint != buyout_config.quote_mint {
+            msg!(
+                "invalid bidder quote account, unexpected mint, expected - {}, got - {}",
+                buyout_config.quote_mint,
+                bidder_quote_info.mint
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if self.args.amount < buyout_config.reserve_price {
+            msg!(
+                "bid - {} is below the reserve price - {}",
+                self.args.amount,
+                buyout_config.reserve_price
+            );
+            return Err(TokenitisError::BidTooLow.into());
+        }
+
+        if let Some(previous_bidder_quote) = accounts.previous_bidder_quote {
+            let buyout_state = Buyout::deserialize(&mut &**accounts.buyout.data.borrow())?;
+            let now = Clock::get()?.unix_timestamp;
+            if now >= buyout_state.end_unix_timestamp {
+                msg!(
+                    "buyout ended at - {}, now - {}",
+                    buyout_state.end_unix_timestamp,
+                    now
+                );
+                return Err(TokenitisError::BuyoutEnded.into());
+            }
+
+            if self.args.amount <= buyout_state.bid {
+                msg!(
+                    "bid - {} has to be higher than the highest bid - {}",
+                    self.args.amount,
+                    buyout_state.bid
+                );
+                return Err(TokenitisError::BidTooLow.into());
+            }
+
+            let previous_bidder_quote_info = unpack_account(previous_bidder_quote)?;
+            if previous_bidder_quote_info.owner != buyout_state.bidder {
+                msg!(
+                    "invalid previous bidder quote account, unexpected owner, expected - {}, got - {}",
+                    buyout_state.bidder,
+                    previous_bidder_quote_info.owner
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+        } else if transform_state.status != TransformStatus::Active {
+            // first bid freezes the transform, paused transforms cannot be bought out
+            msg!(
+                "transform is not active, status - {:?}",
+                transform_state.status
+            );
+            return Err(TokenitisError::TransformPaused.into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use crate::state::Transform;
+use crate::tokenitis_instruction::propose_creator::ProposeCreator;
+use crate::util::resize_pda;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+
+impl ProposeCreator<'_> {
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        transform_state.pending_creator = self.args.new_creator;
+
+        resize_pda(
+            transform_state.try_to_vec()?.len(),
+            accounts.creator,
+            accounts.transform,
+            accounts.system_program,
+        )?;
+        transform_state.serialize(&mut &mut accounts.transform.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
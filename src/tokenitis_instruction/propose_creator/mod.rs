@@ -0,0 +1,65 @@
+use crate::tokenitis_instruction::TokenitisInstruction;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct ProposeCreator<'a> {
+    program_id: Pubkey,
+    accounts: ProposeCreatorAccounts<'a>,
+    args: ProposeCreatorArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct ProposeCreatorArgs {
+    // None cancels a pending proposal
+    pub new_creator: Option<Pubkey>,
+}
+
+struct ProposeCreatorAccounts<'a> {
+    system_program: &'a AccountInfo<'a>,
+    transform: &'a AccountInfo<'a>,
+    creator: &'a AccountInfo<'a>,
+}
+
+impl<'a> ProposeCreator<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: ProposeCreatorArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let system_program = next_account_info(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let creator = next_account_info(accounts)?;
+
+        Ok(ProposeCreator {
+            program_id,
+            accounts: ProposeCreatorAccounts {
+                system_program,
+                transform,
+                creator,
+            },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for ProposeCreator<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,29 @@
+use crate::state::{Tokenitis, Transform};
+use crate::tokenitis_instruction::propose_creator::ProposeCreator;
+use crate::util::validate_creator;
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl ProposeCreator<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        if *accounts.system_program.key != solana_program::system_program::id() {
+            msg!("invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        validate_creator(&transform_state, accounts.creator)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use crate::errors::TokenitisError;
+use crate::state::{Tokenitis, Transform};
+use crate::tokenitis_instruction::rebalance::Rebalance;
+use crate::util::token::gross_amount;
+use crate::util::transfer;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use std::ops::Index;
+
+impl Rebalance<'_> {
+    // Swap the difference of every input amount for all outstanding units with the
+    // counterparty and store the new amounts
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (_, nonce) = Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        let transform_seed = Tokenitis::transform_seed(transform_state.id);
+        let signer_seeds: &[&[u8]] = &[transform_seed.as_slice(), &[nonce]];
+        let mut index = transform_state
+            .index
+            .clone()
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        let epoch = Clock::get()?.epoch;
+        if index.epoch != epoch {
+            index.epoch = epoch;
+            index.epoch_amounts = transform_state
+                .inputs
+                .iter()
+                .map(|(mint, token)| (*mint, token.amount))
+                .collect();
+        }
+
+        for (i, (mint, token)) in transform_state.inputs.iter_mut().enumerate() {
+            let amount = *self.args.amounts.index(mint);
+            let difference = ((amount as i128 - token.amount as i128).unsigned_abs() as u64)
+                .checked_mul(index.outstanding_units)
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+            let input_mint = accounts.input_mints.index(i);
+            if difference != 0 {
+                if amount > token.amount {
+                    transfer(
+                        &accounts.token_programs,
+                        accounts.counterparty_inputs.index(i),
+                        input_mint,
+                        accounts.inputs.index(i),
+                        accounts.counterparty,
+                        gross_amount(input_mint, difference)?,
+                        token.decimals,
+                        &[],
+                    )?;
+                } else {
+                    transfer(
+                        &accounts.token_programs,
+                        accounts.inputs.index(i),
+                        input_mint,
+                        accounts.counterparty_inputs.index(i),
+                        accounts.transform,
+                        difference,
+                        token.decimals,
+                        &[signer_seeds],
+                    )?;
+                }
+            }
+            token.amount = amount;
+        }
+
+        transform_state.index = Some(index);
+        transform_state.serialize(&mut &mut accounts.transform.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
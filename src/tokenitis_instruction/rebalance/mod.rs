@@ -0,0 +1,104 @@
+use crate::state::Transform;
+use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::collections::BTreeMap;
+
+pub mod execute;
+pub mod validate;
+
+pub struct Rebalance<'a> {
+    program_id: Pubkey,
+    accounts: RebalanceAccounts<'a>,
+    args: RebalanceArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct RebalanceArgs {
+    // new per unit amount of every input
+    pub amounts: BTreeMap<Pubkey, u64>,
+    // price of every input mint per smallest unit in a common quote, used to check the nav,
+    // the price authority signs for them
+    pub prices: BTreeMap<Pubkey, u64>,
+}
+
+// counterparty deposits inputs that are increased and receives inputs that are decreased
+struct RebalanceAccounts<'a> {
+    token_programs: TokenPrograms<'a>,
+    transform: &'a AccountInfo<'a>,
+    rebalancer: &'a AccountInfo<'a>,
+    counterparty: &'a AccountInfo<'a>,
+    price_authority: &'a AccountInfo<'a>,
+    input_mints: Vec<&'a AccountInfo<'a>>,
+    inputs: Vec<&'a AccountInfo<'a>>,
+    counterparty_inputs: Vec<&'a AccountInfo<'a>>,
+}
+
+impl<'a> Rebalance<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: RebalanceArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let mut token_programs = TokenPrograms::new(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let rebalancer = next_account_info(accounts)?;
+        let counterparty = next_account_info(accounts)?;
+        let price_authority = next_account_info(accounts)?;
+
+        let transform_state = Transform::deserialize(&mut &**transform.data.borrow())?;
+
+        let mut input_mints: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            input_mints.push(next_account_info(accounts)?)
+        }
+
+        let mut inputs: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            inputs.push(next_account_info(accounts)?)
+        }
+
+        let mut counterparty_inputs: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            counterparty_inputs.push(next_account_info(accounts)?)
+        }
+
+        // remaining accounts are transfer hook accounts
+        token_programs.hook_accounts = accounts.collect();
+
+        Ok(Rebalance {
+            program_id,
+            accounts: RebalanceAccounts {
+                token_programs,
+                transform,
+                rebalancer,
+                counterparty,
+                price_authority,
+                input_mints,
+                inputs,
+                counterparty_inputs,
+            },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for Rebalance<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,168 @@
+use crate::errors::TokenitisError;
+use crate::state::{Tokenitis, Transform};
+use crate::tokenitis_instruction::rebalance::Rebalance;
+use crate::util::token::unpack_account;
+use crate::util::BPS_DENOMINATOR;
+
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::sysvar::Sysvar;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+use std::ops::Index;
+
+impl Rebalance<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        accounts.token_programs.validate()?;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let index = match &transform_state.index {
+            Some(index) => index,
+            None => {
+                msg!("transform is not an index");
+                return Err(ProgramError::InvalidArgument);
+            }
+        };
+
+        if *accounts.rebalancer.key != transform_state.creator
+            && Some(*accounts.rebalancer.key) != index.config.rebalancer
+        {
+            msg!(
+                "invalid rebalancer account - {}, has to be the creator or the rebalancer",
+                accounts.rebalancer.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !accounts.rebalancer.is_signer {
+            msg!("rebalancer has to sign the transaction");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !accounts.counterparty.is_signer {
+            msg!("counterparty has to sign the transaction");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        // the rebalancer would profit from any mispricing it trades against itself
+        if *accounts.counterparty.key == *accounts.rebalancer.key {
+            msg!("counterparty cannot be the rebalancer");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if *accounts.price_authority.key != index.config.price_authority {
+            msg!(
+                "invalid price authority account, expected - {}, got - {}",
+                index.config.price_authority,
+                accounts.price_authority.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !accounts.price_authority.is_signer {
+            msg!("price authority has to sign the transaction");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !self.args.amounts.keys().eq(transform_state.inputs.keys())
+            || !self.args.prices.keys().eq(transform_state.inputs.keys())
+        {
+            msg!("invalid rebalance - every input needs exactly one amount and one price");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // changes within an epoch add up against the amounts at the start of the epoch
+        let epoch = Clock::get()?.epoch;
+        let mut old_nav: u128 = 0;
+        let mut new_nav: u128 = 0;
+        for (mint, token) in transform_state.inputs.iter() {
+            let amount = *self.args.amounts.index(mint);
+            if amount == 0 {
+                msg!("invalid rebalance - input mint - {} has amount 0", mint);
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let epoch_amount = if index.epoch == epoch {
+                *index.epoch_amounts.index(mint)
+            } else {
+                token.amount
+            };
+            let change = (amount as i128 - epoch_amount as i128).unsigned_abs();
+            if change * BPS_DENOMINATOR as u128
+                > epoch_amount as u128 * index.config.max_change_bps as u128
+            {
+                msg!(
+                    "input mint - {} can change by at most {} bps per epoch, from - {} to - {}",
+                    mint,
+                    index.config.max_change_bps,
+                    epoch_amount,
+                    amount
+                );
+                return Err(TokenitisError::RebalanceLimitExceeded.into());
+            }
+
+            let price = *self.args.prices.index(mint) as u128;
+            old_nav = old_nav
+                .checked_add(token.amount as u128 * price)
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+            new_nav = new_nav
+                .checked_add(amount as u128 * price)
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+        }
+
+        let min_nav = old_nav
+            .checked_mul((BPS_DENOMINATOR - index.config.nav_tolerance_bps as u64) as u128)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        let scaled_new_nav = new_nav
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        if scaled_new_nav < min_nav {
+            msg!(
+                "nav per unit can drop by at most {} bps, from - {} to - {}",
+                index.config.nav_tolerance_bps,
+                old_nav,
+                new_nav
+            );
+            return Err(TokenitisError::NavDropExceeded.into());
+        }
+
+        for (i, (mint, token)) in transform_state.inputs.iter().enumerate() {
+            let input_mint = accounts.input_mints.index(i);
+            if *input_mint.key != *mint {
+                msg!(
+                    "invalid input mint at index - {}, expected - {}, got - {}",
+                    i,
+                    mint,
+                    input_mint.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let input_account = accounts.inputs.index(i);
+            if *input_account.key != token.account {
+                msg!(
+                    "invalid input at index - {}, unexpected program account, expected - {}, got - {}",
+                    i,
+                    token.account,
+                    input_account.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let counterparty_input_info = unpack_account(accounts.counterparty_inputs.index(i))?;
+            if counterparty_input_info.mint != *mint {
+                msg!("invalid input at index - {}, unexpected mint of counterparty_input, expected - {}, got - {}",i, mint,counterparty_input_info.mint);
+                return Err(ProgramError::InvalidArgument);
+            }
+            if counterparty_input_info.owner != *accounts.counterparty.key {
+                msg!("invalid input at index - {}, unexpected owner of counterparty_input, expected - {}, got - {}",i, accounts.counterparty.key,counterparty_input_info.owner);
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use crate::errors::TokenitisError;
+use crate::state::{Buyout, Tokenitis, Transform};
+use crate::tokenitis_instruction::redeem_fractions::RedeemFractions;
+use crate::util::{burn, transfer};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+
+impl RedeemFractions<'_> {
+    // Burn the fractions of the holder for their pro-rata share of the proceeds,
+    // the last holder to redeem receives any rounding remainder
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (_, nonce) = Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        let transform_seed = Tokenitis::transform_seed(transform_state.id);
+        let signer_seeds: &[&[u8]] = &[transform_seed.as_slice(), &[nonce]];
+
+        let mut buyout_state = Buyout::deserialize(&mut &**accounts.buyout.data.borrow())?;
+        // buyout transforms have a single output, the fractions
+        let fractions = transform_state
+            .outputs
+            .values()
+            .next()
+            .ok_or(ProgramError::InvalidArgument)?;
+        let payout = (buyout_state.proceeds as u128)
+            .checked_mul(self.args.amount as u128)
+            .and_then(|payout| payout.checked_div(buyout_state.outstanding_fractions as u128))
+            .ok_or(TokenitisError::ArithmeticOverflow)? as u64;
+
+        burn(
+            &accounts.token_programs,
+            accounts.holder_fractions,
+            accounts.fraction_mint,
+            accounts.holder,
+            self.args.amount,
+            fractions.decimals,
+            &[],
+        )?;
+        if payout != 0 {
+            transfer(
+                &accounts.token_programs,
+                accounts.buyout_vault,
+                accounts.quote_mint,
+                accounts.holder_quote,
+                accounts.transform,
+                payout,
+                buyout_state.quote_decimals,
+                &[signer_seeds],
+            )?;
+        }
+
+        buyout_state.outstanding_fractions = buyout_state
+            .outstanding_fractions
+            .checked_sub(self.args.amount)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        buyout_state.proceeds = buyout_state
+            .proceeds
+            .checked_sub(payout)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        buyout_state.serialize(&mut &mut accounts.buyout.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
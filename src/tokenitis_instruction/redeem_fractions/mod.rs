@@ -0,0 +1,87 @@
+use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct RedeemFractions<'a> {
+    program_id: Pubkey,
+    accounts: RedeemFractionsAccounts<'a>,
+    args: RedeemFractionsArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct RedeemFractionsArgs {
+    // fractions burnt for their share of the proceeds
+    pub amount: u64,
+}
+
+struct RedeemFractionsAccounts<'a> {
+    token_programs: TokenPrograms<'a>,
+    transform: &'a AccountInfo<'a>,
+    buyout: &'a AccountInfo<'a>,
+    buyout_vault: &'a AccountInfo<'a>,
+    quote_mint: &'a AccountInfo<'a>,
+    fraction_mint: &'a AccountInfo<'a>,
+    holder: &'a AccountInfo<'a>,
+    holder_fractions: &'a AccountInfo<'a>,
+    holder_quote: &'a AccountInfo<'a>,
+}
+
+impl<'a> RedeemFractions<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: RedeemFractionsArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let mut token_programs = TokenPrograms::new(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let buyout = next_account_info(accounts)?;
+        let buyout_vault = next_account_info(accounts)?;
+        let quote_mint = next_account_info(accounts)?;
+        let fraction_mint = next_account_info(accounts)?;
+        let holder = next_account_info(accounts)?;
+        let holder_fractions = next_account_info(accounts)?;
+        let holder_quote = next_account_info(accounts)?;
+
+        // remaining accounts are transfer hook accounts
+        token_programs.hook_accounts = accounts.collect();
+
+        Ok(RedeemFractions {
+            program_id,
+            accounts: RedeemFractionsAccounts {
+                token_programs,
+                transform,
+                buyout,
+                buyout_vault,
+                quote_mint,
+                fraction_mint,
+                holder,
+                holder_fractions,
+                holder_quote,
+            },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for RedeemFractions<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
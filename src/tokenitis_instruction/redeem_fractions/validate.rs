@@ -0,0 +1,106 @@
+use crate::errors::TokenitisError;
+use crate::state::{Buyout, Tokenitis, Transform};
+use crate::tokenitis_instruction::redeem_fractions::RedeemFractions;
+use crate::util::token::unpack_account;
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl RedeemFractions<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        accounts.token_programs.validate()?;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (buyout_addr, _) = Tokenitis::find_buyout_address(&self.program_id, transform_state.id);
+        if *accounts.buyout.key != buyout_addr {
+            msg!(
+                "invalid buyout account, expected - {}, got - {}",
+                buyout_addr,
+                accounts.buyout.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if accounts.buyout.data_len() == 0 {
+            msg!("transform has no bids");
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        let buyout_state = Buyout::deserialize(&mut &**accounts.buyout.data.borrow())?;
+        if !buyout_state.claimed {
+            msg!("fractions can only be redeemed once the buyout has been claimed");
+            return Err(TokenitisError::BuyoutNotEnded.into());
+        }
+
+        let (buyout_vault_addr, _) =
+            Tokenitis::find_buyout_vault_address(&self.program_id, transform_state.id);
+        if *accounts.buyout_vault.key != buyout_vault_addr {
+            msg!(
+                "invalid buyout vault account, expected - {}, got - {}",
+                buyout_vault_addr,
+                accounts.buyout_vault.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let buyout_config = transform_state
+            .buyout
+            .ok_or(ProgramError::InvalidArgument)?;
+        if *accounts.quote_mint.key != buyout_config.quote_mint {
+            msg!(
+                "invalid quote mint, expected - {}, got - {}",
+                buyout_config.quote_mint,
+                accounts.quote_mint.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let fraction_mint = transform_state
+            .outputs
+            .keys()
+            .next()
+            .ok_or(ProgramError::InvalidArgument)?;
+        if *accounts.fraction_mint.key != *fraction_mint {
+            msg!(
+                "invalid fraction mint, expected - {}, got - {}",
+                fraction_mint,
+                accounts.fraction_mint.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if !accounts.holder.is_signer {
+            msg!("holder has to sign the transaction");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if self.args.amount == 0 || self.args.amount > buyout_state.outstanding_fractions {
+            msg!(
+                "invalid amount - has to be between 1 and the outstanding fractions - {}, got - {}",
+                buyout_state.outstanding_fractions,
+                self.args.amount
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let holder_quote_info = unpack_account(accounts.holder_quote)?;
+        if holder_quote_info.owner != *accounts.holder.key {
+            msg!(
+                "invalid holder quote account, unexpected owner, expected - {}, got - {}",
+                accounts.holder.key,
+                holder_quote_info.owner
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use crate::errors::TokenitisError;
+use crate::state::{PackCommitment, TokenMode, Tokenitis, Transform};
+use crate::tokenitis_instruction::reveal_pack::RevealPack;
+use crate::util::pack::{fallback_pick, find_slot_hash, pick};
+use crate::util::{close_pda, mint_to, transfer};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use std::ops::Index;
+
+impl RevealPack<'_> {
+    // Pick outputs with the hash of the commit slot and pay them out to the caller,
+    // once the hash has expired every pick is the fallback output
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (_, nonce) = Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        let transform_seed = Tokenitis::transform_seed(transform_state.id);
+        let signer_seeds: &[&[u8]] = &[transform_seed.as_slice(), &[nonce]];
+
+        let commitment =
+            PackCommitment::deserialize(&mut &**accounts.pack_commitment.data.borrow())?;
+        let seed = {
+            let slot_hashes = accounts.slot_hashes.data.borrow();
+            find_slot_hash(&slot_hashes, commitment.commit_slot)
+        };
+        // any later hash could be picked by waiting for it, and the picks of the commit slot
+        // are known once its hash is, so an expired pack cannot be picked or undone
+        transform_state.release_pack_outputs(&commitment)?;
+        self.pay_out_picks(
+            &mut transform_state,
+            &commitment,
+            seed.as_ref(),
+            signer_seeds,
+        )?;
+
+        transform_state.pending_packs = transform_state
+            .pending_packs
+            .checked_sub(1)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        transform_state.serialize(&mut &mut accounts.transform.data.borrow_mut()[..])?;
+
+        close_pda(accounts.pack_commitment, accounts.caller)
+    }
+
+    fn pay_out_picks(
+        &self,
+        transform_state: &mut Transform,
+        commitment: &PackCommitment,
+        seed: Option<&[u8; 32]>,
+        signer_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let accounts = &self.accounts;
+        let pack = transform_state
+            .pack
+            .as_ref()
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        // picks are summed per output so every output is paid out at most once
+        let mut amounts: Vec<u64> = vec![0; transform_state.outputs.len()];
+        for i in 0..commitment.picks {
+            let mint = match seed {
+                Some(seed) => pick(&pack.weights, seed, accounts.pack_commitment.key, i),
+                None => fallback_pick(&pack.weights),
+            }
+            .ok_or(ProgramError::InvalidArgument)?;
+            let index = transform_state
+                .outputs
+                .keys()
+                .position(|output_mint| *output_mint == mint)
+                .ok_or(ProgramError::InvalidArgument)?;
+            // amounts of the commit are paid out, they were reserved when committing
+            let amount = commitment
+                .amounts
+                .get(&mint)
+                .ok_or(ProgramError::InvalidArgument)?;
+            amounts[index] = amounts[index]
+                .checked_add(*amount)
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+        }
+
+        for (i, (mint, token)) in transform_state.outputs.iter().enumerate() {
+            let amount = amounts[i];
+            if amount == 0 {
+                continue;
+            }
+            let outstanding = transform_state
+                .outstanding_outputs
+                .entry(*mint)
+                .or_insert(0);
+            *outstanding = outstanding
+                .checked_add(amount)
+                .ok_or(TokenitisError::ArithmeticOverflow)?;
+            match token.mode {
+                TokenMode::Escrow => transfer(
+                    &accounts.token_programs,
+                    accounts.outputs.index(i),
+                    accounts.output_mints.index(i),
+                    accounts.caller_outputs.index(i),
+                    accounts.transform,
+                    amount,
+                    token.decimals,
+                    &[signer_seeds],
+                )?,
+                TokenMode::Mint => mint_to(
+                    &accounts.token_programs,
+                    accounts.output_mints.index(i),
+                    accounts.caller_outputs.index(i),
+                    accounts.transform,
+                    amount,
+                    token.decimals,
+                    &[signer_seeds],
+                )?,
+                TokenMode::Burn => return Err(ProgramError::InvalidArgument),
+            }
+        }
+
+        Ok(())
+    }
+}
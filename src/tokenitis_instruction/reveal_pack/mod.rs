@@ -0,0 +1,92 @@
+use crate::state::Transform;
+use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
+
+use borsh::BorshDeserialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct RevealPack<'a> {
+    program_id: Pubkey,
+    accounts: RevealPackAccounts<'a>,
+}
+
+// anyone can reveal a pack, outputs are always paid out to the caller that committed to it
+struct RevealPackAccounts<'a> {
+    token_programs: TokenPrograms<'a>,
+    slot_hashes: &'a AccountInfo<'a>,
+    transform: &'a AccountInfo<'a>,
+    pack_commitment: &'a AccountInfo<'a>,
+    // wallet that executed the transform, receives the rent of the commitment
+    caller: &'a AccountInfo<'a>,
+    revealer: &'a AccountInfo<'a>,
+    output_mints: Vec<&'a AccountInfo<'a>>,
+    caller_outputs: Vec<&'a AccountInfo<'a>>,
+    outputs: Vec<&'a AccountInfo<'a>>,
+}
+
+impl<'a> RevealPack<'a> {
+    pub fn new(program_id: Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let mut token_programs = TokenPrograms::new(accounts)?;
+        let slot_hashes = next_account_info(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let pack_commitment = next_account_info(accounts)?;
+        let caller = next_account_info(accounts)?;
+        let revealer = next_account_info(accounts)?;
+
+        let transform_state = Transform::deserialize(&mut &**transform.data.borrow())?;
+
+        let mut output_mints: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.outputs.len() {
+            output_mints.push(next_account_info(accounts)?)
+        }
+
+        let mut caller_outputs: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.outputs.len() {
+            caller_outputs.push(next_account_info(accounts)?)
+        }
+
+        let mut outputs: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.outputs.len() {
+            outputs.push(next_account_info(accounts)?)
+        }
+
+        // remaining accounts are transfer hook accounts
+        token_programs.hook_accounts = accounts.collect();
+
+        Ok(RevealPack {
+            program_id,
+            accounts: RevealPackAccounts {
+                token_programs,
+                slot_hashes,
+                transform,
+                pack_commitment,
+                caller,
+                revealer,
+                output_mints,
+                caller_outputs,
+                outputs,
+            },
+        })
+    }
+}
+
+impl TokenitisInstruction for RevealPack<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,111 @@
+use crate::errors::TokenitisError;
+use crate::state::{PackCommitment, Token, Tokenitis, Transform};
+use crate::tokenitis_instruction::reveal_pack::RevealPack;
+use crate::util::token::unpack_account;
+
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::sysvar::Sysvar;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError, pubkey::Pubkey};
+use std::ops::Index;
+
+impl RevealPack<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        accounts.token_programs.validate()?;
+
+        if *accounts.slot_hashes.key != solana_program::sysvar::slot_hashes::id() {
+            msg!("invalid slot hashes sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if transform_state.pack.is_none() {
+            msg!("transform is not a pack");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (pack_commitment_addr, _) =
+            Tokenitis::find_pack_address(&self.program_id, transform_state.id, accounts.caller.key);
+        if *accounts.pack_commitment.key != pack_commitment_addr {
+            msg!(
+                "invalid pack commitment account, expected - {}, got - {}",
+                pack_commitment_addr,
+                accounts.pack_commitment.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if accounts.pack_commitment.data_len() == 0 {
+            msg!("caller has no pack to reveal");
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        if !accounts.revealer.is_signer {
+            msg!("revealer has to sign the transaction");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let commitment =
+            PackCommitment::deserialize(&mut &**accounts.pack_commitment.data.borrow())?;
+        let slot = Clock::get()?.slot;
+        if slot <= commitment.commit_slot {
+            msg!(
+                "pack committed in slot - {} can only be revealed in a later slot",
+                commitment.commit_slot
+            );
+            return Err(TokenitisError::PackNotRevealable.into());
+        }
+
+        let mut outputs: Vec<(Pubkey, Token)> = transform_state
+            .outputs
+            .into_iter()
+            .collect::<Vec<(Pubkey, Token)>>();
+        outputs.sort();
+        for i in 0..outputs.len() {
+            let (mint, token) = outputs.index(i);
+            let output_mint = accounts.output_mints.index(i);
+            if *output_mint.key != *mint {
+                msg!(
+                    "invalid output mint at index - {}, expected - {}, got - {}",
+                    i,
+                    mint,
+                    output_mint.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let caller_output_account = accounts.caller_outputs.index(i);
+            let output_account = accounts.outputs.index(i);
+            if *output_account.key != token.account {
+                msg!(
+                    "invalid output at index - {}, unexpected program account, expected - {}, got - {}",
+                    i,
+                    token.account,
+                    output_account.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let caller_output_account_info = unpack_account(caller_output_account)?;
+            if caller_output_account_info.mint != *mint {
+                msg!("invalid output at index - {}, unexpected mint of caller_output, expected - {}, got - {}",i, mint,caller_output_account_info.mint);
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            if caller_output_account_info.owner != *accounts.caller.key {
+                msg!("invalid output at index - {}, unexpected owner of caller_output, expected - {}, got - {}",i, accounts.caller.key,caller_output_account_info.owner);
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        Ok(())
+    }
+}
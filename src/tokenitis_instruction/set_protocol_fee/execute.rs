@@ -0,0 +1,17 @@
+use crate::state::Tokenitis;
+use crate::tokenitis_instruction::set_protocol_fee::SetProtocolFee;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+
+impl SetProtocolFee<'_> {
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut tokenitis_state = Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?;
+        tokenitis_state.protocol_fee_bps = self.args.protocol_fee_bps;
+        tokenitis_state.serialize(&mut &mut accounts.tokenitis.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
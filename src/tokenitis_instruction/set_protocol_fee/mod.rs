@@ -0,0 +1,58 @@
+use crate::tokenitis_instruction::TokenitisInstruction;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct SetProtocolFee<'a> {
+    program_id: Pubkey,
+    accounts: SetProtocolFeeAccounts<'a>,
+    args: SetProtocolFeeArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct SetProtocolFeeArgs {
+    pub protocol_fee_bps: u16,
+}
+
+struct SetProtocolFeeAccounts<'a> {
+    tokenitis: &'a AccountInfo<'a>,
+    admin: &'a AccountInfo<'a>,
+}
+
+impl<'a> SetProtocolFee<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: SetProtocolFeeArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let tokenitis = next_account_info(accounts)?;
+        let admin = next_account_info(accounts)?;
+
+        Ok(SetProtocolFee {
+            program_id,
+            accounts: SetProtocolFeeAccounts { tokenitis, admin },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for SetProtocolFee<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,37 @@
+use crate::errors;
+use crate::state::Tokenitis;
+use crate::tokenitis_instruction::set_protocol_fee::SetProtocolFee;
+use crate::util::validate_admin;
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl SetProtocolFee<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let (tokenitis_addr, _) = Tokenitis::find_tokenitis_address(&self.program_id);
+        if *accounts.tokenitis.key != tokenitis_addr {
+            msg!(
+                "invalid tokenitis account, expected - {}, got - {}",
+                tokenitis_addr,
+                accounts.tokenitis.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let tokenitis_state = Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?;
+        validate_admin(&tokenitis_state, accounts.admin)?;
+
+        if self.args.protocol_fee_bps > errors::MAX_PROTOCOL_FEE_BPS {
+            msg!(
+                "invalid protocol fee - fee cannot be more than {} bps, got - {}",
+                errors::MAX_PROTOCOL_FEE_BPS,
+                self.args.protocol_fee_bps
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use crate::state::Transform;
+use crate::tokenitis_instruction::set_transform_status::SetTransformStatus;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+
+impl SetTransformStatus<'_> {
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        transform_state.status = self.args.status;
+        transform_state.serialize(&mut &mut accounts.transform.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
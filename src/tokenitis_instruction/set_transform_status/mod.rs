@@ -0,0 +1,59 @@
+use crate::state::TransformStatus;
+use crate::tokenitis_instruction::TokenitisInstruction;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct SetTransformStatus<'a> {
+    program_id: Pubkey,
+    accounts: SetTransformStatusAccounts<'a>,
+    args: SetTransformStatusArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct SetTransformStatusArgs {
+    pub status: TransformStatus,
+}
+
+struct SetTransformStatusAccounts<'a> {
+    transform: &'a AccountInfo<'a>,
+    creator: &'a AccountInfo<'a>,
+}
+
+impl<'a> SetTransformStatus<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: SetTransformStatusArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let transform = next_account_info(accounts)?;
+        let creator = next_account_info(accounts)?;
+
+        Ok(SetTransformStatus {
+            program_id,
+            accounts: SetTransformStatusAccounts { transform, creator },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for SetTransformStatus<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,34 @@
+use crate::state::{Tokenitis, Transform, TransformStatus};
+use crate::tokenitis_instruction::set_transform_status::SetTransformStatus;
+use crate::util::validate_creator;
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl SetTransformStatus<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        validate_creator(&transform_state, accounts.creator)?;
+
+        if transform_state.status == TransformStatus::Buyout {
+            msg!("transform is being bought out, status cannot be changed");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if self.args.status == TransformStatus::Buyout {
+            msg!("buyout status is only set by the first bid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use crate::state::Tokenitis;
+use crate::tokenitis_instruction::update_tokenitis_config::UpdateTokenitisConfig;
+use crate::util::resize_pda;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+
+impl UpdateTokenitisConfig<'_> {
+    // denylist can grow or shrink, admin pays for or receives the difference in rent
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut tokenitis_state = Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?;
+        tokenitis_state.config = self.args.config.clone();
+
+        resize_pda(
+            tokenitis_state.try_to_vec()?.len(),
+            accounts.admin,
+            accounts.tokenitis,
+            accounts.system_program,
+        )?;
+        tokenitis_state.serialize(&mut &mut accounts.tokenitis.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::state::TokenitisConfig;
+use crate::tokenitis_instruction::TokenitisInstruction;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct UpdateTokenitisConfig<'a> {
+    program_id: Pubkey,
+    accounts: UpdateTokenitisConfigAccounts<'a>,
+    args: UpdateTokenitisConfigArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct UpdateTokenitisConfigArgs {
+    pub config: TokenitisConfig,
+}
+
+struct UpdateTokenitisConfigAccounts<'a> {
+    system_program: &'a AccountInfo<'a>,
+    tokenitis: &'a AccountInfo<'a>,
+    admin: &'a AccountInfo<'a>,
+}
+
+impl<'a> UpdateTokenitisConfig<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: UpdateTokenitisConfigArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let system_program = next_account_info(accounts)?;
+        let tokenitis = next_account_info(accounts)?;
+        let admin = next_account_info(accounts)?;
+
+        Ok(UpdateTokenitisConfig {
+            program_id,
+            accounts: UpdateTokenitisConfigAccounts {
+                system_program,
+                tokenitis,
+                admin,
+            },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for UpdateTokenitisConfig<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,32 @@
+use crate::state::Tokenitis;
+use crate::tokenitis_instruction::update_tokenitis_config::UpdateTokenitisConfig;
+use crate::util::{validate_admin, validate_config};
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl UpdateTokenitisConfig<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        if *accounts.system_program.key != solana_program::system_program::id() {
+            msg!("invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (tokenitis_addr, _) = Tokenitis::find_tokenitis_address(&self.program_id);
+        if *accounts.tokenitis.key != tokenitis_addr {
+            msg!(
+                "invalid tokenitis account, expected - {}, got - {}",
+                tokenitis_addr,
+                accounts.tokenitis.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let tokenitis_state = Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?;
+        validate_admin(&tokenitis_state, accounts.admin)?;
+
+        validate_config(&self.args.config)
+    }
+}
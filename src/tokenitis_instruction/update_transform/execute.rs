@@ -0,0 +1,26 @@
+use crate::state::Transform;
+use crate::tokenitis_instruction::update_transform::UpdateTransform;
+use crate::util::resize_pda;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::entrypoint::ProgramResult;
+
+impl UpdateTransform<'_> {
+    // Replace metadata and resize the transform account to fit it
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        transform_state.metadata = self.args.metadata.clone();
+
+        resize_pda(
+            transform_state.try_to_vec()?.len(),
+            accounts.creator,
+            accounts.transform,
+            accounts.system_program,
+        )?;
+        transform_state.serialize(&mut &mut accounts.transform.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use crate::state::TransformMetadata;
+use crate::tokenitis_instruction::TokenitisInstruction;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct UpdateTransform<'a> {
+    program_id: Pubkey,
+    accounts: UpdateTransformAccounts<'a>,
+    args: UpdateTransformArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct UpdateTransformArgs {
+    pub metadata: TransformMetadata,
+}
+
+struct UpdateTransformAccounts<'a> {
+    system_program: &'a AccountInfo<'a>,
+    tokenitis: &'a AccountInfo<'a>,
+    transform: &'a AccountInfo<'a>,
+    creator: &'a AccountInfo<'a>,
+}
+
+impl<'a> UpdateTransform<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: UpdateTransformArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let system_program = next_account_info(accounts)?;
+        let tokenitis = next_account_info(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let creator = next_account_info(accounts)?;
+
+        Ok(UpdateTransform {
+            program_id,
+            accounts: UpdateTransformAccounts {
+                system_program,
+                tokenitis,
+                transform,
+                creator,
+            },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for UpdateTransform<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,41 @@
+use crate::state::{Tokenitis, Transform};
+use crate::tokenitis_instruction::update_transform::UpdateTransform;
+use crate::util::{validate_creator, validate_metadata};
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+
+impl UpdateTransform<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        if *accounts.system_program.key != solana_program::system_program::id() {
+            msg!("invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (tokenitis_addr, _) = Tokenitis::find_tokenitis_address(&self.program_id);
+        if *accounts.tokenitis.key != tokenitis_addr {
+            msg!(
+                "invalid tokenitis account, expected - {}, got - {}",
+                tokenitis_addr,
+                accounts.tokenitis.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        let tokenitis_state = Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?;
+        validate_metadata(&self.args.metadata, &tokenitis_state.config)?;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        validate_creator(&transform_state, accounts.creator)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::state::{Tokenitis, Transform};
+use crate::tokenitis_instruction::withdraw_fees::WithdrawFees;
+use crate::util::token::unpack_account;
+use crate::util::{split_fee, transfer};
+
+use borsh::BorshDeserialize;
+use solana_program::entrypoint::ProgramResult;
+use std::ops::Index;
+
+impl WithdrawFees<'_> {
+    // Split everything held in the fee vaults between the fee recipients
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (_, nonce) = Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        let transform_seed = Tokenitis::transform_seed(transform_state.id);
+        let signer_seeds: &[&[u8]] = &[transform_seed.as_slice(), &[nonce]];
+
+        let fee_recipients = transform_state.fee_splits();
+        // fee vaults are passed in the order of the inputs
+        for (i, (fee_vault, token)) in accounts
+            .fee_vaults
+            .iter()
+            .zip(transform_state.inputs.values())
+            .enumerate()
+        {
+            let amount = unpack_account(fee_vault)?.amount;
+            let splits = split_fee(amount, &fee_recipients)?;
+            for (j, split) in splits.into_iter().enumerate() {
+                if split == 0 {
+                    continue;
+                }
+                transfer(
+                    &accounts.token_programs,
+                    fee_vault,
+                    accounts.input_mints.index(i),
+                    accounts
+                        .recipient_accounts
+                        .index(i * fee_recipients.len() + j),
+                    accounts.transform,
+                    split,
+                    token.decimals,
+                    &[signer_seeds],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
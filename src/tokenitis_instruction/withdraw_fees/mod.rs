@@ -0,0 +1,82 @@
+use crate::state::Transform;
+use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
+
+use borsh::BorshDeserialize;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct WithdrawFees<'a> {
+    program_id: Pubkey,
+    accounts: WithdrawFeesAccounts<'a>,
+}
+
+// one recipient account per input for every fee recipient
+struct WithdrawFeesAccounts<'a> {
+    token_programs: TokenPrograms<'a>,
+    transform: &'a AccountInfo<'a>,
+    creator: &'a AccountInfo<'a>,
+    input_mints: Vec<&'a AccountInfo<'a>>,
+    fee_vaults: Vec<&'a AccountInfo<'a>>,
+    recipient_accounts: Vec<&'a AccountInfo<'a>>,
+}
+
+impl<'a> WithdrawFees<'a> {
+    pub fn new(program_id: Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let mut token_programs = TokenPrograms::new(accounts)?;
+        let transform = next_account_info(accounts)?;
+        let creator = next_account_info(accounts)?;
+
+        let transform_state = Transform::deserialize(&mut &**transform.data.borrow())?;
+
+        let mut input_mints: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            input_mints.push(next_account_info(accounts)?)
+        }
+
+        let mut fee_vaults: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..transform_state.inputs.len() {
+            fee_vaults.push(next_account_info(accounts)?)
+        }
+
+        let mut recipient_accounts: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..(transform_state.inputs.len() * transform_state.fee_splits().len()) {
+            recipient_accounts.push(next_account_info(accounts)?)
+        }
+
+        // remaining accounts are transfer hook accounts
+        token_programs.hook_accounts = accounts.collect();
+
+        Ok(WithdrawFees {
+            program_id,
+            accounts: WithdrawFeesAccounts {
+                token_programs,
+                transform,
+                creator,
+                input_mints,
+                fee_vaults,
+                recipient_accounts,
+            },
+        })
+    }
+}
+
+impl TokenitisInstruction for WithdrawFees<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
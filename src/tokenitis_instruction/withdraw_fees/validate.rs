@@ -0,0 +1,72 @@
+use crate::state::{Tokenitis, Transform};
+use crate::tokenitis_instruction::withdraw_fees::WithdrawFees;
+use crate::util::token::associated_token_address;
+use crate::util::validate_creator;
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+use std::ops::Index;
+
+impl WithdrawFees<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        accounts.token_programs.validate()?;
+
+        let transform_state = Transform::deserialize(&mut &**accounts.transform.data.borrow())?;
+        let (transform_addr, _) =
+            Tokenitis::find_transform_address(&self.program_id, transform_state.id);
+        if *accounts.transform.key != transform_addr {
+            msg!("invalid transform account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        validate_creator(&transform_state, accounts.creator)?;
+
+        if !transform_state.has_fee() {
+            msg!("transform does not charge a fee");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let fee_recipients = transform_state.fee_splits();
+        for (i, mint) in transform_state.inputs.keys().enumerate() {
+            let input_mint = accounts.input_mints.index(i);
+            if *input_mint.key != *mint {
+                msg!(
+                    "invalid input mint at index - {}, expected - {}, got - {}",
+                    i,
+                    mint,
+                    input_mint.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let fee_vault = accounts.fee_vaults.index(i);
+            let (fee_vault_addr, _) =
+                Tokenitis::find_fee_vault_address(&self.program_id, transform_state.id, mint);
+            if *fee_vault.key != fee_vault_addr {
+                msg!(
+                    "invalid fee vault at index - {}, expected - {}, got - {}",
+                    i,
+                    fee_vault_addr,
+                    fee_vault.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            for (j, recipient) in fee_recipients.iter().enumerate() {
+                let recipient_account = accounts
+                    .recipient_accounts
+                    .index(i * fee_recipients.len() + j);
+                let expected_recipient_account =
+                    associated_token_address(&recipient.owner, mint, input_mint.owner);
+                if *recipient_account.key != expected_recipient_account {
+                    msg!("invalid recipient account at index - {}, unexpected account for recipient - {}, expected - {}, got - {}",i, recipient.owner, expected_recipient_account,recipient_account.key);
+                    return Err(ProgramError::InvalidArgument);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use crate::state::Tokenitis;
+use crate::tokenitis_instruction::withdraw_protocol_fees::WithdrawProtocolFees;
+use crate::util::token::{unpack_account, unpack_mint};
+use crate::util::transfer;
+
+use solana_program::entrypoint::ProgramResult;
+use std::ops::Index;
+
+impl WithdrawProtocolFees<'_> {
+    // Move everything held in the treasuries to the destination accounts
+    pub(crate) fn execute_instruction(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let (_, nonce) = Tokenitis::find_tokenitis_address(&self.program_id);
+        let tokenitis_seed = Tokenitis::tokenitis_seed();
+        let signer_seeds: &[&[u8]] = &[tokenitis_seed.as_slice(), &[nonce]];
+
+        for (i, treasury) in accounts.treasuries.iter().enumerate() {
+            let amount = unpack_account(treasury)?.amount;
+            if amount == 0 {
+                continue;
+            }
+            // treasuries are shared between transforms, there is no state to take decimals from
+            let mint = accounts.mints.index(i);
+            transfer(
+                &accounts.token_programs,
+                treasury,
+                mint,
+                accounts.destinations.index(i),
+                accounts.tokenitis,
+                amount,
+                unpack_mint(mint)?.decimals,
+                &[signer_seeds],
+            )?;
+        }
+
+        Ok(())
+    }
+}
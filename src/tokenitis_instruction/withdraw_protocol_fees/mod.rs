@@ -0,0 +1,90 @@
+use crate::tokenitis_instruction::TokenitisInstruction;
+use crate::util::token::TokenPrograms;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub mod execute;
+pub mod validate;
+
+pub struct WithdrawProtocolFees<'a> {
+    program_id: Pubkey,
+    accounts: WithdrawProtocolFeesAccounts<'a>,
+    args: WithdrawProtocolFeesArgs,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+pub struct WithdrawProtocolFeesArgs {
+    pub mints: Vec<Pubkey>,
+}
+
+// one mint, treasury and destination token account per mint
+struct WithdrawProtocolFeesAccounts<'a> {
+    token_programs: TokenPrograms<'a>,
+    tokenitis: &'a AccountInfo<'a>,
+    admin: &'a AccountInfo<'a>,
+    mints: Vec<&'a AccountInfo<'a>>,
+    treasuries: Vec<&'a AccountInfo<'a>>,
+    destinations: Vec<&'a AccountInfo<'a>>,
+}
+
+impl<'a> WithdrawProtocolFees<'a> {
+    pub fn new(
+        program_id: Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        args: WithdrawProtocolFeesArgs,
+    ) -> Result<Self, ProgramError> {
+        let accounts = &mut accounts.iter();
+
+        let mut token_programs = TokenPrograms::new(accounts)?;
+        let tokenitis = next_account_info(accounts)?;
+        let admin = next_account_info(accounts)?;
+
+        let mut mints: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..args.mints.len() {
+            mints.push(next_account_info(accounts)?)
+        }
+
+        let mut treasuries: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..args.mints.len() {
+            treasuries.push(next_account_info(accounts)?)
+        }
+
+        let mut destinations: Vec<&AccountInfo> = Vec::new();
+        for _ in 0..args.mints.len() {
+            destinations.push(next_account_info(accounts)?)
+        }
+
+        // remaining accounts are transfer hook accounts
+        token_programs.hook_accounts = accounts.collect();
+
+        Ok(WithdrawProtocolFees {
+            program_id,
+            accounts: WithdrawProtocolFeesAccounts {
+                token_programs,
+                tokenitis,
+                admin,
+                mints,
+                treasuries,
+                destinations,
+            },
+            args,
+        })
+    }
+}
+
+impl TokenitisInstruction for WithdrawProtocolFees<'_> {
+    fn validate(&self) -> ProgramResult {
+        self.validate_instruction()
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_instruction()
+    }
+}
@@ -0,0 +1,67 @@
+use crate::state::Tokenitis;
+use crate::tokenitis_instruction::withdraw_protocol_fees::WithdrawProtocolFees;
+use crate::util::token::unpack_account;
+use crate::util::validate_admin;
+
+use borsh::BorshDeserialize;
+use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError};
+use std::ops::Index;
+
+impl WithdrawProtocolFees<'_> {
+    pub(crate) fn validate_instruction(&self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        accounts.token_programs.validate()?;
+
+        let (tokenitis_addr, _) = Tokenitis::find_tokenitis_address(&self.program_id);
+        if *accounts.tokenitis.key != tokenitis_addr {
+            msg!(
+                "invalid tokenitis account, expected - {}, got - {}",
+                tokenitis_addr,
+                accounts.tokenitis.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let tokenitis_state = Tokenitis::deserialize(&mut &**accounts.tokenitis.data.borrow())?;
+        validate_admin(&tokenitis_state, accounts.admin)?;
+
+        for (i, mint) in self.args.mints.iter().enumerate() {
+            let mint_account = accounts.mints.index(i);
+            if *mint_account.key != *mint {
+                msg!(
+                    "invalid mint at index - {}, expected - {}, got - {}",
+                    i,
+                    mint,
+                    mint_account.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let treasury = accounts.treasuries.index(i);
+            let (treasury_addr, _) = Tokenitis::find_treasury_address(&self.program_id, mint);
+            if *treasury.key != treasury_addr {
+                msg!(
+                    "invalid treasury at index - {}, expected - {}, got - {}",
+                    i,
+                    treasury_addr,
+                    treasury.key
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let destination_info = unpack_account(accounts.destinations.index(i))?;
+            if destination_info.mint != *mint {
+                msg!(
+                    "invalid destination at index - {}, unexpected mint, expected - {}, got - {}",
+                    i,
+                    mint,
+                    destination_info.mint
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        Ok(())
+    }
+}
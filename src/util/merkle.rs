@@ -0,0 +1,23 @@
+use solana_program::keccak::hashv;
+use solana_program::pubkey::Pubkey;
+
+// leaf of the allowlist tree, a wallet can execute forward up to max_allocation units
+pub fn allowlist_leaf(wallet: &Pubkey, max_allocation: u64) -> [u8; 32] {
+    hashv(&[wallet.as_ref(), &max_allocation.to_le_bytes()]).to_bytes()
+}
+
+// pairs are hashed in sorted order so proofs do not need to record the side of each node
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        hashv(&[a, b]).to_bytes()
+    } else {
+        hashv(&[b, a]).to_bytes()
+    }
+}
+
+pub fn verify_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed == *root
+}
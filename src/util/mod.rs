@@ -1,9 +1,33 @@
+use crate::errors::TokenitisError;
+use crate::state::{
+    DirectionPolicy, Fee, FeeRecipient, Rounding, Token, Tokenitis, TokenitisConfig, Transform,
+    TransformMetadata,
+};
+use crate::util::token::{token_instruction, MintExtensions, TokenPrograms};
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
-use solana_program::program::invoke_signed;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::msg;
+use solana_program::program::{invoke, invoke_signed};
+use solana_program::program_error::ProgramError;
+use solana_program::program_option::COption;
 use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
 use solana_program::sysvar::Sysvar;
-use std::ops::{Div, Mul};
+use spl_token::instruction::{AuthorityType, TokenInstruction};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+pub mod merkle;
+pub mod pack;
+pub mod token;
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// transfer fee extension instruction, harvest withheld tokens to mint
+const HARVEST_WITHHELD_TOKENS_TO_MINT: [u8; 2] = [26, 4];
 
 pub fn create_pda<'a>(
     program_id: &Pubkey,
@@ -11,29 +35,576 @@ pub fn create_pda<'a>(
     creator: &AccountInfo<'a>,
     pda: &AccountInfo<'a>,
     system_program: &AccountInfo<'a>,
-    seed: &[u8],
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let (_, nonce) = Pubkey::find_program_address(seeds, program_id);
+    let nonce = [nonce];
+    let signer_seeds = [seeds, &[&nonce[..]]].concat();
+    create_account_signed(
+        space,
+        program_id,
+        creator,
+        pda,
+        system_program,
+        signer_seeds.as_slice(),
+    )
+}
+
+// create an account at a pda, seeds have to include the bump, pda addresses can be derived by
+// anyone so lamports sent to one before it is created are topped up to the rent instead of
+// failing create account
+fn create_account_signed<'a>(
+    space: usize,
+    owner: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    seeds: &[&[u8]],
 ) -> ProgramResult {
     let rent = solana_program::sysvar::rent::Rent::get()?.minimum_balance(space);
+    let lamports = pda.lamports();
+    if lamports == 0 {
+        let ix = solana_program::system_instruction::create_account(
+            payer.key,
+            pda.key,
+            rent,
+            space as u64,
+            owner,
+        );
+        return invoke_signed(
+            &ix,
+            &[payer.clone(), pda.clone(), system_program.clone()],
+            &[seeds],
+        );
+    }
 
-    let ix = solana_program::system_instruction::create_account(
-        creator.key,
-        pda.key,
-        rent,
-        space as u64,
-        program_id,
+    if rent > lamports {
+        let ix = solana_program::system_instruction::transfer(payer.key, pda.key, rent - lamports);
+        invoke(&ix, &[payer.clone(), pda.clone(), system_program.clone()])?;
+    }
+    let ix = solana_program::system_instruction::allocate(pda.key, space as u64);
+    invoke_signed(&ix, &[pda.clone(), system_program.clone()], &[seeds])?;
+    let ix = solana_program::system_instruction::assign(pda.key, owner);
+    invoke_signed(&ix, &[pda.clone(), system_program.clone()], &[seeds])
+}
+
+// create a token account at a pda of the program, seeds have to include the bump,
+// token 2022 accounts are sized for the extensions required by the mint
+#[allow(clippy::too_many_arguments)]
+pub fn create_token_account_pda<'a>(
+    token_programs: &TokenPrograms<'a>,
+    system_program: &AccountInfo<'a>,
+    rent_sysvar: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    pda: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let token_program = token_programs.program_of(mint)?;
+    let space = MintExtensions::unpack(mint)?.account_len(token_program.key);
+    create_account_signed(space, token_program.key, payer, pda, system_program, seeds)?;
+
+    let ix = token_instruction(
+        token_program.key,
+        TokenInstruction::InitializeAccount,
+        vec![
+            AccountMeta::new(*pda.key, false),
+            AccountMeta::new_readonly(*mint.key, false),
+            AccountMeta::new_readonly(*authority.key, false),
+            AccountMeta::new_readonly(*rent_sysvar.key, false),
+        ],
     );
+    invoke(
+        &ix,
+        &[
+            pda.clone(),
+            mint.clone(),
+            authority.clone(),
+            rent_sysvar.clone(),
+            token_program.clone(),
+        ],
+    )
+}
+
+// resize a pda owned by the program, payer funds or receives the difference in rent
+pub fn resize_pda<'a>(
+    space: usize,
+    payer: &AccountInfo<'a>,
+    pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let rent = solana_program::sysvar::rent::Rent::get()?.minimum_balance(space);
+    let lamports = pda.lamports();
+    if rent > lamports {
+        let ix = solana_program::system_instruction::transfer(payer.key, pda.key, rent - lamports);
+        invoke(&ix, &[payer.clone(), pda.clone(), system_program.clone()])?;
+    } else if lamports > rent {
+        let refund = lamports - rent;
+        **pda.try_borrow_mut_lamports()? = rent;
+        **payer.try_borrow_mut_lamports()? = payer
+            .lamports()
+            .checked_add(refund)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+    }
+
+    pda.realloc(space, false)
+}
+
+// move all lamports out of a pda owned by the program, runtime deletes it after the transaction
+pub fn close_pda<'a>(pda: &AccountInfo<'a>, destination: &AccountInfo<'a>) -> ProgramResult {
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(pda.lamports())
+        .ok_or(TokenitisError::ArithmeticOverflow)?;
+    **pda.try_borrow_mut_lamports()? = 0;
+    pda.data.borrow_mut().fill(0);
+
+    Ok(())
+}
+
+pub fn validate_creator(transform_state: &Transform, creator: &AccountInfo) -> ProgramResult {
+    if *creator.key != transform_state.creator {
+        msg!(
+            "invalid creator account, expected - {}, got - {}",
+            transform_state.creator,
+            creator.key
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if !creator.is_signer {
+        msg!("creator has to sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    Ok(())
+}
+
+pub fn validate_admin(tokenitis_state: &Tokenitis, admin: &AccountInfo) -> ProgramResult {
+    if *admin.key != tokenitis_state.admin {
+        msg!(
+            "invalid admin account, expected - {}, got - {}",
+            tokenitis_state.admin,
+            admin.key
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if !admin.is_signer {
+        msg!("admin has to sign the transaction");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    Ok(())
+}
+
+pub fn validate_metadata(metadata: &TransformMetadata, config: &TokenitisConfig) -> ProgramResult {
+    if metadata.name.len() as u64 > config.max_string_size {
+        msg!(
+            "transform name too large - expected - {}, got - {}",
+            config.max_string_size,
+            metadata.name.len()
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if metadata.image.len() as u64 > config.max_string_size {
+        msg!(
+            "transform image too large - expected - {}, got - {}",
+            config.max_string_size,
+            metadata.image.len()
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(())
+}
+
+pub fn validate_config(config: &TokenitisConfig) -> ProgramResult {
+    if config.max_string_size == 0 {
+        msg!("invalid config - max string size has to be greater than 0");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if config.min_fee_bps == 0
+        || config.min_fee_bps > config.max_fee_bps
+        || config.max_fee_bps as u64 > BPS_DENOMINATOR
+    {
+        msg!(
+            "invalid config - fee range has to be within 1 and {} bps, got - {} to {}",
+            BPS_DENOMINATOR,
+            config.min_fee_bps,
+            config.max_fee_bps
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if config.max_inputs == 0 || config.max_outputs == 0 {
+        msg!("invalid config - max inputs and outputs have to be greater than 0");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
+
+pub fn validate_not_paused(config: &TokenitisConfig) -> ProgramResult {
+    if config.paused {
+        msg!("tokenitis is paused");
+        return Err(TokenitisError::TokenitisPaused.into());
+    }
+
+    Ok(())
+}
+
+pub fn validate_mints_allowed(
+    config: &TokenitisConfig,
+    inputs: &BTreeMap<Pubkey, Token>,
+    outputs: &BTreeMap<Pubkey, Token>,
+) -> ProgramResult {
+    for mint in inputs.keys().chain(outputs.keys()) {
+        if config.denied_mints.contains(mint) {
+            msg!("mint - {} is on the denylist", mint);
+            return Err(TokenitisError::MintDenied.into());
+        }
+    }
 
-    let (_, nonce) = Pubkey::find_program_address(&[seed], program_id);
+    Ok(())
+}
+
+// checks a transform against the global config, on creation and on every forward execution
+pub fn validate_against_config(
+    config: &TokenitisConfig,
+    policies: &[&DirectionPolicy],
+    inputs: &BTreeMap<Pubkey, Token>,
+    outputs: &BTreeMap<Pubkey, Token>,
+) -> ProgramResult {
+    validate_not_paused(config)?;
+
+    if inputs.len() as u64 > config.max_inputs {
+        msg!(
+            "too many inputs - expected - {}, got - {}",
+            config.max_inputs,
+            inputs.len()
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if outputs.len() as u64 > config.max_outputs {
+        msg!(
+            "too many outputs - expected - {}, got - {}",
+            config.max_outputs,
+            outputs.len()
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    validate_mints_allowed(config, inputs, outputs)?;
+
+    for fee in policies.iter().filter_map(|policy| policy.fee.as_ref()) {
+        if !(config.min_fee_bps..=config.max_fee_bps).contains(&fee.bps) {
+            msg!(
+                "invalid fee - fee has to be between {} and {} bps, got - {}",
+                config.min_fee_bps,
+                config.max_fee_bps,
+                fee.bps
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    Ok(())
+}
+
+// ed25519 program instruction data - signature count, padding, then per signature
+// the offsets of the signature, public key and message and the instructions holding them
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+
+// checks that the instruction before the current one verified a signature of signer over message,
+// the runtime fails the transaction if an ed25519 program instruction has an invalid signature
+pub fn validate_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> ProgramResult {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    if current_index == 0 {
+        msg!("ed25519 instruction has to be placed before the current instruction");
+        return Err(TokenitisError::InvalidPermit.into());
+    }
+    let ix = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    if ix.program_id != solana_program::ed25519_program::id() {
+        msg!("instruction before the current instruction is not an ed25519 instruction");
+        return Err(TokenitisError::InvalidPermit.into());
+    }
+
+    let data = &ix.data;
+    if data.len() < ED25519_OFFSETS_START + ED25519_OFFSETS_LEN || data[0] != 1 {
+        msg!("ed25519 instruction has to verify exactly one signature");
+        return Err(TokenitisError::InvalidPermit.into());
+    }
+    let offsets: Vec<usize> = data
+        [ED25519_OFFSETS_START..ED25519_OFFSETS_START + ED25519_OFFSETS_LEN]
+        .chunks(2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+        .collect();
+    let (public_key_offset, message_offset, message_size) = (offsets[2], offsets[4], offsets[5]);
+    // signature, public key and message have to be in the ed25519 instruction itself
+    if [offsets[1], offsets[3], offsets[6]]
+        .iter()
+        .any(|index| *index != u16::MAX as usize)
+    {
+        msg!("ed25519 instruction has to hold the signature, public key and message");
+        return Err(TokenitisError::InvalidPermit.into());
+    }
+
+    let public_key = data.get(public_key_offset..public_key_offset + 32);
+    let signed_message = data.get(message_offset..message_offset + message_size);
+    if public_key != Some(signer.as_ref()) || signed_message != Some(message) {
+        msg!(
+            "ed25519 instruction does not match the expected signer - {} and message",
+            signer
+        );
+        return Err(TokenitisError::InvalidPermit.into());
+    }
+
+    Ok(())
+}
+
+pub fn calculate_fee(amount: u64, fee: &Fee) -> Result<u64, ProgramError> {
+    let bps_denominator = BPS_DENOMINATOR as u128;
+    let numerator = (amount as u128)
+        .checked_mul(fee.bps as u128)
+        .ok_or(TokenitisError::ArithmeticOverflow)?;
+    let fee_amount = match fee.rounding {
+        Rounding::Floor => numerator / bps_denominator,
+        Rounding::Ceil => {
+            numerator
+                .checked_add(bps_denominator - 1)
+                .ok_or(TokenitisError::ArithmeticOverflow)?
+                / bps_denominator
+        }
+    };
+
+    u64::try_from(fee_amount).map_err(|_| TokenitisError::ArithmeticOverflow.into())
+}
+
+// splits the fee by recipient share rounding down, the remainder goes to the first recipient
+pub fn split_fee(fee_amount: u64, recipients: &[FeeRecipient]) -> Result<Vec<u64>, ProgramError> {
+    let mut splits: Vec<u64> = Vec::with_capacity(recipients.len());
+    let mut distributed: u64 = 0;
+    for recipient in recipients.iter() {
+        let split = (fee_amount as u128)
+            .checked_mul(recipient.share_bps as u128)
+            .ok_or(TokenitisError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR as u128;
+        let split = u64::try_from(split).map_err(|_| TokenitisError::ArithmeticOverflow)?;
+        distributed = distributed
+            .checked_add(split)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+        splits.push(split);
+    }
+
+    let remainder = fee_amount
+        .checked_sub(distributed)
+        .ok_or(TokenitisError::ArithmeticOverflow)?;
+    if let Some(first) = splits.first_mut() {
+        *first = first
+            .checked_add(remainder)
+            .ok_or(TokenitisError::ArithmeticOverflow)?;
+    }
+
+    Ok(splits)
+}
+
+pub fn scale_amount(amount: u64, quantity: u64) -> Result<u64, ProgramError> {
+    amount.checked_mul(quantity).ok_or_else(|| {
+        msg!(
+            "amount overflow - amount - {}, quantity - {}",
+            amount,
+            quantity
+        );
+        TokenitisError::ArithmeticOverflow.into()
+    })
+}
+
+// transfers are checked so token 2022 mints with a transfer fee or transfer hook can be moved,
+// hook accounts are forwarded for mints with a transfer hook, decimals are taken from the
+// state the mint was validated against instead of reading the mint
+#[allow(clippy::too_many_arguments)]
+pub fn transfer<'a>(
+    token_programs: &TokenPrograms<'a>,
+    src: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    dst: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let token_program = token_programs.program_of(mint)?;
+    let mut accounts = vec![
+        AccountMeta::new(*src.key, false),
+        AccountMeta::new_readonly(*mint.key, false),
+        AccountMeta::new(*dst.key, false),
+        AccountMeta::new_readonly(*authority.key, true),
+    ];
+    let mut account_infos = vec![
+        src.clone(),
+        mint.clone(),
+        dst.clone(),
+        authority.clone(),
+        token_program.clone(),
+    ];
+    if MintExtensions::unpack(mint)?.transfer_hook.is_some() {
+        for account in token_programs.hook_accounts.iter() {
+            accounts.push(AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            });
+            account_infos.push((*account).clone());
+        }
+    }
+
+    let ix = token_instruction(
+        token_program.key,
+        TokenInstruction::TransferChecked { amount, decimals },
+        accounts,
+    );
+    invoke_signed(&ix, &account_infos, signer_seeds)
+}
+
+pub fn mint_to<'a>(
+    token_programs: &TokenPrograms<'a>,
+    mint: &AccountInfo<'a>,
+    dst: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let token_program = token_programs.program_of(mint)?;
+    let ix = token_instruction(
+        token_program.key,
+        TokenInstruction::MintToChecked { amount, decimals },
+        vec![
+            AccountMeta::new(*mint.key, false),
+            AccountMeta::new(*dst.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+        ],
+    );
+    invoke_signed(
+        &ix,
+        &[
+            mint.clone(),
+            dst.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+pub fn burn<'a>(
+    token_programs: &TokenPrograms<'a>,
+    src: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let token_program = token_programs.program_of(mint)?;
+    let ix = token_instruction(
+        token_program.key,
+        TokenInstruction::BurnChecked { amount, decimals },
+        vec![
+            AccountMeta::new(*src.key, false),
+            AccountMeta::new(*mint.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+        ],
+    );
     invoke_signed(
         &ix,
-        &[creator.clone(), pda.clone(), system_program.clone()],
-        &[&[seed, &[nonce]]],
+        &[
+            src.clone(),
+            mint.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
     )
 }
 
-pub fn calculate_fee(amount: u64, fee_percent: u64) -> u64 {
-    let amount = amount as f64;
-    let fee_percent = fee_percent as f64;
-    // cast truncates the fractional part, its like floor for positive numbers
-    amount.mul(fee_percent.div(100_f64)) as u64
+// token 2022 accounts holding withheld transfer fees cannot be closed,
+// the fees are harvested to the mint first
+pub fn close_token_account<'a>(
+    token_programs: &TokenPrograms<'a>,
+    account: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let token_program = token_programs.program_of(mint)?;
+    if MintExtensions::unpack(mint)?.transfer_fee.is_some() {
+        let ix = Instruction {
+            program_id: *token_program.key,
+            accounts: vec![
+                AccountMeta::new(*mint.key, false),
+                AccountMeta::new(*account.key, false),
+            ],
+            data: HARVEST_WITHHELD_TOKENS_TO_MINT.to_vec(),
+        };
+        invoke(&ix, &[mint.clone(), account.clone(), token_program.clone()])?;
+    }
+
+    let ix = token_instruction(
+        token_program.key,
+        TokenInstruction::CloseAccount,
+        vec![
+            AccountMeta::new(*account.key, false),
+            AccountMeta::new(*destination.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+        ],
+    );
+    invoke_signed(
+        &ix,
+        &[
+            account.clone(),
+            destination.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+// account is a token account or a mint owned by the token program
+pub fn set_authority<'a>(
+    token_program: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    new_authority: &Pubkey,
+    authority_type: AuthorityType,
+    authority: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let ix = token_instruction(
+        token_program.key,
+        TokenInstruction::SetAuthority {
+            authority_type,
+            new_authority: COption::Some(*new_authority),
+        },
+        vec![
+            AccountMeta::new(*account.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+        ],
+    );
+    invoke_signed(
+        &ix,
+        &[account.clone(), authority.clone(), token_program.clone()],
+        signer_seeds,
+    )
 }
@@ -0,0 +1,67 @@
+use solana_program::keccak::hashv;
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+
+// slot hashes sysvar is a length prefixed list of (slot, hash), newest first,
+// it is too large to deserialize so entries are read in place
+const SLOT_HASH_LEN: usize = 8 + 32;
+
+fn slot_hashes(data: &[u8]) -> impl Iterator<Item = (u64, &[u8])> {
+    let len = data
+        .get(..8)
+        .map(|len| u64::from_le_bytes(len.try_into().unwrap()) as usize)
+        .unwrap_or(0);
+    data.get(8..)
+        .unwrap_or(&[])
+        .chunks_exact(SLOT_HASH_LEN)
+        .take(len)
+        .map(|entry| {
+            (
+                u64::from_le_bytes(entry[..8].try_into().unwrap()),
+                &entry[8..],
+            )
+        })
+}
+
+// only the last 512 slot hashes are kept
+pub fn find_slot_hash(data: &[u8], slot: u64) -> Option<[u8; 32]> {
+    slot_hashes(data)
+        .find(|(entry_slot, _)| *entry_slot == slot)
+        .map(|(_, hash)| hash.try_into().unwrap())
+}
+
+// picks an output mint with probability weight / total weight
+pub fn pick(
+    weights: &BTreeMap<Pubkey, u64>,
+    seed: &[u8; 32],
+    commitment: &Pubkey,
+    index: u64,
+) -> Option<Pubkey> {
+    let total: u64 = weights.values().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let hash = hashv(&[seed, commitment.as_ref(), &index.to_le_bytes()]).to_bytes();
+    let mut roll = u64::from_le_bytes(hash[..8].try_into().unwrap()) % total;
+    for (mint, weight) in weights.iter() {
+        if roll < *weight {
+            return Some(*mint);
+        }
+        roll -= weight;
+    }
+
+    None
+}
+
+// output of every pick of an expired pack, the most common output so that letting a pack
+// expire is never better than revealing it, ties go to the lowest mint
+pub fn fallback_pick(weights: &BTreeMap<Pubkey, u64>) -> Option<Pubkey> {
+    weights
+        .iter()
+        .filter(|(_, weight)| **weight > 0)
+        .rev()
+        .max_by_key(|(_, weight)| **weight)
+        .map(|(mint, _)| *mint)
+}
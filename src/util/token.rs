@@ -0,0 +1,399 @@
+use crate::errors::TokenitisError;
+use crate::state::TokenProgram;
+use crate::util::BPS_DENOMINATOR;
+
+use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+use spl_token::instruction::TokenInstruction;
+use spl_token::state::{Account, AccountState, Mint};
+use std::convert::{TryFrom, TryInto};
+
+pub mod token_2022 {
+    solana_program::declare_id!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+    pub mod native_mint {
+        solana_program::declare_id!("9pan9bMn5HatX4EJduwg6sgizWwP9QPsv7ziqo9XEoRw");
+    }
+}
+
+// token 2022 accounts start with the spl token layout, extensions are stored as
+// type-length-value entries after the account type byte that follows it
+const EXTENSIONS_OFFSET: usize = Account::LEN + 1;
+const EXTENSION_HEADER_LEN: usize = 4;
+
+const TRANSFER_FEE_CONFIG: u16 = 1;
+const MINT_CLOSE_AUTHORITY: u16 = 3;
+const DEFAULT_ACCOUNT_STATE: u16 = 6;
+const MEMO_TRANSFER: u16 = 8;
+const NON_TRANSFERABLE: u16 = 9;
+const CPI_GUARD: u16 = 11;
+const PERMANENT_DELEGATE: u16 = 12;
+const TRANSFER_HOOK: u16 = 14;
+
+// both token programs are passed to every instruction that moves tokens, the program of a
+// mint is the owner of the mint account
+#[derive(Debug)]
+pub struct TokenPrograms<'a> {
+    pub token: &'a AccountInfo<'a>,
+    pub token_2022: &'a AccountInfo<'a>,
+    // passed after every other account, forwarded to transfers of mints with a transfer hook
+    pub hook_accounts: Vec<&'a AccountInfo<'a>>,
+}
+
+impl<'a> TokenPrograms<'a> {
+    pub fn new<I: Iterator<Item = &'a AccountInfo<'a>>>(
+        accounts: &mut I,
+    ) -> Result<Self, ProgramError> {
+        Ok(TokenPrograms {
+            token: next_account_info(accounts)?,
+            token_2022: next_account_info(accounts)?,
+            hook_accounts: vec![],
+        })
+    }
+
+    pub fn validate(&self) -> ProgramResult {
+        if *self.token.key != spl_token::id() {
+            msg!("invalid token program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if *self.token_2022.key != token_2022::id() {
+            msg!("invalid token 2022 program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+
+    // token program that owns a mint or token account
+    pub fn program_of(&self, account: &AccountInfo) -> Result<&'a AccountInfo<'a>, ProgramError> {
+        match TokenProgram::from_id(account.owner) {
+            Some(TokenProgram::Token) => Ok(self.token),
+            Some(TokenProgram::Token2022) => Ok(self.token_2022),
+            None => {
+                msg!(
+                    "account - {} is not owned by a token program, got - {}",
+                    account.key,
+                    account.owner
+                );
+                Err(ProgramError::IncorrectProgramId)
+            }
+        }
+    }
+}
+
+impl TokenProgram {
+    pub fn id(&self) -> Pubkey {
+        match self {
+            TokenProgram::Token => spl_token::id(),
+            TokenProgram::Token2022 => token_2022::id(),
+        }
+    }
+
+    pub fn from_id(id: &Pubkey) -> Option<Self> {
+        if *id == spl_token::id() {
+            Some(TokenProgram::Token)
+        } else if *id == token_2022::id() {
+            Some(TokenProgram::Token2022)
+        } else {
+            None
+        }
+    }
+}
+
+// wrapped sol mints, accounts of them hold lamports on top of their rent exempt reserve
+// and the amount only counts the lamports above the reserve
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::id() || *mint == token_2022::native_mint::id()
+}
+
+// base state of a token account of either token program
+pub fn unpack_account(account: &AccountInfo) -> Result<Account, ProgramError> {
+    if TokenProgram::from_id(account.owner).is_none() {
+        msg!(
+            "token account - {} is not owned by a token program",
+            account.key
+        );
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let data = account.data.borrow();
+    Account::unpack(
+        data.get(..Account::LEN)
+            .ok_or(ProgramError::InvalidAccountData)?,
+    )
+}
+
+// base state of a mint of either token program
+pub fn unpack_mint(mint: &AccountInfo) -> Result<Mint, ProgramError> {
+    if TokenProgram::from_id(mint.owner).is_none() {
+        msg!("mint - {} is not owned by a token program", mint.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let data = mint.data.borrow();
+    Mint::unpack(
+        data.get(..Mint::LEN)
+            .ok_or(ProgramError::InvalidAccountData)?,
+    )
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TransferFee {
+    pub epoch: u64,
+    pub maximum_fee: u64,
+    pub bps: u16,
+}
+
+impl TransferFee {
+    fn unpack(data: &[u8]) -> Self {
+        TransferFee {
+            epoch: u64::from_le_bytes(data[..8].try_into().unwrap()),
+            maximum_fee: u64::from_le_bytes(data[8..16].try_into().unwrap()),
+            bps: u16::from_le_bytes(data[16..18].try_into().unwrap()),
+        }
+    }
+
+    // fee withheld from a transfer of amount, rounded up
+    pub fn fee(&self, amount: u64) -> Option<u64> {
+        if self.bps == 0 || amount == 0 {
+            return Some(0);
+        }
+        let numerator = (amount as u128).checked_mul(self.bps as u128)?;
+        let fee = (numerator + BPS_DENOMINATOR as u128 - 1) / BPS_DENOMINATOR as u128;
+        u64::try_from(fee).ok().map(|fee| fee.min(self.maximum_fee))
+    }
+
+    // amount that has to be sent for the recipient to receive net_amount
+    pub fn gross_amount(&self, net_amount: u64) -> Option<u64> {
+        if self.bps == 0 || net_amount == 0 {
+            return Some(net_amount);
+        }
+        if self.bps as u64 >= BPS_DENOMINATOR {
+            return net_amount.checked_add(self.maximum_fee);
+        }
+        // rounded up, the fee is rounded up on the gross amount
+        let numerator = net_amount as u128 * BPS_DENOMINATOR as u128;
+        let denominator = (BPS_DENOMINATOR - self.bps as u64) as u128;
+        let mut gross = numerator / denominator;
+        if gross * denominator < numerator {
+            gross += 1;
+        }
+        if gross - net_amount as u128 >= self.maximum_fee as u128 {
+            net_amount.checked_add(self.maximum_fee)
+        } else {
+            u64::try_from(gross).ok()
+        }
+    }
+}
+
+// token 2022 extensions of a mint that change how tokens are moved
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct MintExtensions {
+    // older and newer transfer fee, newer applies from its epoch
+    pub transfer_fee: Option<(TransferFee, TransferFee)>,
+    pub transfer_hook: Option<Pubkey>,
+    pub non_transferable: bool,
+    pub permanent_delegate: bool,
+    pub close_authority: bool,
+    // new token accounts of the mint start frozen
+    pub default_frozen: bool,
+}
+
+impl MintExtensions {
+    pub fn unpack(mint: &AccountInfo) -> Result<Self, ProgramError> {
+        let mut extensions = MintExtensions::default();
+        if *mint.owner != token_2022::id() {
+            return Ok(extensions);
+        }
+
+        let data = mint.data.borrow();
+        for (extension_type, value) in unpack_extensions(&data)? {
+            match extension_type {
+                TRANSFER_FEE_CONFIG => {
+                    // fee authorities and withheld amount come before the fees
+                    let fees = value.get(72..108).ok_or(ProgramError::InvalidAccountData)?;
+                    extensions.transfer_fee = Some((
+                        TransferFee::unpack(&fees[..18]),
+                        TransferFee::unpack(&fees[18..]),
+                    ));
+                }
+                MINT_CLOSE_AUTHORITY => {
+                    extensions.close_authority = value.iter().any(|byte| *byte != 0)
+                }
+                DEFAULT_ACCOUNT_STATE => {
+                    extensions.default_frozen = value.first() == Some(&(AccountState::Frozen as u8))
+                }
+                NON_TRANSFERABLE => extensions.non_transferable = true,
+                PERMANENT_DELEGATE => {
+                    extensions.permanent_delegate = value.iter().any(|byte| *byte != 0)
+                }
+                TRANSFER_HOOK => {
+                    let program_id = value.get(32..64).ok_or(ProgramError::InvalidAccountData)?;
+                    if program_id.iter().any(|byte| *byte != 0) {
+                        extensions.transfer_hook = Some(Pubkey::new(program_id));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(extensions)
+    }
+
+    pub fn transfer_fee(&self, epoch: u64) -> Option<TransferFee> {
+        let (older, newer) = self.transfer_fee?;
+        if epoch >= newer.epoch {
+            Some(newer)
+        } else {
+            Some(older)
+        }
+    }
+
+    // space of a token account of the mint, accounts hold an extension for some mint extensions
+    pub fn account_len(&self, token_program: &Pubkey) -> usize {
+        if *token_program != token_2022::id() {
+            return Account::LEN;
+        }
+
+        // transfer fee amount and transfer hook account extensions
+        let mut extension_lens = vec![];
+        if self.transfer_fee.is_some() {
+            extension_lens.push(8);
+        }
+        if self.transfer_hook.is_some() {
+            extension_lens.push(1);
+        }
+        if extension_lens.is_empty() {
+            return Account::LEN;
+        }
+
+        EXTENSIONS_OFFSET
+            + extension_lens
+                .iter()
+                .map(|len| EXTENSION_HEADER_LEN + len)
+                .sum::<usize>()
+    }
+}
+
+// type and value of every extension of a token 2022 mint or token account
+fn unpack_extensions(data: &[u8]) -> Result<Vec<(u16, &[u8])>, ProgramError> {
+    let mut extensions = vec![];
+    let mut offset = EXTENSIONS_OFFSET;
+    while offset + EXTENSION_HEADER_LEN <= data.len() {
+        let extension_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let len = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        // rest of the account is unused
+        if extension_type == 0 {
+            break;
+        }
+        let value = data
+            .get(offset + EXTENSION_HEADER_LEN..offset + EXTENSION_HEADER_LEN + len)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        extensions.push((extension_type, value));
+        offset += EXTENSION_HEADER_LEN + len;
+    }
+
+    Ok(extensions)
+}
+
+// tokens of non transferable mints cannot be escrowed, a permanent delegate could drain the escrow,
+// a closed mint or frozen escrows would lock the tokens in the transform
+pub fn validate_mint_extensions(mint: &AccountInfo) -> ProgramResult {
+    let extensions = MintExtensions::unpack(mint)?;
+    if extensions.non_transferable {
+        msg!("mint - {} is non transferable", mint.key);
+        return Err(TokenitisError::MintNotTransferable.into());
+    }
+    if extensions.permanent_delegate {
+        msg!("mint - {} has a permanent delegate", mint.key);
+        return Err(TokenitisError::MintHasPermanentDelegate.into());
+    }
+    if extensions.close_authority {
+        msg!("mint - {} has a close authority", mint.key);
+        return Err(TokenitisError::MintHasCloseAuthority.into());
+    }
+    if extensions.default_frozen {
+        msg!("mint - {} freezes new token accounts", mint.key);
+        return Err(TokenitisError::MintDefaultFrozen.into());
+    }
+
+    Ok(())
+}
+
+// escrow accounts are moved by the transform through cpi and receive transfers without a memo
+pub fn validate_escrow_extensions(token_account: &AccountInfo) -> ProgramResult {
+    if *token_account.owner != token_2022::id() {
+        return Ok(());
+    }
+
+    let data = token_account.data.borrow();
+    for (extension_type, value) in unpack_extensions(&data)? {
+        let enabled = value.first() == Some(&1);
+        if extension_type == MEMO_TRANSFER && enabled {
+            msg!(
+                "token account - {} requires a memo on transfers",
+                token_account.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        if extension_type == CPI_GUARD && enabled {
+            msg!("token account - {} has a cpi guard", token_account.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    Ok(())
+}
+
+// amount that has to be sent into a program account for it to receive the full amount,
+// amounts sent out of program accounts are reduced by the transfer fee instead
+pub fn gross_amount(mint: &AccountInfo, amount: u64) -> Result<u64, ProgramError> {
+    let epoch = Clock::get()?.epoch;
+    match MintExtensions::unpack(mint)?.transfer_fee(epoch) {
+        Some(transfer_fee) => Ok(transfer_fee
+            .gross_amount(amount)
+            .ok_or(TokenitisError::ArithmeticOverflow)?),
+        None => Ok(amount),
+    }
+}
+
+// amount received when amount is sent out of a program account
+pub fn net_amount(mint: &AccountInfo, amount: u64) -> Result<u64, ProgramError> {
+    let epoch = Clock::get()?.epoch;
+    match MintExtensions::unpack(mint)?.transfer_fee(epoch) {
+        Some(transfer_fee) => Ok(transfer_fee
+            .fee(amount)
+            .and_then(|fee| amount.checked_sub(fee))
+            .ok_or(TokenitisError::ArithmeticOverflow)?),
+        None => Ok(amount),
+    }
+}
+
+// associated token account of a wallet for a mint of either token program
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &spl_associated_token_account::id(),
+    )
+    .0
+}
+
+// spl token instruction builders reject the token 2022 program id,
+// both programs share the instruction layout
+pub fn token_instruction(
+    token_program: &Pubkey,
+    instruction: TokenInstruction,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    Instruction {
+        program_id: *token_program,
+        accounts,
+        data: instruction.pack(),
+    }
+}
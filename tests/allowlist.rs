@@ -0,0 +1,124 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use tokenitis::errors::TokenitisError;
+use tokenitis::sdk::merkle::AllowlistTree;
+use tokenitis::state::{Tokenitis, TransformWallet};
+use tokenitis::tokenitis_instruction::execute_transform::{AllowlistProof, Direction};
+
+struct AllowlistTest {
+    bank: Bank,
+    transform_num: u64,
+    tree: AllowlistTree,
+    caller: Pubkey,
+    other: Pubkey,
+    accounts: (BTreeMap<Pubkey, Pubkey>, BTreeMap<Pubkey, Pubkey>),
+}
+
+impl AllowlistTest {
+    // the caller can execute up to 3 units forward
+    fn new() -> Self {
+        let mut bank = Bank::new();
+        let creator = bank.new_wallet();
+        initialize_tokenitis(&mut bank, &creator).unwrap();
+
+        let caller = bank.new_wallet();
+        let other = bank.new_wallet();
+        let tree = AllowlistTree::new(vec![(caller, 3), (other, 5)]);
+        let mut args = pair_args(&mut bank, &creator, 1);
+        args.allowlist = tree.root();
+        let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+        let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+
+        AllowlistTest {
+            bank,
+            transform_num,
+            tree,
+            caller,
+            other,
+            accounts,
+        }
+    }
+
+    fn execute(&mut self, quantity: u64, allowlist_proof: Option<AllowlistProof>) -> ProgramResult {
+        let mut args = execute_args(Direction::Forward, quantity);
+        args.allowlist_proof = allowlist_proof;
+        execute_transform(
+            &mut self.bank,
+            self.transform_num,
+            &self.caller,
+            args,
+            &self.accounts,
+        )
+    }
+
+    fn proof(&self) -> AllowlistProof {
+        self.tree.proof(&self.caller).unwrap()
+    }
+
+    fn claimed(&self) -> u64 {
+        let (wallet, _) =
+            Tokenitis::find_wallet_address(&tokenitis::id(), self.transform_num, &self.caller);
+        self.bank.state::<TransformWallet>(&wallet).executions
+    }
+}
+
+#[test]
+fn allowlisted_wallet_executes() {
+    let mut test = AllowlistTest::new();
+    let proof = test.proof();
+    test.execute(2, Some(proof)).unwrap();
+    assert_eq!(test.claimed(), 2);
+    assert_eq!(
+        transform_state(&test.bank, test.transform_num).total_executions,
+        2
+    );
+}
+
+#[test]
+fn invalid_proof_is_rejected() {
+    let mut test = AllowlistTest::new();
+    assert_eq!(
+        test.execute(1, None),
+        Err(custom_error(TokenitisError::NotAllowlisted))
+    );
+
+    // the allocation is part of the leaf, a wallet cannot claim more than it was given
+    let mut proof = test.proof();
+    proof.max_allocation += 1;
+    assert_eq!(
+        test.execute(1, Some(proof)),
+        Err(custom_error(TokenitisError::NotAllowlisted))
+    );
+
+    // a proof of another wallet does not verify for the caller
+    let proof = test.tree.proof(&test.other).unwrap();
+    assert_eq!(
+        test.execute(1, Some(proof)),
+        Err(custom_error(TokenitisError::NotAllowlisted))
+    );
+}
+
+#[test]
+fn allocation_is_tracked_across_executions() {
+    let mut test = AllowlistTest::new();
+    let proof = test.proof();
+    test.execute(2, Some(proof.clone())).unwrap();
+    assert_eq!(test.claimed(), 2);
+
+    assert_eq!(
+        test.execute(2, Some(proof.clone())),
+        Err(custom_error(TokenitisError::AllocationExceeded))
+    );
+    assert_eq!(test.claimed(), 2);
+
+    test.execute(1, Some(proof.clone())).unwrap();
+    assert_eq!(test.claimed(), 3);
+    assert_eq!(
+        test.execute(1, Some(proof)),
+        Err(custom_error(TokenitisError::AllocationExceeded))
+    );
+}
@@ -10,7 +10,7 @@ use solana_program::{
 };
 use solana_sdk::account::ReadableAccount;
 use solana_sdk::{
-    signature::{Keypair, Signature, Signer},
+    signature::{read_keypair_file, Keypair, Signature, Signer},
     transaction::Transaction,
 };
 use spl_token::{
@@ -19,13 +19,23 @@ use spl_token::{
 };
 use std::{collections::BTreeMap, thread::sleep, time::Duration};
 use tokenitis::sdk::InstructionBuilder;
-use tokenitis::state::{Token, TransformMetadata};
-use tokenitis::state::{Tokenitis, Transform};
+use tokenitis::state::{
+    ClosePolicy, DirectionPolicy, ExecutionLimits, Fee, FeeRecipient, Rounding, Token, TokenMode,
+    TokenProgram, TransformMetadata, TransformStatus,
+};
+use tokenitis::state::{Tokenitis, TokenitisConfig, Transform, TransformWallet};
 use tokenitis::tokenitis_instruction::create_transform::CreateTransformArgs;
 use tokenitis::tokenitis_instruction::execute_transform::{Direction, ExecuteTransformArgs};
+use tokenitis::tokenitis_instruction::initialize_tokenitis::InitializeTokenitisArgs;
+use tokenitis::tokenitis_instruction::set_transform_status::SetTransformStatusArgs;
+use tokenitis::tokenitis_instruction::update_transform::UpdateTransformArgs;
 
 const TRANSFORM_AMOUNT: u64 = 50;
-const FEE_PERCENT: u64 = 5;
+const TRANSFORM_QUANTITY: u64 = 2;
+const FEE: Fee = Fee {
+    bps: 500,
+    rounding: Rounding::Floor,
+};
 const OUTPUT_PROGRAM_ACC_SUPPLY: u64 = 1000;
 const INPUT_CALLER_ACC_SUPPLY: u64 = 1000;
 
@@ -50,12 +60,26 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
     let input_mint1 = Keypair::new();
     let input_mint2 = Keypair::new();
     let mint_rent = client.get_minimum_balance_for_rent_exemption(Mint::LEN)?;
-    InstructionBuilder::create_spl_token_mint(&input_mint1.pubkey(), user, None, 0, mint_rent)?
-        .iter()
-        .for_each(|i| instructions.push(i.clone()));
-    InstructionBuilder::create_spl_token_mint(&input_mint2.pubkey(), user, None, 0, mint_rent)?
-        .iter()
-        .for_each(|i| instructions.push(i.clone()));
+    InstructionBuilder::create_spl_token_mint(
+        &spl_token::ID,
+        &input_mint1.pubkey(),
+        user,
+        None,
+        0,
+        mint_rent,
+    )?
+    .iter()
+    .for_each(|i| instructions.push(i.clone()));
+    InstructionBuilder::create_spl_token_mint(
+        &spl_token::ID,
+        &input_mint2.pubkey(),
+        user,
+        None,
+        0,
+        mint_rent,
+    )?
+    .iter()
+    .for_each(|i| instructions.push(i.clone()));
     let sig = create_and_send_tx(
         &client,
         instructions,
@@ -75,7 +99,7 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
     let input1_program_account = Keypair::new();
     let input2_program_account = Keypair::new();
     let output1_program_account = Keypair::new();
-    let output2_program_account = Keypair::new();
+    let fee_recipient = Keypair::new();
 
     let mut inputs: BTreeMap<Pubkey, Token> = BTreeMap::new();
     let mut outputs: BTreeMap<Pubkey, Token> = BTreeMap::new();
@@ -85,6 +109,9 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
         Token {
             account: input1_program_account.pubkey(),
             amount: TRANSFORM_AMOUNT,
+            mode: TokenMode::Escrow,
+            token_program: TokenProgram::Token,
+            decimals: 0,
         },
     );
     inputs.insert(
@@ -92,6 +119,9 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
         Token {
             account: input2_program_account.pubkey(),
             amount: TRANSFORM_AMOUNT,
+            mode: TokenMode::Escrow,
+            token_program: TokenProgram::Token,
+            decimals: 0,
         },
     );
     outputs.insert(
@@ -99,25 +129,65 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
         Token {
             account: output1_program_account.pubkey(),
             amount: TRANSFORM_AMOUNT,
+            mode: TokenMode::Escrow,
+            token_program: TokenProgram::Token,
+            decimals: 0,
         },
     );
     outputs.insert(
         output_mint2.pubkey(),
         Token {
-            account: output2_program_account.pubkey(),
+            account: output_mint2.pubkey(),
             amount: TRANSFORM_AMOUNT,
+            mode: TokenMode::Mint,
+            token_program: TokenProgram::Token,
+            decimals: 0,
         },
     );
     output_supply.insert(output_mint1.pubkey(), OUTPUT_PROGRAM_ACC_SUPPLY);
-    output_supply.insert(output_mint2.pubkey(), OUTPUT_PROGRAM_ACC_SUPPLY);
     let args = CreateTransformArgs {
         metadata: TransformMetadata {
             name: "test123".to_string(),
             image: "".to_string(),
         },
-        fee: Some(FEE_PERCENT),
+        forward: DirectionPolicy {
+            enabled: true,
+            fee: Some(FEE),
+            amounts: BTreeMap::new(),
+            start_unix_timestamp: None,
+            end_unix_timestamp: None,
+        },
+        reverse: DirectionPolicy {
+            enabled: true,
+            fee: None,
+            amounts: BTreeMap::new(),
+            start_unix_timestamp: None,
+            end_unix_timestamp: None,
+        },
+        fee_recipients: vec![
+            FeeRecipient {
+                owner: *user,
+                share_bps: 8_000,
+            },
+            FeeRecipient {
+                owner: fee_recipient.pubkey(),
+                share_bps: 2_000,
+            },
+        ],
         inputs,
         outputs,
+        close_policy: ClosePolicy::NoOutstanding,
+        limits: ExecutionLimits {
+            max_total_executions: Some(TRANSFORM_QUANTITY * 10),
+            max_executions_per_wallet: Some(TRANSFORM_QUANTITY),
+            cooldown_slots: None,
+        },
+        gate: None,
+        allowlist: None,
+        permit_signer: None,
+        pack: None,
+        buyout: None,
+        index: None,
     };
     let spl_token_rent =
         client.get_minimum_balance_for_rent_exemption(spl_token::state::Account::LEN)?;
@@ -137,7 +207,7 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
         Some(user),
     )?;
 
-    let instructions = InstructionBuilder::create_trarnsform_output_accounts(
+    let instructions = InstructionBuilder::create_transform_output_accounts(
         user,
         spl_token_rent,
         spl_mint_rent,
@@ -152,7 +222,6 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
             &output_mint1,
             &output_mint2,
             &output1_program_account,
-            &output2_program_account,
         ],
         Some(user),
     )?;
@@ -164,9 +233,30 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
     println!("created program accounts");
 
     let (tokenitis_pub, _) = tokenitis::state::Tokenitis::find_tokenitis_address(&tokenitis::id());
+    // initialize tokenitis with the user as admin on a fresh validator, has to be signed by
+    // the upgrade authority which is the default cli keypair that deployed the program
+    if client.get_account(&tokenitis_pub).is_err() {
+        let deployer_keypair =
+            read_keypair_file(format!("{}/.config/solana/id.json", std::env::var("HOME")?))?;
+        let deployer = &deployer_keypair.pubkey();
+        let instructions = InstructionBuilder::initialize_tokenitis(
+            tokenitis::id(),
+            deployer,
+            InitializeTokenitisArgs {
+                admin: *user,
+                config: TokenitisConfig::default(),
+            },
+        )?;
+        let sig = create_and_send_tx(
+            &client,
+            instructions,
+            vec![&deployer_keypair],
+            Some(deployer),
+        )?;
+        confirm_transactions(&client, vec![sig])?;
+    }
     let tokenitis_account = client.get_account(&tokenitis_pub)?;
-    let tokenitis_state = Tokenitis::try_from_slice(tokenitis_account.data())
-        .unwrap_or(Tokenitis { num_transforms: 0 });
+    let tokenitis_state = Tokenitis::try_from_slice(tokenitis_account.data())?;
 
     let instructions = InstructionBuilder::create_transform(
         tokenitis::id(),
@@ -178,6 +268,13 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
     confirm_transactions(&client, vec![sig])?;
     println!("initialized tokenitis - args - {:?}\n", args);
 
+    // protocol fee is set by the admin, it is 0 unless the deployment has set it
+    let tokenitis_state = Tokenitis::try_from_slice(client.get_account(&tokenitis_pub)?.data())?;
+    let protocol_fee = tokenitis::util::calculate_fee(
+        TRANSFORM_AMOUNT * TRANSFORM_QUANTITY,
+        &tokenitis_state.protocol_fee(),
+    )?;
+
     // Create user token accounts
     let mut instructions: Vec<Instruction> = Vec::new();
     let mut user_inputs: BTreeMap<Pubkey, Pubkey> = BTreeMap::new();
@@ -192,9 +289,15 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
     user_outputs.insert(output_mint2.pubkey(), output2_user_account.pubkey());
     let account_rent = client.get_minimum_balance_for_rent_exemption(Account::LEN)?;
     for (mint, user_account) in user_inputs.iter() {
-        InstructionBuilder::create_spl_token_account(mint, user_account, user, account_rent)?
-            .iter()
-            .for_each(|i| instructions.push(i.clone()));
+        InstructionBuilder::create_spl_token_account(
+            &spl_token::ID,
+            mint,
+            user_account,
+            user,
+            account_rent,
+        )?
+        .iter()
+        .for_each(|i| instructions.push(i.clone()));
         instructions.push(mint_to_checked(
             &spl_token::ID,
             mint,
@@ -206,9 +309,15 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
         )?)
     }
     for (mint, user_account) in user_outputs.iter() {
-        InstructionBuilder::create_spl_token_account(mint, user_account, user, account_rent)?
-            .iter()
-            .for_each(|i| instructions.push(i.clone()));
+        InstructionBuilder::create_spl_token_account(
+            &spl_token::ID,
+            mint,
+            user_account,
+            user,
+            account_rent,
+        )?
+        .iter()
+        .for_each(|i| instructions.push(i.clone()));
     }
     let sig = create_and_send_tx(
         &client,
@@ -280,10 +389,10 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
     );
     assert_eq!(
         client
-            .get_token_account_balance(&output2_program_account.pubkey())?
+            .get_token_supply(&output_mint2.pubkey())?
             .amount
             .parse::<u64>()?,
-        OUTPUT_PROGRAM_ACC_SUPPLY,
+        0,
     );
 
     let (transform_pub, _) = tokenitis::state::Tokenitis::find_transform_address(
@@ -293,9 +402,50 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
     let transform_account = client.get_account(&transform_pub)?;
     let transform_state = Transform::try_from_slice(transform_account.data())?;
 
+    // Update transform metadata
+    let metadata = TransformMetadata {
+        name: "test123-updated".to_string(),
+        image: "https://example.com/test123.png".to_string(),
+    };
+    let instructions = InstructionBuilder::update_transform(
+        tokenitis::id(),
+        user,
+        transform_state.id,
+        UpdateTransformArgs {
+            metadata: metadata.clone(),
+        },
+    )?;
+    let sig = create_and_send_tx(&client, instructions, vec![&user_keypair], Some(user))?;
+    confirm_transactions(&client, vec![sig])?;
+    let transform_account = client.get_account(&transform_pub)?;
+    let transform_state = Transform::try_from_slice(transform_account.data())?;
+    assert_eq!(transform_state.metadata, metadata);
+    println!("updated transform metadata - {:?}\n", metadata);
+
+    // Pause and resume transform
+    for status in [TransformStatus::Paused, TransformStatus::Active] {
+        let instructions = InstructionBuilder::set_transform_status(
+            tokenitis::id(),
+            user,
+            transform_state.id,
+            SetTransformStatusArgs { status },
+        )?;
+        let sig = create_and_send_tx(&client, instructions, vec![&user_keypair], Some(user))?;
+        confirm_transactions(&client, vec![sig])?;
+        let transform_account = client.get_account(&transform_pub)?;
+        assert_eq!(
+            Transform::try_from_slice(transform_account.data())?.status,
+            status
+        );
+    }
+    println!("paused and resumed transform\n");
+
     // Execute tokenitis forward
     let args = ExecuteTransformArgs {
         direction: Direction::Forward,
+        quantity: TRANSFORM_QUANTITY,
+        allowlist_proof: None,
+        permit: None,
     };
     let instructions = InstructionBuilder::execute_transform(
         tokenitis::id(),
@@ -304,6 +454,7 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
         args.clone(),
         user_inputs.clone(),
         user_outputs.clone(),
+        BTreeMap::new(),
     )?;
     let sig = create_and_send_tx(&client, instructions, vec![&user_keypair], Some(user))?;
     confirm_transactions(&client, vec![sig])?;
@@ -313,27 +464,41 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
         args,
     );
 
-    let fee = tokenitis::util::calculate_fee(TRANSFORM_AMOUNT, FEE_PERCENT);
-    let fee_account1 =
-        spl_associated_token_account::get_associated_token_address(user, &input_mint1.pubkey());
-    let fee_account2 =
-        spl_associated_token_account::get_associated_token_address(user, &input_mint2.pubkey());
+    let (wallet_pub, _) =
+        Tokenitis::find_wallet_address(&tokenitis::id(), transform_state.id, user);
+    let wallet_state = TransformWallet::try_from_slice(client.get_account(&wallet_pub)?.data())?;
+    assert_eq!(wallet_state.executions, TRANSFORM_QUANTITY);
+    let transform_account = client.get_account(&transform_pub)?;
+    let executed_transform = Transform::try_from_slice(transform_account.data())?;
+    assert_eq!(executed_transform.total_executions, TRANSFORM_QUANTITY);
+
+    let fee = tokenitis::util::calculate_fee(TRANSFORM_AMOUNT * TRANSFORM_QUANTITY, &FEE)?;
+    let (fee_vault1, _) = Tokenitis::find_fee_vault_address(
+        &tokenitis::id(),
+        transform_state.id,
+        &input_mint1.pubkey(),
+    );
+    let (fee_vault2, _) = Tokenitis::find_fee_vault_address(
+        &tokenitis::id(),
+        transform_state.id,
+        &input_mint2.pubkey(),
+    );
 
     println!(
-        "fee - {}, fee account1 - {:?}, fee account2 - {:?}\n",
-        fee, fee_account1, fee_account2
+        "fee - {}, fee vault1 - {:?}, fee vault2 - {:?}\n",
+        fee, fee_vault1, fee_vault2
     );
 
     assert_eq!(
         client
-            .get_token_account_balance(&fee_account1)?
+            .get_token_account_balance(&fee_vault1)?
             .amount
             .parse::<u64>()?,
         fee,
     );
     assert_eq!(
         client
-            .get_token_account_balance(&fee_account2)?
+            .get_token_account_balance(&fee_vault2)?
             .amount
             .parse::<u64>()?,
         fee,
@@ -343,69 +508,73 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
             .get_token_account_balance(&input1_user_account.pubkey())?
             .amount
             .parse::<u64>()?,
-        INPUT_CALLER_ACC_SUPPLY - TRANSFORM_AMOUNT - fee,
+        INPUT_CALLER_ACC_SUPPLY - TRANSFORM_AMOUNT * TRANSFORM_QUANTITY - fee - protocol_fee,
     );
     assert_eq!(
         client
             .get_token_account_balance(&input2_user_account.pubkey())?
             .amount
             .parse::<u64>()?,
-        INPUT_CALLER_ACC_SUPPLY - TRANSFORM_AMOUNT - fee,
+        INPUT_CALLER_ACC_SUPPLY - TRANSFORM_AMOUNT * TRANSFORM_QUANTITY - fee - protocol_fee,
     );
     assert_eq!(
         client
             .get_token_account_balance(&output1_user_account.pubkey())?
             .amount
             .parse::<u64>()?,
-        TRANSFORM_AMOUNT
+        TRANSFORM_AMOUNT * TRANSFORM_QUANTITY
     );
     assert_eq!(
         client
             .get_token_account_balance(&output2_user_account.pubkey())?
             .amount
             .parse::<u64>()?,
-        TRANSFORM_AMOUNT
+        TRANSFORM_AMOUNT * TRANSFORM_QUANTITY
     );
     assert_eq!(
         client
             .get_token_account_balance(&input1_program_account.pubkey())?
             .amount
             .parse::<u64>()?,
-        TRANSFORM_AMOUNT
+        TRANSFORM_AMOUNT * TRANSFORM_QUANTITY
     );
     assert_eq!(
         client
             .get_token_account_balance(&input2_program_account.pubkey())?
             .amount
             .parse::<u64>()?,
-        TRANSFORM_AMOUNT,
+        TRANSFORM_AMOUNT * TRANSFORM_QUANTITY,
     );
     assert_eq!(
         client
             .get_token_account_balance(&output1_program_account.pubkey())?
             .amount
             .parse::<u64>()?,
-        OUTPUT_PROGRAM_ACC_SUPPLY - TRANSFORM_AMOUNT,
+        OUTPUT_PROGRAM_ACC_SUPPLY - TRANSFORM_AMOUNT * TRANSFORM_QUANTITY,
     );
     assert_eq!(
         client
-            .get_token_account_balance(&output2_program_account.pubkey())?
+            .get_token_supply(&output_mint2.pubkey())?
             .amount
             .parse::<u64>()?,
-        OUTPUT_PROGRAM_ACC_SUPPLY - TRANSFORM_AMOUNT,
+        TRANSFORM_AMOUNT * TRANSFORM_QUANTITY,
     );
 
     // Execute tokenitis reverse
     let args = ExecuteTransformArgs {
         direction: Direction::Reverse,
+        quantity: TRANSFORM_QUANTITY,
+        allowlist_proof: None,
+        permit: None,
     };
     let instructions = InstructionBuilder::execute_transform(
         tokenitis::id(),
         user,
-        transform_state,
+        transform_state.clone(),
         args.clone(),
-        user_inputs,
-        user_outputs,
+        user_inputs.clone(),
+        user_outputs.clone(),
+        BTreeMap::new(),
     )?;
     let sig = create_and_send_tx(&client, instructions, vec![&user_keypair], Some(user))?;
     confirm_transactions(&client, vec![sig])?;
@@ -417,14 +586,14 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
 
     assert_eq!(
         client
-            .get_token_account_balance(&fee_account1)?
+            .get_token_account_balance(&fee_vault1)?
             .amount
             .parse::<u64>()?,
         fee,
     );
     assert_eq!(
         client
-            .get_token_account_balance(&fee_account2)?
+            .get_token_account_balance(&fee_vault2)?
             .amount
             .parse::<u64>()?,
         fee,
@@ -434,14 +603,14 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
             .get_token_account_balance(&input1_user_account.pubkey())?
             .amount
             .parse::<u64>()?,
-        INPUT_CALLER_ACC_SUPPLY - fee,
+        INPUT_CALLER_ACC_SUPPLY - fee - 2 * protocol_fee,
     );
     assert_eq!(
         client
             .get_token_account_balance(&input2_user_account.pubkey())?
             .amount
             .parse::<u64>()?,
-        INPUT_CALLER_ACC_SUPPLY - fee,
+        INPUT_CALLER_ACC_SUPPLY - fee - 2 * protocol_fee,
     );
     assert_eq!(
         client
@@ -480,7 +649,64 @@ fn basic() -> Result<(), Box<dyn std::error::Error>> {
     );
     assert_eq!(
         client
-            .get_token_account_balance(&output2_program_account.pubkey())?
+            .get_token_supply(&output_mint2.pubkey())?
+            .amount
+            .parse::<u64>()?,
+        0,
+    );
+
+    // Withdraw fees, split between the user and the fee recipient
+    let instructions =
+        InstructionBuilder::withdraw_fees(tokenitis::id(), user, transform_state.clone())?;
+    let sig = create_and_send_tx(&client, instructions, vec![&user_keypair], Some(user))?;
+    confirm_transactions(&client, vec![sig])?;
+
+    println!("successfully withdrew fees\n");
+
+    let fee_splits = tokenitis::util::split_fee(fee, &transform_state.fee_splits())?;
+    for mint in [input_mint1.pubkey(), input_mint2.pubkey()] {
+        let user_fee_account =
+            spl_associated_token_account::get_associated_token_address(user, &mint);
+        let recipient_fee_account = spl_associated_token_account::get_associated_token_address(
+            &fee_recipient.pubkey(),
+            &mint,
+        );
+        assert_eq!(
+            client
+                .get_token_account_balance(&user_fee_account)?
+                .amount
+                .parse::<u64>()?,
+            fee_splits[0],
+        );
+        assert_eq!(
+            client
+                .get_token_account_balance(&recipient_fee_account)?
+                .amount
+                .parse::<u64>()?,
+            fee_splits[1],
+        );
+    }
+
+    // Close transform, user is the creator so escrow is returned to the user accounts
+    let instructions = InstructionBuilder::close_transform(
+        tokenitis::id(),
+        user,
+        transform_state,
+        user_inputs,
+        user_outputs,
+    )?;
+    let sig = create_and_send_tx(&client, instructions, vec![&user_keypair], Some(user))?;
+    confirm_transactions(&client, vec![sig])?;
+
+    println!("successfully closed transform\n");
+
+    assert!(client.get_account(&transform_pub).is_err());
+    assert!(client
+        .get_account(&output1_program_account.pubkey())
+        .is_err());
+    assert_eq!(
+        client
+            .get_token_account_balance(&output1_user_account.pubkey())?
             .amount
             .parse::<u64>()?,
         OUTPUT_PROGRAM_ACC_SUPPLY,
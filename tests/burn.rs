@@ -0,0 +1,56 @@
+mod common;
+
+use common::*;
+use solana_program::program_error::ProgramError;
+use std::collections::BTreeMap;
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+#[test]
+fn execute_burns_inputs() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let (input_mint, input) = burn_input(&mut bank, 3);
+    let (output_mint, output) = escrow_output(&mut bank, &creator, 1, 10);
+    let mut args = transform_args(
+        BTreeMap::from([(input_mint, input)]),
+        BTreeMap::from([(output_mint, output)]),
+    );
+    args.reverse.enabled = false;
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+    let supply = bank.mint_supply(&input_mint);
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 2),
+        &accounts,
+    )
+    .unwrap();
+
+    assert_eq!(bank.token_balance(&accounts.0[&input_mint]), 4);
+    assert_eq!(bank.mint_supply(&input_mint), supply - 6);
+    assert_eq!(bank.token_balance(&accounts.1[&output_mint]), 2);
+}
+
+#[test]
+fn create_rejects_reversible_burn() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let (input_mint, input) = burn_input(&mut bank, 3);
+    let (output_mint, output) = escrow_output(&mut bank, &creator, 1, 10);
+    let args = transform_args(
+        BTreeMap::from([(input_mint, input)]),
+        BTreeMap::from([(output_mint, output)]),
+    );
+    assert_eq!(
+        create_transform(&mut bank, &creator, args),
+        Err(ProgramError::InvalidArgument)
+    );
+}
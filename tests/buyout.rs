@@ -0,0 +1,281 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+use spl_token::instruction::{burn, transfer};
+use std::collections::BTreeMap;
+use tokenitis::errors::TokenitisError;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{Buyout, BuyoutConfig, Tokenitis, TransformStatus};
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+use tokenitis::tokenitis_instruction::place_bid::PlaceBidArgs;
+use tokenitis::tokenitis_instruction::redeem_fractions::RedeemFractionsArgs;
+use tokenitis::tokenitis_instruction::set_transform_status::SetTransformStatusArgs;
+
+const RESERVE_PRICE: u64 = 100;
+const DURATION: i64 = 60;
+const FRACTIONS: u64 = 100;
+
+struct BuyoutTest {
+    bank: Bank,
+    creator: Pubkey,
+    transform_num: u64,
+    input_mint: Pubkey,
+    fraction_mint: Pubkey,
+    quote_mint: Pubkey,
+    // fraction holders with their fraction and quote accounts
+    holders: Vec<(Pubkey, Pubkey, Pubkey)>,
+}
+
+impl BuyoutTest {
+    // input is fractionalized and the fractions are split 33, 33, 34 between three holders
+    fn new() -> Self {
+        let mut bank = Bank::new();
+        let creator = bank.new_wallet();
+        initialize_tokenitis(&mut bank, &creator).unwrap();
+
+        let quote_mint = bank.create_mint(None, 0);
+        let (input_mint, input) = escrow_input(&mut bank, &creator, 1);
+        let (fraction_mint, fractions) = escrow_output(&mut bank, &creator, FRACTIONS, FRACTIONS);
+        let mut args = transform_args(
+            BTreeMap::from([(input_mint, input)]),
+            BTreeMap::from([(fraction_mint, fractions)]),
+        );
+        args.buyout = Some(BuyoutConfig {
+            quote_mint,
+            reserve_price: RESERVE_PRICE,
+            duration: DURATION,
+        });
+        let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+
+        let owner = bank.new_wallet();
+        let accounts = caller_accounts(&mut bank, transform_num, &owner, 1);
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &owner,
+            execute_args(Direction::Forward, 1),
+            &accounts,
+        )
+        .unwrap();
+
+        let owner_fractions = accounts.1[&fraction_mint];
+        let mut holders = vec![(
+            owner,
+            owner_fractions,
+            bank.create_token_account(&quote_mint, &owner, 0),
+        )];
+        for _ in 0..2 {
+            let holder = bank.new_wallet();
+            let holder_fractions = bank.create_token_account(&fraction_mint, &holder, 0);
+            let instruction = transfer(
+                &spl_token::id(),
+                &owner_fractions,
+                &holder_fractions,
+                &owner,
+                &[],
+                33,
+            )
+            .unwrap();
+            bank.process(vec![instruction], &[owner]).unwrap();
+            let holder_quote = bank.create_token_account(&quote_mint, &holder, 0);
+            holders.push((holder, holder_fractions, holder_quote));
+        }
+
+        BuyoutTest {
+            bank,
+            creator,
+            transform_num,
+            input_mint,
+            fraction_mint,
+            quote_mint,
+            holders,
+        }
+    }
+
+    fn bidder(&mut self, balance: u64) -> (Pubkey, Pubkey) {
+        let bidder = self.bank.new_wallet();
+        let quote = self.quote_mint;
+        let bidder_quote = self.bank.create_token_account(&quote, &bidder, balance);
+        (bidder, bidder_quote)
+    }
+
+    fn bid(
+        &mut self,
+        bidder: &(Pubkey, Pubkey),
+        previous_bidder: Option<&(Pubkey, Pubkey)>,
+        amount: u64,
+    ) -> ProgramResult {
+        let instructions = InstructionBuilder::place_bid(
+            tokenitis::id(),
+            &bidder.0,
+            transform_state(&self.bank, self.transform_num),
+            &bidder.1,
+            previous_bidder.map(|(_, quote)| quote),
+            PlaceBidArgs { amount },
+        )
+        .unwrap();
+        self.bank.process(instructions, &[bidder.0])
+    }
+
+    fn claim(&mut self, bidder: &Pubkey) -> ProgramResult {
+        let instructions = InstructionBuilder::claim_buyout(
+            tokenitis::id(),
+            bidder,
+            transform_state(&self.bank, self.transform_num),
+        )
+        .unwrap();
+        self.bank.process(instructions, &[])
+    }
+
+    fn redeem(&mut self, holder: usize, amount: u64) -> ProgramResult {
+        let (holder, holder_fractions, holder_quote) = self.holders[holder];
+        let instructions = InstructionBuilder::redeem_fractions(
+            tokenitis::id(),
+            &holder,
+            transform_state(&self.bank, self.transform_num),
+            &holder_fractions,
+            &holder_quote,
+            RedeemFractionsArgs { amount },
+        )
+        .unwrap();
+        self.bank.process(instructions, &[holder])
+    }
+}
+
+#[test]
+fn bid_below_reserve_rejected() {
+    let mut test = BuyoutTest::new();
+    let bidder = test.bidder(1_000);
+    assert_eq!(
+        test.bid(&bidder, None, RESERVE_PRICE - 1),
+        Err(custom_error(TokenitisError::BidTooLow))
+    );
+    test.bid(&bidder, None, RESERVE_PRICE).unwrap();
+}
+
+#[test]
+fn outbid_refunds_previous_bidder() {
+    let mut test = BuyoutTest::new();
+    let first = test.bidder(1_000);
+    let second = test.bidder(1_000);
+    test.bid(&first, None, 500).unwrap();
+    assert_eq!(test.bank.token_balance(&first.1), 500);
+
+    assert_eq!(
+        test.bid(&second, Some(&first), 500),
+        Err(custom_error(TokenitisError::BidTooLow))
+    );
+    test.bid(&second, Some(&first), 600).unwrap();
+    assert_eq!(test.bank.token_balance(&first.1), 1_000);
+    assert_eq!(test.bank.token_balance(&second.1), 400);
+}
+
+#[test]
+fn claim_before_end_rejected() {
+    let mut test = BuyoutTest::new();
+    let bidder = test.bidder(1_000);
+    test.bid(&bidder, None, 500).unwrap();
+    let input_mint = test.input_mint;
+    let bidder_input = test
+        .bank
+        .create_associated_token_account(&input_mint, &bidder.0, 0);
+
+    test.bank
+        .warp_to_timestamp(START_UNIX_TIMESTAMP + DURATION - 1);
+    assert_eq!(
+        test.claim(&bidder.0),
+        Err(custom_error(TokenitisError::BuyoutNotEnded))
+    );
+
+    test.bank.warp_to_timestamp(START_UNIX_TIMESTAMP + DURATION);
+    test.claim(&bidder.0).unwrap();
+    assert_eq!(test.bank.token_balance(&bidder_input), 1);
+}
+
+#[test]
+fn redemptions_add_up_to_proceeds() {
+    let mut test = BuyoutTest::new();
+    let bid = 1_001;
+    let bidder = test.bidder(bid);
+    test.bid(&bidder, None, bid).unwrap();
+    let input_mint = test.input_mint;
+    test.bank
+        .create_associated_token_account(&input_mint, &bidder.0, 0);
+    test.bank.warp_to_timestamp(START_UNIX_TIMESTAMP + DURATION);
+    test.claim(&bidder.0).unwrap();
+
+    // pro-rata payouts round down, the last holder receives the remainder
+    test.redeem(1, 33).unwrap();
+    test.redeem(2, 33).unwrap();
+    test.redeem(0, 34).unwrap();
+    let payouts: Vec<u64> = test
+        .holders
+        .iter()
+        .map(|(_, _, quote)| test.bank.token_balance(quote))
+        .collect();
+    assert_eq!(payouts, vec![341, 330, 330]);
+    assert_eq!(payouts.iter().sum::<u64>(), bid);
+}
+
+#[test]
+fn first_bid_requires_active_transform() {
+    let mut test = BuyoutTest::new();
+    let instructions = InstructionBuilder::set_transform_status(
+        tokenitis::id(),
+        &test.creator,
+        test.transform_num,
+        SetTransformStatusArgs {
+            status: TransformStatus::Paused,
+        },
+    )
+    .unwrap();
+    let creator = test.creator;
+    test.bank.process(instructions, &[creator]).unwrap();
+
+    let bidder = test.bidder(1_000);
+    assert_eq!(
+        test.bid(&bidder, None, RESERVE_PRICE),
+        Err(custom_error(TokenitisError::TransformPaused))
+    );
+}
+
+#[test]
+fn claim_without_outstanding_fractions() {
+    let mut test = BuyoutTest::new();
+    let bid = 500;
+    let bidder = test.bidder(bid);
+    test.bid(&bidder, None, bid).unwrap();
+
+    // holders burn every fraction before the buyout is claimed
+    let fraction_mint = test.fraction_mint;
+    for (holder, holder_fractions, _) in test.holders.clone() {
+        let amount = test.bank.token_balance(&holder_fractions);
+        let instruction = burn(
+            &spl_token::id(),
+            &holder_fractions,
+            &fraction_mint,
+            &holder,
+            &[],
+            amount,
+        )
+        .unwrap();
+        test.bank.process(vec![instruction], &[holder]).unwrap();
+    }
+
+    let input_mint = test.input_mint;
+    let bidder_input = test
+        .bank
+        .create_associated_token_account(&input_mint, &bidder.0, 0);
+    test.bank.warp_to_timestamp(START_UNIX_TIMESTAMP + DURATION);
+    test.claim(&bidder.0).unwrap();
+    assert_eq!(test.bank.token_balance(&bidder_input), 1);
+
+    // nothing can be redeemed, the proceeds stay in the buyout vault
+    let (buyout, _) = Tokenitis::find_buyout_address(&tokenitis::id(), test.transform_num);
+    let buyout_state: Buyout = test.bank.state(&buyout);
+    assert_eq!(buyout_state.outstanding_fractions, 0);
+    assert_eq!(buyout_state.proceeds, bid);
+    assert!(test.redeem(0, 1).is_err());
+}
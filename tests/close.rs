@@ -0,0 +1,129 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use tokenitis::state::ClosePolicy;
+use tokenitis::tokenitis_instruction::create_transform::CreateTransformArgs;
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+struct CloseTest {
+    bank: Bank,
+    creator: Pubkey,
+    transform_num: u64,
+    caller: Pubkey,
+    accounts: (BTreeMap<Pubkey, Pubkey>, BTreeMap<Pubkey, Pubkey>),
+    // receive what is left in the escrows on close
+    creator_accounts: (BTreeMap<Pubkey, Pubkey>, BTreeMap<Pubkey, Pubkey>),
+}
+
+impl CloseTest {
+    // caller holds 2 outputs for 2 inputs
+    fn new(configure: impl FnOnce(&mut CreateTransformArgs)) -> Self {
+        let mut bank = Bank::new();
+        let creator = bank.new_wallet();
+        initialize_tokenitis(&mut bank, &creator).unwrap();
+
+        let mut args = pair_args(&mut bank, &creator, 1);
+        configure(&mut args);
+        let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+        let caller = bank.new_wallet();
+        let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &caller,
+            execute_args(Direction::Forward, 2),
+            &accounts,
+        )
+        .unwrap();
+        let creator_accounts = caller_accounts(&mut bank, transform_num, &creator, 0);
+
+        CloseTest {
+            bank,
+            creator,
+            transform_num,
+            caller,
+            accounts,
+            creator_accounts,
+        }
+    }
+
+    fn close(&mut self) -> ProgramResult {
+        close_transform(
+            &mut self.bank,
+            self.transform_num,
+            &self.creator,
+            &self.creator_accounts,
+        )
+    }
+
+    fn reverse(&mut self, quantity: u64) {
+        execute_transform(
+            &mut self.bank,
+            self.transform_num,
+            &self.caller,
+            execute_args(Direction::Reverse, quantity),
+            &self.accounts,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn outstanding_ignores_supply_held_outside() {
+    let mut test = CloseTest::new(|_| {});
+    let output_mint = *test.accounts.1.keys().next().unwrap();
+    // supply the creator kept outside of the transform was never released by it
+    let creator = test.creator;
+    test.bank.create_token_account(&output_mint, &creator, 5);
+    assert_eq!(
+        transform_state(&test.bank, test.transform_num).outstanding_outputs[&output_mint],
+        2
+    );
+    assert_eq!(test.close(), Err(ProgramError::InvalidArgument));
+
+    test.reverse(2);
+    assert_eq!(
+        transform_state(&test.bank, test.transform_num).outstanding_outputs[&output_mint],
+        0
+    );
+    test.close().unwrap();
+    let creator_output = test.creator_accounts.1[&output_mint];
+    assert_eq!(test.bank.token_balance(&creator_output), 10);
+}
+
+#[test]
+fn anytime_keeps_inputs_backing_reversible_outputs() {
+    let mut test = CloseTest::new(|args| args.close_policy = ClosePolicy::Anytime);
+    let input_escrow = transform_state(&test.bank, test.transform_num)
+        .inputs
+        .values()
+        .next()
+        .unwrap()
+        .account;
+    assert_eq!(test.close(), Err(ProgramError::InvalidArgument));
+    assert_eq!(test.bank.token_balance(&input_escrow), 2);
+
+    // holders can still exit
+    test.reverse(2);
+    test.close().unwrap();
+}
+
+#[test]
+fn anytime_closes_one_way_transform() {
+    let mut test = CloseTest::new(|args| {
+        args.close_policy = ClosePolicy::Anytime;
+        args.reverse.enabled = false;
+    });
+    let input_mint = *test.accounts.0.keys().next().unwrap();
+    let output_mint = *test.accounts.1.keys().next().unwrap();
+
+    test.close().unwrap();
+    let (creator_inputs, creator_outputs) = &test.creator_accounts;
+    assert_eq!(test.bank.token_balance(&creator_inputs[&input_mint]), 2);
+    assert_eq!(test.bank.token_balance(&creator_outputs[&output_mint]), 8);
+    assert_eq!(test.bank.token_balance(&test.accounts.1[&output_mint]), 2);
+}
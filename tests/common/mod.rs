@@ -0,0 +1,631 @@
+#![allow(dead_code)]
+
+// runs the program in process with solana-program-test, the token programs are the ones
+// bundled with it, the associated token account program runs in process since the bundled
+// one has no idempotent create
+use borsh::BorshDeserialize;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::hash;
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::program_option::COption;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::slot_hashes::{SlotHashes, MAX_ENTRIES};
+use solana_program::{bpf_loader_upgradeable, system_program};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{Account as SolanaAccount, AccountSharedData};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use spl_token::state::{Account, AccountState, Mint};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use tokenitis::errors::TokenitisError;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{
+    ClosePolicy, DirectionPolicy, ExecutionLimits, Token, TokenMode, TokenProgram, Tokenitis,
+    TokenitisConfig, Transform, TransformMetadata,
+};
+use tokenitis::tokenitis_instruction::create_transform::CreateTransformArgs;
+use tokenitis::tokenitis_instruction::execute_transform::{Direction, ExecuteTransformArgs};
+use tokenitis::tokenitis_instruction::initialize_tokenitis::InitializeTokenitisArgs;
+use tokenitis::tokenitis_instruction::update_tokenitis_config::UpdateTokenitisConfigArgs;
+use tokenitis::util::token::{associated_token_address, token_2022};
+use tokio::runtime::Runtime;
+
+pub const START_UNIX_TIMESTAMP: i64 = 1_000_000;
+const WALLET_LAMPORTS: u64 = 100_000_000_000;
+
+// the program ties the lifetime of the account slice to the account infos, the leaked
+// clones share their lamports and data with the account infos of the runtime
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    tokenitis::entrypoint::process_instruction(program_id, accounts, data)
+}
+
+// idempotent create does nothing if the account exists
+fn process_associated_token_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if data == [1] && *accounts[1].owner == *accounts[5].key {
+        return Ok(());
+    }
+    spl_associated_token_account::processor::process_create_associated_token_account(
+        program_id, accounts,
+    )
+}
+
+// transaction errors are returned as the error of the failed instruction
+fn program_error(error: BanksClientError) -> ProgramError {
+    match error.unwrap() {
+        TransactionError::InstructionError(_, error) => ProgramError::try_from(error)
+            .unwrap_or_else(|error| panic!("instruction failed - {:?}", error)),
+        error => panic!("transaction failed - {:?}", error),
+    }
+}
+
+pub struct Bank {
+    context: ProgramTestContext,
+    // keypairs of the wallets created by the bank, transactions are signed with them
+    wallets: BTreeMap<Pubkey, Keypair>,
+    clock: Clock,
+    runtime: Runtime,
+}
+
+impl Bank {
+    pub fn new() -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mut program_test = ProgramTest::new(
+            "tokenitis",
+            tokenitis::id(),
+            processor!(process_instruction),
+        );
+        program_test.add_program(
+            "spl_token_2022",
+            token_2022::id(),
+            processor!(spl_token_2022::processor::Processor::process),
+        );
+        program_test.add_program(
+            "spl_associated_token_account",
+            spl_associated_token_account::id(),
+            processor!(process_associated_token_account),
+        );
+        let context = runtime.block_on(program_test.start_with_context());
+        let mut bank = Bank {
+            context,
+            wallets: BTreeMap::new(),
+            clock: Clock::default(),
+            runtime,
+        };
+        bank.set_clock(Clock {
+            slot: 1,
+            unix_timestamp: START_UNIX_TIMESTAMP,
+            ..Clock::default()
+        });
+        bank
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+
+    // slot hashes hold the hash of the slot number for the last 512 slots
+    pub fn set_clock(&mut self, clock: Clock) {
+        let slot_hashes: Vec<_> = (clock.slot.saturating_sub(MAX_ENTRIES as u64)..clock.slot)
+            .map(|slot| (slot, hash(&slot.to_le_bytes())))
+            .collect();
+        self.context.set_sysvar(&SlotHashes::new(&slot_hashes));
+        self.context.set_sysvar(&clock);
+        self.clock = clock;
+    }
+
+    pub fn warp_to_timestamp(&mut self, unix_timestamp: i64) {
+        self.set_clock(Clock {
+            unix_timestamp,
+            ..self.clock()
+        });
+    }
+
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        self.set_clock(Clock {
+            slot,
+            ..self.clock()
+        });
+    }
+
+    pub fn rent(&self) -> Rent {
+        let mut banks_client = self.context.banks_client.clone();
+        self.runtime.block_on(banks_client.get_rent()).unwrap()
+    }
+
+    pub fn account(&self, key: &Pubkey) -> Option<SolanaAccount> {
+        let mut banks_client = self.context.banks_client.clone();
+        self.runtime
+            .block_on(banks_client.get_account(*key))
+            .unwrap()
+    }
+
+    pub fn set_account(&mut self, key: &Pubkey, account: SolanaAccount) {
+        self.context
+            .set_account(key, &AccountSharedData::from(account));
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.account(key)
+            .map(|account| account.lamports)
+            .unwrap_or(0)
+    }
+
+    pub fn state<T: BorshDeserialize>(&self, key: &Pubkey) -> T {
+        T::deserialize(&mut &self.account(key).unwrap().data[..]).unwrap()
+    }
+
+    pub fn fund(&mut self, key: &Pubkey, lamports: u64) {
+        let mut account = self.account(key).unwrap_or(SolanaAccount {
+            owner: system_program::id(),
+            ..SolanaAccount::default()
+        });
+        account.lamports += lamports;
+        self.set_account(key, account);
+    }
+
+    pub fn new_wallet(&mut self) -> Pubkey {
+        let keypair = Keypair::new();
+        let wallet = keypair.pubkey();
+        self.fund(&wallet, WALLET_LAMPORTS);
+        self.wallets.insert(wallet, keypair);
+        wallet
+    }
+
+    // signs the message with the keypair of a wallet created by the bank
+    pub fn sign(&self, wallet: &Pubkey, message: &[u8]) -> [u8; 64] {
+        let signature = self.wallets[wallet].sign_message(message);
+        <[u8; 64]>::try_from(signature.as_ref()).unwrap()
+    }
+
+    pub fn set_owner(&mut self, key: &Pubkey, owner: &Pubkey) {
+        let mut account = self.account(key).unwrap();
+        account.owner = *owner;
+        self.set_account(key, account);
+    }
+
+    // program data account of the deployed program, written the way the upgradeable
+    // loader serializes it
+    pub fn set_upgrade_authority(&mut self, upgrade_authority: &Pubkey) {
+        let (program_data, _) = Pubkey::find_program_address(
+            &[tokenitis::id().as_ref()],
+            &bpf_loader_upgradeable::id(),
+        );
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(upgrade_authority.as_ref());
+        let lamports = self.rent().minimum_balance(data.len());
+        self.set_account(
+            &program_data,
+            SolanaAccount {
+                lamports,
+                data,
+                owner: bpf_loader_upgradeable::id(),
+                ..SolanaAccount::default()
+            },
+        );
+    }
+
+    pub fn create_mint(&mut self, mint_authority: Option<&Pubkey>, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0; Mint::LEN];
+        Mint {
+            mint_authority: mint_authority.cloned().into(),
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        self.set_token_program_account(&mint, data, 0, &spl_token::id());
+        mint
+    }
+
+    // token 2022 mint with the same transfer fee in every epoch, the mint is padded to the
+    // account length and followed by the mint account type and the transfer fee config
+    pub fn create_transfer_fee_mint(&mut self, bps: u16, maximum_fee: u64) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0; Account::LEN];
+        Mint {
+            decimals: 0,
+            is_initialized: true,
+            ..Mint::default()
+        }
+        .pack_into_slice(&mut data[..Mint::LEN]);
+        data.push(1);
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&108u16.to_le_bytes());
+        // no fee authorities and nothing withheld
+        data.extend_from_slice(&[0; 72]);
+        for _ in 0..2 {
+            data.extend_from_slice(&0u64.to_le_bytes());
+            data.extend_from_slice(&maximum_fee.to_le_bytes());
+            data.extend_from_slice(&bps.to_le_bytes());
+        }
+        self.set_token_program_account(&mint, data, 0, &token_2022::id());
+        mint
+    }
+
+    // wrapped sol mint, it has no supply
+    pub fn create_native_mint(&mut self) -> Pubkey {
+        let mint = spl_token::native_mint::id();
+        let mut data = vec![0; Mint::LEN];
+        Mint {
+            decimals: spl_token::native_mint::DECIMALS,
+            is_initialized: true,
+            ..Mint::default()
+        }
+        .pack_into_slice(&mut data);
+        self.set_token_program_account(&mint, data, 0, &spl_token::id());
+        mint
+    }
+
+    // amount is added to the supply of the mint, or to the lamports of a wrapped sol account
+    pub fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let token_account = Pubkey::new_unique();
+        self.create_token_account_at(&token_account, mint, owner, amount);
+        token_account
+    }
+
+    pub fn create_associated_token_account(
+        &mut self,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let token_account = associated_token_address(owner, mint, &spl_token::id());
+        self.create_token_account_at(&token_account, mint, owner, amount);
+        token_account
+    }
+
+    pub fn create_token_account_at(
+        &mut self,
+        token_account: &Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) {
+        let mut data = vec![0; Account::LEN];
+        let native = *mint == spl_token::native_mint::id();
+        Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: AccountState::Initialized,
+            is_native: if native {
+                COption::Some(self.rent().minimum_balance(Account::LEN))
+            } else {
+                COption::None
+            },
+            ..Account::default()
+        }
+        .pack_into_slice(&mut data);
+        if native {
+            self.set_token_program_account(token_account, data, amount, &spl_token::id());
+            return;
+        }
+
+        let mut mint_account = self.account(mint).unwrap();
+        // accounts of transfer fee mints hold the withheld fee
+        if mint_account.data.len() > Account::LEN {
+            data.push(2);
+            data.extend_from_slice(&2u16.to_le_bytes());
+            data.extend_from_slice(&8u16.to_le_bytes());
+            data.extend_from_slice(&0u64.to_le_bytes());
+        }
+        self.set_token_program_account(token_account, data, 0, &mint_account.owner);
+
+        let mut mint_info = Mint::unpack(&mint_account.data[..Mint::LEN]).unwrap();
+        mint_info.supply += amount;
+        mint_info.pack_into_slice(&mut mint_account.data[..Mint::LEN]);
+        self.set_account(mint, mint_account);
+    }
+
+    // rent exempt account owned by the token program, with lamports on top of the rent
+    fn set_token_program_account(
+        &mut self,
+        key: &Pubkey,
+        data: Vec<u8>,
+        lamports: u64,
+        token_program: &Pubkey,
+    ) {
+        let lamports = self.rent().minimum_balance(data.len()) + lamports;
+        self.set_account(
+            key,
+            SolanaAccount {
+                lamports,
+                data,
+                owner: *token_program,
+                ..SolanaAccount::default()
+            },
+        );
+    }
+
+    pub fn token_balance(&self, token_account: &Pubkey) -> u64 {
+        Account::unpack(&self.account(token_account).unwrap().data[..Account::LEN])
+            .unwrap()
+            .amount
+    }
+
+    pub fn mint_supply(&self, mint: &Pubkey) -> u64 {
+        Mint::unpack(&self.account(mint).unwrap().data[..Mint::LEN])
+            .unwrap()
+            .supply
+    }
+
+    // instructions are sent as one transaction signed by the given wallets, fees are
+    // paid by a new payer so balances of the wallets only change by what the program does
+    pub fn process(&mut self, instructions: Vec<Instruction>, signers: &[Pubkey]) -> ProgramResult {
+        for meta in instructions
+            .iter()
+            .flat_map(|instruction| &instruction.accounts)
+        {
+            if meta.is_signer && !signers.contains(&meta.pubkey) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        }
+
+        let payer = Keypair::new();
+        self.fund(&payer.pubkey(), WALLET_LAMPORTS);
+        let mut keypairs = vec![&payer];
+        for signer in signers {
+            if !keypairs.iter().any(|keypair| keypair.pubkey() == *signer) {
+                keypairs.push(&self.wallets[signer]);
+            }
+        }
+
+        let blockhash = self
+            .runtime
+            .block_on(self.context.banks_client.get_latest_blockhash())
+            .unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &keypairs,
+            blockhash,
+        );
+        self.runtime
+            .block_on(self.context.banks_client.process_transaction(transaction))
+            .map_err(program_error)
+    }
+}
+
+impl Default for Bank {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn custom_error(error: TokenitisError) -> ProgramError {
+    error.into()
+}
+
+pub fn direction_policy() -> DirectionPolicy {
+    DirectionPolicy {
+        enabled: true,
+        fee: None,
+        amounts: BTreeMap::new(),
+        start_unix_timestamp: None,
+        end_unix_timestamp: None,
+    }
+}
+
+pub fn transform_args(
+    inputs: BTreeMap<Pubkey, Token>,
+    outputs: BTreeMap<Pubkey, Token>,
+) -> CreateTransformArgs {
+    CreateTransformArgs {
+        metadata: TransformMetadata {
+            name: "test".to_string(),
+            image: "".to_string(),
+        },
+        forward: direction_policy(),
+        reverse: direction_policy(),
+        fee_recipients: vec![],
+        inputs,
+        outputs,
+        close_policy: ClosePolicy::NoOutstanding,
+        limits: ExecutionLimits::default(),
+        gate: None,
+        allowlist: None,
+        permit_signer: None,
+        pack: None,
+        buyout: None,
+        index: None,
+    }
+}
+
+pub fn token(account: Pubkey, amount: u64, mode: TokenMode) -> Token {
+    Token {
+        account,
+        amount,
+        mode,
+        token_program: TokenProgram::Token,
+        decimals: 0,
+    }
+}
+
+// escrowed input, the escrow is owned by the creator until the transform is created
+pub fn escrow_input(bank: &mut Bank, creator: &Pubkey, amount: u64) -> (Pubkey, Token) {
+    let mint = bank.create_mint(None, 0);
+    let escrow = bank.create_token_account(&mint, creator, 0);
+    (mint, token(escrow, amount, TokenMode::Escrow))
+}
+
+pub fn burn_input(bank: &mut Bank, amount: u64) -> (Pubkey, Token) {
+    let mint = bank.create_mint(None, 0);
+    (mint, token(mint, amount, TokenMode::Burn))
+}
+
+// escrowed output with a fixed supply held by the escrow
+pub fn escrow_output(
+    bank: &mut Bank,
+    creator: &Pubkey,
+    amount: u64,
+    supply: u64,
+) -> (Pubkey, Token) {
+    let mint = bank.create_mint(None, 0);
+    let escrow = bank.create_token_account(&mint, creator, supply);
+    (mint, token(escrow, amount, TokenMode::Escrow))
+}
+
+// one escrowed input for one escrowed output with a supply of 10, tests adjust the args
+// for the feature they cover
+pub fn pair_args(bank: &mut Bank, creator: &Pubkey, input_amount: u64) -> CreateTransformArgs {
+    let (input_mint, input) = escrow_input(bank, creator, input_amount);
+    let (output_mint, output) = escrow_output(bank, creator, 1, 10);
+    transform_args(
+        BTreeMap::from([(input_mint, input)]),
+        BTreeMap::from([(output_mint, output)]),
+    )
+}
+
+pub fn minted_output(bank: &mut Bank, creator: &Pubkey, amount: u64) -> (Pubkey, Token) {
+    let mint = bank.create_mint(Some(creator), 0);
+    (mint, token(mint, amount, TokenMode::Mint))
+}
+
+// admin deployed the program and initializes tokenitis
+pub fn initialize_tokenitis(bank: &mut Bank, admin: &Pubkey) -> ProgramResult {
+    bank.set_upgrade_authority(admin);
+    let instructions = InstructionBuilder::initialize_tokenitis(
+        tokenitis::id(),
+        admin,
+        InitializeTokenitisArgs {
+            admin: *admin,
+            config: TokenitisConfig::default(),
+        },
+    )
+    .unwrap();
+    bank.process(instructions, &[*admin])
+}
+
+pub fn tokenitis_state(bank: &Bank) -> Tokenitis {
+    let (tokenitis, _) = Tokenitis::find_tokenitis_address(&tokenitis::id());
+    bank.state(&tokenitis)
+}
+
+// returns the id of the created transform
+pub fn create_transform(
+    bank: &mut Bank,
+    creator: &Pubkey,
+    args: CreateTransformArgs,
+) -> Result<u64, ProgramError> {
+    let transform_num = tokenitis_state(bank).num_transforms + 1;
+    let instructions =
+        InstructionBuilder::create_transform(tokenitis::id(), creator, transform_num, args)
+            .unwrap();
+    bank.process(instructions, &[*creator])?;
+    Ok(transform_num)
+}
+
+pub fn transform_state(bank: &Bank, transform_num: u64) -> Transform {
+    let (transform, _) = Tokenitis::find_transform_address(&tokenitis::id(), transform_num);
+    bank.state(&transform)
+}
+
+// token accounts of the caller for every input and output of the transform,
+// input accounts hold input_balance
+pub fn caller_accounts(
+    bank: &mut Bank,
+    transform_num: u64,
+    caller: &Pubkey,
+    input_balance: u64,
+) -> (BTreeMap<Pubkey, Pubkey>, BTreeMap<Pubkey, Pubkey>) {
+    let transform_state = transform_state(bank, transform_num);
+    let inputs = transform_state
+        .inputs
+        .keys()
+        .map(|mint| {
+            (
+                *mint,
+                bank.create_token_account(mint, caller, input_balance),
+            )
+        })
+        .collect();
+    let outputs = transform_state
+        .outputs
+        .keys()
+        .map(|mint| (*mint, bank.create_token_account(mint, caller, 0)))
+        .collect();
+    (inputs, outputs)
+}
+
+pub fn execute_args(direction: Direction, quantity: u64) -> ExecuteTransformArgs {
+    ExecuteTransformArgs {
+        direction,
+        quantity,
+        allowlist_proof: None,
+        permit: None,
+    }
+}
+
+pub fn execute_transform(
+    bank: &mut Bank,
+    transform_num: u64,
+    caller: &Pubkey,
+    args: ExecuteTransformArgs,
+    accounts: &(BTreeMap<Pubkey, Pubkey>, BTreeMap<Pubkey, Pubkey>),
+) -> ProgramResult {
+    let instructions = InstructionBuilder::execute_transform(
+        tokenitis::id(),
+        caller,
+        transform_state(bank, transform_num),
+        args,
+        accounts.0.clone(),
+        accounts.1.clone(),
+        BTreeMap::new(),
+    )
+    .unwrap();
+    bank.process(instructions, &[*caller])
+}
+
+// what is left in the escrows is returned to the creator accounts
+pub fn close_transform(
+    bank: &mut Bank,
+    transform_num: u64,
+    creator: &Pubkey,
+    creator_accounts: &(BTreeMap<Pubkey, Pubkey>, BTreeMap<Pubkey, Pubkey>),
+) -> ProgramResult {
+    let instructions = InstructionBuilder::close_transform(
+        tokenitis::id(),
+        creator,
+        transform_state(bank, transform_num),
+        creator_accounts.0.clone(),
+        creator_accounts.1.clone(),
+    )
+    .unwrap();
+    bank.process(instructions, &[*creator])
+}
+
+pub fn update_tokenitis_config(
+    bank: &mut Bank,
+    admin: &Pubkey,
+    config: TokenitisConfig,
+) -> ProgramResult {
+    let instructions = InstructionBuilder::update_tokenitis_config(
+        tokenitis::id(),
+        admin,
+        UpdateTokenitisConfigArgs { config },
+    )
+    .unwrap();
+    bank.process(instructions, &[*admin])
+}
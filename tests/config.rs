@@ -0,0 +1,116 @@
+mod common;
+
+use common::*;
+use solana_program::program_error::ProgramError;
+use std::collections::BTreeMap;
+use tokenitis::errors::TokenitisError;
+use tokenitis::state::{Fee, Rounding, TokenitisConfig};
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+#[test]
+fn forward_execute_checks_current_config() {
+    let mut bank = Bank::new();
+    let admin = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &admin).unwrap();
+
+    let creator = bank.new_wallet();
+    let (input_mint, input) = escrow_input(&mut bank, &creator, 100);
+    let (output_mint, output) = escrow_output(&mut bank, &creator, 1, 10);
+    let (second_output_mint, second_output) = escrow_output(&mut bank, &creator, 1, 10);
+    let mut args = transform_args(
+        BTreeMap::from([(input_mint, input)]),
+        BTreeMap::from([(output_mint, output), (second_output_mint, second_output)]),
+    );
+    args.forward.fee = Some(Fee {
+        bps: 100,
+        rounding: Rounding::Floor,
+    });
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 1_000);
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 3),
+        &accounts,
+    )
+    .unwrap();
+
+    // a fee out of the range or too many outputs stop forward executions, holders can
+    // still reverse out
+    let fee_range = TokenitisConfig {
+        min_fee_bps: 200,
+        ..TokenitisConfig::default()
+    };
+    let shape = TokenitisConfig {
+        max_outputs: 1,
+        ..TokenitisConfig::default()
+    };
+    for config in [fee_range, shape] {
+        update_tokenitis_config(&mut bank, &admin, config).unwrap();
+        assert_eq!(
+            execute_transform(
+                &mut bank,
+                transform_num,
+                &caller,
+                execute_args(Direction::Forward, 1),
+                &accounts,
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &caller,
+            execute_args(Direction::Reverse, 1),
+            &accounts,
+        )
+        .unwrap();
+    }
+    let config = TokenitisConfig::default();
+    update_tokenitis_config(&mut bank, &admin, config.clone()).unwrap();
+
+    // denied mints cannot be transformed into but can be reversed out of
+    let denied = TokenitisConfig {
+        denied_mints: vec![output_mint],
+        ..config.clone()
+    };
+    update_tokenitis_config(&mut bank, &admin, denied).unwrap();
+    assert_eq!(
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &caller,
+            execute_args(Direction::Forward, 1),
+            &accounts,
+        ),
+        Err(custom_error(TokenitisError::MintDenied))
+    );
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Reverse, 1),
+        &accounts,
+    )
+    .unwrap();
+
+    let paused = TokenitisConfig {
+        paused: true,
+        ..config
+    };
+    update_tokenitis_config(&mut bank, &admin, paused).unwrap();
+    for direction in [Direction::Forward, Direction::Reverse] {
+        assert_eq!(
+            execute_transform(
+                &mut bank,
+                transform_num,
+                &caller,
+                execute_args(direction, 1),
+                &accounts,
+            ),
+            Err(custom_error(TokenitisError::TokenitisPaused))
+        );
+    }
+}
@@ -0,0 +1,215 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use tokenitis::errors::TokenitisError;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{Fee, FeeRecipient, Rounding, TransformStatus};
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+use tokenitis::tokenitis_instruction::propose_creator::ProposeCreatorArgs;
+use tokenitis::tokenitis_instruction::set_transform_status::SetTransformStatusArgs;
+use tokenitis::util::token::associated_token_address;
+
+fn pause(bank: &mut Bank, creator: &Pubkey, transform_num: u64) -> ProgramResult {
+    let instructions = InstructionBuilder::set_transform_status(
+        tokenitis::id(),
+        creator,
+        transform_num,
+        SetTransformStatusArgs {
+            status: TransformStatus::Paused,
+        },
+    )
+    .unwrap();
+    bank.process(instructions, &[*creator])
+}
+
+#[test]
+fn handoff_moves_creator_rights_and_fees() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let (input_mint, input) = escrow_input(&mut bank, &creator, 100);
+    let (output_mint, output) = escrow_output(&mut bank, &creator, 1, 10);
+    let mut args = transform_args(
+        BTreeMap::from([(input_mint, input)]),
+        BTreeMap::from([(output_mint, output)]),
+    );
+    args.forward.fee = Some(Fee {
+        bps: 100,
+        rounding: Rounding::Floor,
+    });
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 1_000);
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 2),
+        &accounts,
+    )
+    .unwrap();
+
+    let new_creator = bank.new_wallet();
+    let instructions = InstructionBuilder::propose_creator(
+        tokenitis::id(),
+        &creator,
+        transform_num,
+        ProposeCreatorArgs {
+            new_creator: Some(new_creator),
+        },
+    )
+    .unwrap();
+    bank.process(instructions, &[creator]).unwrap();
+
+    // proposal has to be accepted by the proposed creator
+    let intruder = bank.new_wallet();
+    let instructions = InstructionBuilder::accept_creator(
+        tokenitis::id(),
+        &intruder,
+        transform_state(&bank, transform_num),
+    )
+    .unwrap();
+    assert!(bank.process(instructions, &[intruder]).is_err());
+    assert_eq!(transform_state(&bank, transform_num).creator, creator);
+
+    // fees collected before the handoff are withdrawn by the previous creator
+    let instructions = InstructionBuilder::accept_creator(
+        tokenitis::id(),
+        &new_creator,
+        transform_state(&bank, transform_num),
+    )
+    .unwrap();
+    assert_eq!(
+        bank.process(instructions, &[new_creator]),
+        Err(custom_error(TokenitisError::FeesNotWithdrawn))
+    );
+    withdraw_fees(&mut bank, &creator, transform_num, &input_mint);
+    let fee_account = associated_token_address(&creator, &input_mint, &spl_token::id());
+    assert_eq!(bank.token_balance(&fee_account), 2);
+
+    let instructions = InstructionBuilder::accept_creator(
+        tokenitis::id(),
+        &new_creator,
+        transform_state(&bank, transform_num),
+    )
+    .unwrap();
+    bank.process(instructions, &[new_creator]).unwrap();
+    let state = transform_state(&bank, transform_num);
+    assert_eq!(state.creator, new_creator);
+    assert_eq!(state.pending_creator, None);
+
+    assert_eq!(
+        pause(&mut bank, &creator, transform_num),
+        Err(ProgramError::InvalidArgument)
+    );
+    let instructions = InstructionBuilder::withdraw_fees(tokenitis::id(), &creator, state);
+    assert_eq!(
+        bank.process(instructions.unwrap(), &[creator]),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 2),
+        &accounts,
+    )
+    .unwrap();
+    withdraw_fees(&mut bank, &new_creator, transform_num, &input_mint);
+    let fee_account = associated_token_address(&new_creator, &input_mint, &spl_token::id());
+    assert_eq!(bank.token_balance(&fee_account), 2);
+
+    pause(&mut bank, &new_creator, transform_num).unwrap();
+}
+
+// fees of the input are paid to the associated token account of the creator
+fn withdraw_fees(bank: &mut Bank, creator: &Pubkey, transform_num: u64, input_mint: &Pubkey) {
+    let instructions = vec![InstructionBuilder::create_associated_token_account(
+        creator,
+        creator,
+        input_mint,
+        &spl_token::id(),
+    )];
+    bank.process(instructions, &[*creator]).unwrap();
+    let instructions = InstructionBuilder::withdraw_fees(
+        tokenitis::id(),
+        creator,
+        transform_state(bank, transform_num),
+    )
+    .unwrap();
+    bank.process(instructions, &[*creator]).unwrap();
+}
+
+fn hand_off(bank: &mut Bank, creator: &Pubkey, new_creator: &Pubkey, transform_num: u64) {
+    let instructions = InstructionBuilder::propose_creator(
+        tokenitis::id(),
+        creator,
+        transform_num,
+        ProposeCreatorArgs {
+            new_creator: Some(*new_creator),
+        },
+    )
+    .unwrap();
+    bank.process(instructions, &[*creator]).unwrap();
+    let instructions = InstructionBuilder::accept_creator(
+        tokenitis::id(),
+        new_creator,
+        transform_state(bank, transform_num),
+    )
+    .unwrap();
+    bank.process(instructions, &[*new_creator]).unwrap();
+}
+
+#[test]
+fn handoff_moves_fee_recipient_share() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let partner = bank.new_wallet();
+    let mut args = pair_args(&mut bank, &creator, 1);
+    args.fee_recipients = vec![
+        FeeRecipient {
+            owner: creator,
+            share_bps: 6_000,
+        },
+        FeeRecipient {
+            owner: partner,
+            share_bps: 4_000,
+        },
+    ];
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+
+    let new_creator = bank.new_wallet();
+    hand_off(&mut bank, &creator, &new_creator, transform_num);
+    assert_eq!(
+        transform_state(&bank, transform_num).fee_recipients,
+        vec![
+            FeeRecipient {
+                owner: new_creator,
+                share_bps: 6_000,
+            },
+            FeeRecipient {
+                owner: partner,
+                share_bps: 4_000,
+            },
+        ]
+    );
+
+    // handing off to an existing recipient merges the shares
+    hand_off(&mut bank, &new_creator, &partner, transform_num);
+    assert_eq!(
+        transform_state(&bank, transform_num).fee_recipients,
+        vec![FeeRecipient {
+            owner: partner,
+            share_bps: 10_000,
+        }]
+    );
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::*;
+use solana_program::program_error::ProgramError;
+use tokenitis::errors::TokenitisError;
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+#[test]
+fn disabled_direction_is_rejected() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let mut args = pair_args(&mut bank, &creator, 1);
+    args.reverse.enabled = false;
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 1),
+        &accounts,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &caller,
+            execute_args(Direction::Reverse, 1),
+            &accounts,
+        ),
+        Err(custom_error(TokenitisError::DirectionDisabled))
+    );
+}
+
+#[test]
+fn amounts_differ_per_direction() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    // 10 inputs per unit forward, 8 returned per unit on reverse
+    let mut args = pair_args(&mut bank, &creator, 10);
+    let (input_mint, input) = args.inputs.iter().next().unwrap();
+    let (input_mint, escrow) = (*input_mint, input.account);
+    args.reverse.amounts.insert(input_mint, 8);
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 100);
+
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 2),
+        &accounts,
+    )
+    .unwrap();
+    assert_eq!(bank.token_balance(&accounts.0[&input_mint]), 80);
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Reverse, 2),
+        &accounts,
+    )
+    .unwrap();
+    assert_eq!(bank.token_balance(&accounts.0[&input_mint]), 96);
+    assert_eq!(bank.token_balance(&escrow), 4);
+}
+
+#[test]
+fn reverse_cannot_return_more_than_forward_takes() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let mut args = pair_args(&mut bank, &creator, 10);
+    let input_mint = *args.inputs.keys().next().unwrap();
+    args.reverse.amounts.insert(input_mint, 11);
+    assert_eq!(
+        create_transform(&mut bank, &creator, args),
+        Err(ProgramError::InvalidArgument)
+    );
+}
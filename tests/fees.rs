@@ -0,0 +1,231 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{Fee, FeeRecipient, Rounding, Tokenitis};
+use tokenitis::tokenitis_instruction::create_transform::CreateTransformArgs;
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+struct FeeTest {
+    bank: Bank,
+    creator: Pubkey,
+    transform_num: u64,
+    input_mint: Pubkey,
+    caller: Pubkey,
+    accounts: (BTreeMap<Pubkey, Pubkey>, BTreeMap<Pubkey, Pubkey>),
+}
+
+impl FeeTest {
+    // 10 inputs per unit, the caller holds 1000
+    fn new(configure: impl FnOnce(&mut CreateTransformArgs)) -> Self {
+        let mut bank = Bank::new();
+        let creator = bank.new_wallet();
+        initialize_tokenitis(&mut bank, &creator).unwrap();
+
+        let mut args = pair_args(&mut bank, &creator, 10);
+        let input_mint = *args.inputs.keys().next().unwrap();
+        configure(&mut args);
+        let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+        let caller = bank.new_wallet();
+        let accounts = caller_accounts(&mut bank, transform_num, &caller, 1_000);
+
+        FeeTest {
+            bank,
+            creator,
+            transform_num,
+            input_mint,
+            caller,
+            accounts,
+        }
+    }
+
+    fn execute(&mut self, direction: Direction, quantity: u64) -> ProgramResult {
+        execute_transform(
+            &mut self.bank,
+            self.transform_num,
+            &self.caller,
+            execute_args(direction, quantity),
+            &self.accounts,
+        )
+    }
+
+    fn caller_balance(&self) -> u64 {
+        self.bank.token_balance(&self.accounts.0[&self.input_mint])
+    }
+
+    fn fee_vault_balance(&self) -> u64 {
+        let (fee_vault, _) = Tokenitis::find_fee_vault_address(
+            &tokenitis::id(),
+            self.transform_num,
+            &self.input_mint,
+        );
+        self.bank.token_balance(&fee_vault)
+    }
+
+    fn fees_collected(&self) -> u64 {
+        transform_state(&self.bank, self.transform_num).fees_collected[&self.input_mint]
+    }
+
+    // recipients are paid into their associated token accounts
+    fn withdraw(&mut self, creator: &Pubkey) -> ProgramResult {
+        let instructions = InstructionBuilder::withdraw_fees(
+            tokenitis::id(),
+            creator,
+            transform_state(&self.bank, self.transform_num),
+        )
+        .unwrap();
+        self.bank.process(instructions, &[*creator])
+    }
+
+    fn recipient_account(&mut self, owner: &Pubkey) -> Pubkey {
+        self.bank
+            .create_associated_token_account(&self.input_mint, owner, 0)
+    }
+}
+
+fn fee(bps: u16, rounding: Rounding) -> Option<Fee> {
+    Some(Fee { bps, rounding })
+}
+
+#[test]
+fn forward_fee_rounding() {
+    // 1.5% of 10 is 0.15 and of 100 is 1.5
+    let mut floor = FeeTest::new(|args| args.forward.fee = fee(150, Rounding::Floor));
+    floor.execute(Direction::Forward, 1).unwrap();
+    assert_eq!(floor.caller_balance(), 990);
+    floor.execute(Direction::Forward, 10).unwrap();
+    assert_eq!(floor.caller_balance(), 889);
+    assert_eq!(floor.fee_vault_balance(), 1);
+    assert_eq!(floor.fees_collected(), 1);
+
+    let mut ceil = FeeTest::new(|args| args.forward.fee = fee(150, Rounding::Ceil));
+    ceil.execute(Direction::Forward, 1).unwrap();
+    assert_eq!(ceil.caller_balance(), 989);
+    ceil.execute(Direction::Forward, 10).unwrap();
+    assert_eq!(ceil.caller_balance(), 887);
+    assert_eq!(ceil.fee_vault_balance(), 3);
+    assert_eq!(ceil.fees_collected(), 3);
+}
+
+#[test]
+fn reverse_fee_is_withheld() {
+    let mut test = FeeTest::new(|args| args.reverse.fee = fee(150, Rounding::Ceil));
+    test.execute(Direction::Forward, 10).unwrap();
+    assert_eq!(test.caller_balance(), 900);
+    assert_eq!(test.fee_vault_balance(), 0);
+
+    test.execute(Direction::Reverse, 10).unwrap();
+    assert_eq!(test.caller_balance(), 998);
+    assert_eq!(test.fee_vault_balance(), 2);
+    assert_eq!(test.fees_collected(), 2);
+}
+
+#[test]
+fn fee_has_to_be_within_config_range() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let mut args = pair_args(&mut bank, &creator, 10);
+    args.forward.fee = fee(1_001, Rounding::Floor);
+    assert_eq!(
+        create_transform(&mut bank, &creator, args),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn fees_are_split_between_recipients() {
+    let recipients = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let mut test = FeeTest::new(|args| {
+        args.forward.fee = fee(100, Rounding::Floor);
+        args.fee_recipients = recipients
+            .iter()
+            .zip([6_000, 3_000, 1_000])
+            .map(|(owner, share_bps)| FeeRecipient {
+                owner: *owner,
+                share_bps,
+            })
+            .collect();
+    });
+    test.execute(Direction::Forward, 70).unwrap();
+    assert_eq!(test.fee_vault_balance(), 7);
+
+    // shares round down and the remainder goes to the first recipient
+    let accounts: Vec<Pubkey> = recipients
+        .iter()
+        .map(|owner| test.recipient_account(owner))
+        .collect();
+    let creator = test.creator;
+    test.withdraw(&creator).unwrap();
+    let balances: Vec<u64> = accounts
+        .iter()
+        .map(|account| test.bank.token_balance(account))
+        .collect();
+    assert_eq!(balances, vec![5, 2, 0]);
+    assert_eq!(test.fee_vault_balance(), 0);
+}
+
+#[test]
+fn recipient_shares_have_to_add_up() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let partner = Pubkey::new_unique();
+    for shares in [[6_000, 3_000], [6_000, 5_000]] {
+        let mut args = pair_args(&mut bank, &creator, 10);
+        args.forward.fee = fee(100, Rounding::Floor);
+        args.fee_recipients = vec![
+            FeeRecipient {
+                owner: creator,
+                share_bps: shares[0],
+            },
+            FeeRecipient {
+                owner: partner,
+                share_bps: shares[1],
+            },
+        ];
+        assert_eq!(
+            create_transform(&mut bank, &creator, args),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
+
+#[test]
+fn creator_withdraws_fee_vault() {
+    let mut test = FeeTest::new(|args| args.forward.fee = fee(100, Rounding::Floor));
+    test.execute(Direction::Forward, 50).unwrap();
+    assert_eq!(test.fee_vault_balance(), 5);
+
+    let creator = test.creator;
+    let account = test.recipient_account(&creator);
+    let caller = test.caller;
+    assert_eq!(test.withdraw(&caller), Err(ProgramError::InvalidArgument));
+
+    // without fee recipients the creator receives everything
+    test.withdraw(&creator).unwrap();
+    assert_eq!(test.bank.token_balance(&account), 5);
+    assert_eq!(test.fee_vault_balance(), 0);
+    assert_eq!(test.fees_collected(), 5);
+
+    test.execute(Direction::Forward, 20).unwrap();
+    test.withdraw(&creator).unwrap();
+    assert_eq!(test.bank.token_balance(&account), 7);
+}
+
+#[test]
+fn withdraw_requires_a_fee() {
+    let mut test = FeeTest::new(|_| {});
+    let creator = test.creator;
+    assert_eq!(test.withdraw(&creator), Err(ProgramError::InvalidArgument));
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::*;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use tokenitis::errors::TokenitisError;
+use tokenitis::state::{Gate, TokenProgram};
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+use tokenitis::util::token::{associated_token_address, token_2022};
+
+fn gated_transform(bank: &mut Bank, gate_mint: &Pubkey, token_program: TokenProgram) -> u64 {
+    let creator = bank.new_wallet();
+    initialize_tokenitis(bank, &creator).unwrap();
+    let mut args = pair_args(bank, &creator, 1);
+    args.gate = Some(Gate {
+        mint: *gate_mint,
+        min_amount: 5,
+        token_program,
+    });
+    create_transform(bank, &creator, args).unwrap()
+}
+
+#[test]
+fn gate_requires_min_amount() {
+    let mut bank = Bank::new();
+    let gate_mint = bank.create_mint(None, 0);
+    let transform_num = gated_transform(&mut bank, &gate_mint, TokenProgram::Token);
+
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+    let gate_account = bank.create_associated_token_account(&gate_mint, &caller, 4);
+    assert_eq!(
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &caller,
+            execute_args(Direction::Forward, 1),
+            &accounts,
+        ),
+        Err(custom_error(TokenitisError::GateNotMet))
+    );
+
+    bank.create_token_account_at(&gate_account, &gate_mint, &caller, 5);
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 1),
+        &accounts,
+    )
+    .unwrap();
+}
+
+#[test]
+fn gate_rejects_foreign_account() {
+    let mut bank = Bank::new();
+    let gate_mint = bank.create_mint(None, 0);
+    let transform_num = gated_transform(&mut bank, &gate_mint, TokenProgram::Token);
+
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+    let gate_account = bank.create_associated_token_account(&gate_mint, &caller, 5);
+    // same data as a token account holding the gate mint, owned by another program
+    bank.set_owner(&gate_account, &Pubkey::new_unique());
+    assert_eq!(
+        gate_account,
+        associated_token_address(&caller, &gate_mint, &spl_token::id())
+    );
+    assert_eq!(
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &caller,
+            execute_args(Direction::Forward, 1),
+            &accounts,
+        ),
+        Err(ProgramError::IncorrectProgramId)
+    );
+}
+
+#[test]
+fn gate_account_of_token_2022_mint() {
+    let mut bank = Bank::new();
+    let gate_mint = bank.create_transfer_fee_mint(0, 0);
+    let transform_num = gated_transform(&mut bank, &gate_mint, TokenProgram::Token2022);
+
+    // the sdk derives the gate account with the program of the gate mint
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+    let gate_account = associated_token_address(&caller, &gate_mint, &token_2022::id());
+    bank.create_token_account_at(&gate_account, &gate_mint, &caller, 5);
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 1),
+        &accounts,
+    )
+    .unwrap();
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::*;
+use solana_program::program_error::ProgramError;
+use solana_sdk::account::Account;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{Tokenitis, TokenitisConfig};
+use tokenitis::tokenitis_instruction::initialize_tokenitis::InitializeTokenitisArgs;
+
+#[test]
+fn initialize_requires_upgrade_authority() {
+    let mut bank = Bank::new();
+    let deployer = bank.new_wallet();
+    bank.set_upgrade_authority(&deployer);
+
+    let front_runner = bank.new_wallet();
+    let instructions = InstructionBuilder::initialize_tokenitis(
+        tokenitis::id(),
+        &front_runner,
+        InitializeTokenitisArgs {
+            admin: front_runner,
+            config: TokenitisConfig::default(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        bank.process(instructions, &[front_runner]),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    let admin = bank.new_wallet();
+    let instructions = InstructionBuilder::initialize_tokenitis(
+        tokenitis::id(),
+        &deployer,
+        InitializeTokenitisArgs {
+            admin,
+            config: TokenitisConfig::default(),
+        },
+    )
+    .unwrap();
+    bank.process(instructions, &[deployer]).unwrap();
+    assert_eq!(tokenitis_state(&bank).admin, admin);
+}
+
+#[test]
+fn initialize_migrates_legacy_tokenitis() {
+    let mut bank = Bank::new();
+    // created by create transform before tokenitis had to be initialized
+    let (tokenitis, _) = Tokenitis::find_tokenitis_address(&tokenitis::id());
+    let data = 5u64.to_le_bytes().to_vec();
+    let lamports = bank.rent().minimum_balance(data.len());
+    bank.set_account(
+        &tokenitis,
+        Account {
+            lamports,
+            data,
+            owner: tokenitis::id(),
+            ..Account::default()
+        },
+    );
+
+    let admin = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &admin).unwrap();
+    let state = tokenitis_state(&bank);
+    assert_eq!(state.num_transforms, 5);
+    assert_eq!(state.admin, admin);
+    assert_eq!(state.config, TokenitisConfig::default());
+
+    let args = pair_args(&mut bank, &admin, 1);
+    assert_eq!(create_transform(&mut bank, &admin, args), Ok(6));
+    assert_eq!(
+        initialize_tokenitis(&mut bank, &admin),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::*;
+use tokenitis::errors::TokenitisError;
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+#[test]
+fn wallet_limit_applies_to_first_execution() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let mut args = pair_args(&mut bank, &creator, 1);
+    args.limits.max_executions_per_wallet = Some(2);
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+
+    assert_eq!(
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &caller,
+            execute_args(Direction::Forward, 3),
+            &accounts,
+        ),
+        Err(custom_error(TokenitisError::WalletLimitReached))
+    );
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 2),
+        &accounts,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &caller,
+            execute_args(Direction::Forward, 1),
+            &accounts,
+        ),
+        Err(custom_error(TokenitisError::WalletLimitReached))
+    );
+}
@@ -0,0 +1,28 @@
+use solana_program::pubkey::Pubkey;
+use tokenitis::sdk::merkle::AllowlistTree;
+use tokenitis::util::merkle::{allowlist_leaf, verify_proof};
+
+#[test]
+fn allowlist_proofs() {
+    // odd number of wallets so a node is carried up without a sibling
+    let entries: Vec<(Pubkey, u64)> = (0..5).map(|i| (Pubkey::new_unique(), i + 1)).collect();
+    let tree = AllowlistTree::new(entries.clone());
+    let root = tree.root().unwrap();
+
+    for (wallet, max_allocation) in entries.iter() {
+        let proof = tree.proof(wallet).unwrap();
+        assert_eq!(proof.max_allocation, *max_allocation);
+        assert!(verify_proof(
+            allowlist_leaf(wallet, proof.max_allocation),
+            &proof.proof,
+            &root
+        ));
+        assert!(!verify_proof(
+            allowlist_leaf(wallet, proof.max_allocation + 1),
+            &proof.proof,
+            &root
+        ));
+    }
+
+    assert!(tree.proof(&Pubkey::new_unique()).is_none());
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::*;
+use solana_program::program_error::ProgramError;
+use solana_program::program_option::COption;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use spl_token::state::Mint;
+use std::collections::BTreeMap;
+use tokenitis::state::Tokenitis;
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+fn mint_authority(bank: &Bank, mint: &Pubkey) -> COption<Pubkey> {
+    Mint::unpack(&bank.account(mint).unwrap().data[..Mint::LEN])
+        .unwrap()
+        .mint_authority
+}
+
+#[test]
+fn minted_outputs_follow_executions() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let (input_mint, input) = escrow_input(&mut bank, &creator, 1);
+    let (output_mint, output) = minted_output(&mut bank, &creator, 2);
+    let args = transform_args(
+        BTreeMap::from([(input_mint, input)]),
+        BTreeMap::from([(output_mint, output)]),
+    );
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+    let (transform, _) = Tokenitis::find_transform_address(&tokenitis::id(), transform_num);
+    assert_eq!(
+        mint_authority(&bank, &output_mint),
+        COption::Some(transform)
+    );
+
+    // outputs are minted on forward and burnt on reverse, there is no escrowed supply
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 3),
+        &accounts,
+    )
+    .unwrap();
+    assert_eq!(bank.token_balance(&accounts.1[&output_mint]), 6);
+    assert_eq!(bank.mint_supply(&output_mint), 6);
+
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Reverse, 1),
+        &accounts,
+    )
+    .unwrap();
+    assert_eq!(bank.token_balance(&accounts.0[&input_mint]), 8);
+    assert_eq!(bank.token_balance(&accounts.1[&output_mint]), 4);
+    assert_eq!(bank.mint_supply(&output_mint), 4);
+    assert_eq!(
+        transform_state(&bank, transform_num).outstanding_outputs[&output_mint],
+        4
+    );
+
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Reverse, 2),
+        &accounts,
+    )
+    .unwrap();
+    assert_eq!(bank.mint_supply(&output_mint), 0);
+
+    // the mint authority is handed back on close
+    let creator_accounts = caller_accounts(&mut bank, transform_num, &creator, 0);
+    close_transform(&mut bank, transform_num, &creator, &creator_accounts).unwrap();
+    assert_eq!(mint_authority(&bank, &output_mint), COption::Some(creator));
+}
+
+#[test]
+fn minted_output_requires_creator_authority() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let (input_mint, input) = escrow_input(&mut bank, &creator, 1);
+    let other = bank.new_wallet();
+    let (output_mint, output) = minted_output(&mut bank, &other, 2);
+    let args = transform_args(
+        BTreeMap::from([(input_mint, input)]),
+        BTreeMap::from([(output_mint, output)]),
+    );
+    assert_eq!(
+        create_transform(&mut bank, &creator, args),
+        Err(ProgramError::InvalidArgument)
+    );
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use common::*;
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{ClosePolicy, TokenMode};
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+use tokenitis::util::token::associated_token_address;
+
+#[test]
+fn native_output_outstanding_is_counted() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let native_mint = bank.create_native_mint();
+    let escrow = bank.create_token_account(&native_mint, &creator, 1_000_000);
+    let mut output = token(escrow, 1_000, TokenMode::Escrow);
+    output.decimals = spl_token::native_mint::DECIMALS;
+    let input = escrow_input(&mut bank, &creator, 1);
+    let mut args = transform_args(
+        BTreeMap::from([input]),
+        BTreeMap::from([(native_mint, output)]),
+    );
+    // wrapped sol has no supply, outstanding outputs are counted by the transform
+    args.close_policy = ClosePolicy::NoOutstanding;
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 2),
+        &accounts,
+    )
+    .unwrap();
+    assert_eq!(
+        transform_state(&bank, transform_num).outstanding_outputs[&native_mint],
+        2_000
+    );
+    let creator_accounts = caller_accounts(&mut bank, transform_num, &creator, 0);
+    assert_eq!(
+        close_transform(&mut bank, transform_num, &creator, &creator_accounts),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+struct WrapTest {
+    bank: Bank,
+    transform_num: u64,
+    native_mint: Pubkey,
+    caller: Pubkey,
+    caller_output: Pubkey,
+}
+
+impl WrapTest {
+    // 1000 lamports of wrapped sol for 1 output
+    fn new() -> Self {
+        let mut bank = Bank::new();
+        let creator = bank.new_wallet();
+        initialize_tokenitis(&mut bank, &creator).unwrap();
+
+        let native_mint = bank.create_native_mint();
+        let escrow = bank.create_token_account(&native_mint, &creator, 0);
+        let mut input = token(escrow, 1_000, TokenMode::Escrow);
+        input.decimals = spl_token::native_mint::DECIMALS;
+        let output = escrow_output(&mut bank, &creator, 1, 10);
+        let output_mint = output.0;
+        let args = transform_args(
+            BTreeMap::from([(native_mint, input)]),
+            BTreeMap::from([output]),
+        );
+        let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+
+        let caller = bank.new_wallet();
+        let caller_output = bank.create_token_account(&output_mint, &caller, 0);
+        WrapTest {
+            bank,
+            transform_num,
+            native_mint,
+            caller,
+            caller_output,
+        }
+    }
+
+    fn execute(
+        &mut self,
+        direction: Direction,
+        caller_inputs: BTreeMap<Pubkey, Pubkey>,
+        lamports: u64,
+    ) -> Vec<Instruction> {
+        let output_mint = *transform_state(&self.bank, self.transform_num)
+            .outputs
+            .keys()
+            .next()
+            .unwrap();
+        let instructions = InstructionBuilder::execute_transform(
+            tokenitis::id(),
+            &self.caller,
+            transform_state(&self.bank, self.transform_num),
+            execute_args(direction, 2),
+            caller_inputs,
+            BTreeMap::from([(output_mint, self.caller_output)]),
+            BTreeMap::from([(self.native_mint, lamports)]),
+        )
+        .unwrap();
+        self.bank
+            .process(instructions.clone(), &[self.caller])
+            .unwrap();
+        instructions
+    }
+}
+
+#[test]
+fn wrap_and_unwrap_sol() {
+    let mut test = WrapTest::new();
+    let ata = associated_token_address(&test.caller, &test.native_mint, &spl_token::id());
+    let lamports = test.bank.lamports(&test.caller);
+
+    // associated account is created to wrap the sol and closed after executing
+    test.execute(Direction::Forward, BTreeMap::new(), 2_000);
+    assert_eq!(test.bank.lamports(&test.caller), lamports - 2_000);
+    assert_eq!(test.bank.token_balance(&test.caller_output), 2);
+    assert!(test.bank.account(&ata).is_none());
+
+    // received sol is unwrapped back to the caller
+    test.execute(Direction::Reverse, BTreeMap::new(), 0);
+    assert_eq!(test.bank.lamports(&test.caller), lamports);
+    assert!(test.bank.account(&ata).is_none());
+}
+
+#[test]
+fn provided_native_account_is_reused() {
+    let mut test = WrapTest::new();
+    let (native_mint, caller) = (test.native_mint, test.caller);
+    let caller_input = test
+        .bank
+        .create_associated_token_account(&native_mint, &caller, 500);
+
+    let instructions = test.execute(
+        Direction::Forward,
+        BTreeMap::from([(native_mint, caller_input)]),
+        2_000,
+    );
+    assert!(instructions
+        .iter()
+        .all(|instruction| instruction.program_id != spl_associated_token_account::id()));
+    // account is not closed, its sol stays wrapped
+    assert_eq!(test.bank.token_balance(&caller_input), 500);
+    assert_eq!(test.bank.token_balance(&test.caller_output), 2);
+}
@@ -0,0 +1,54 @@
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use tokenitis::util::pack::{fallback_pick, find_slot_hash, pick};
+
+#[test]
+fn pack_picks() {
+    let common = Pubkey::new_unique();
+    let rare = Pubkey::new_unique();
+    let weights: BTreeMap<Pubkey, u64> = vec![(common, 99), (rare, 1)].into_iter().collect();
+    let seed = [7u8; 32];
+    let commitment = Pubkey::new_unique();
+
+    let picks: Vec<Pubkey> = (0..1000)
+        .map(|i| pick(&weights, &seed, &commitment, i).unwrap())
+        .collect();
+    let rare_picks = picks.iter().filter(|mint| **mint == rare).count();
+    assert!(rare_picks > 0 && rare_picks < 50);
+
+    // picks only depend on the seed, commitment and index
+    assert_eq!(pick(&weights, &seed, &commitment, 3), Some(picks[3]));
+    assert!(pick(&BTreeMap::new(), &seed, &commitment, 0).is_none());
+}
+
+#[test]
+fn fallback_is_most_common_output() {
+    let (first, second, third) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let weights: BTreeMap<Pubkey, u64> = vec![(first, 5), (second, 9), (third, 9)]
+        .into_iter()
+        .collect();
+    // ties go to the lowest mint
+    let expected = std::cmp::min(second, third);
+    assert_eq!(fallback_pick(&weights), Some(expected));
+
+    let unpicked: BTreeMap<Pubkey, u64> = vec![(first, 0)].into_iter().collect();
+    assert_eq!(fallback_pick(&unpicked), None);
+}
+
+#[test]
+fn slot_hash_lookup() {
+    let mut data = 2u64.to_le_bytes().to_vec();
+    for (slot, hash) in [(11u64, [2u8; 32]), (10u64, [1u8; 32])] {
+        data.extend_from_slice(&slot.to_le_bytes());
+        data.extend_from_slice(&hash);
+    }
+
+    assert_eq!(find_slot_hash(&data, 11), Some([2u8; 32]));
+    assert_eq!(find_slot_hash(&data, 10), Some([1u8; 32]));
+    assert_eq!(find_slot_hash(&data, 9), None);
+    assert_eq!(find_slot_hash(&[], 10), None);
+}
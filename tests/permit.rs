@@ -0,0 +1,135 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use tokenitis::errors::TokenitisError;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{PermitSigner, Tokenitis};
+use tokenitis::tokenitis_instruction::execute_transform::{Direction, PermitArgs};
+
+struct PermitTest {
+    bank: Bank,
+    transform_num: u64,
+    signer: Pubkey,
+    caller: Pubkey,
+    accounts: (BTreeMap<Pubkey, Pubkey>, BTreeMap<Pubkey, Pubkey>),
+}
+
+impl PermitTest {
+    fn new() -> Self {
+        let mut bank = Bank::new();
+        let creator = bank.new_wallet();
+        initialize_tokenitis(&mut bank, &creator).unwrap();
+
+        let signer = bank.new_wallet();
+        let mut args = pair_args(&mut bank, &creator, 1);
+        args.permit_signer = Some(PermitSigner::Key(signer));
+        let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+        let caller = bank.new_wallet();
+        let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+
+        PermitTest {
+            bank,
+            transform_num,
+            signer,
+            caller,
+            accounts,
+        }
+    }
+
+    // signatures are verified by the runtime, the program checks the signer and message
+    fn execute(&mut self, signer: &Pubkey, expiry: i64, nonce: u64) -> ProgramResult {
+        let mut args = execute_args(Direction::Forward, 1);
+        args.permit = Some(PermitArgs { expiry, nonce });
+        let message =
+            InstructionBuilder::permit_message(self.transform_num, &self.caller, &args).unwrap();
+        let signature = self.bank.sign(signer, &message);
+        let mut instructions = vec![InstructionBuilder::permit_instruction(
+            signer, &signature, &message,
+        )];
+        instructions.extend(
+            InstructionBuilder::execute_transform(
+                tokenitis::id(),
+                &self.caller,
+                transform_state(&self.bank, self.transform_num),
+                args,
+                self.accounts.0.clone(),
+                self.accounts.1.clone(),
+                BTreeMap::new(),
+            )
+            .unwrap(),
+        );
+        self.bank.process(instructions, &[self.caller])
+    }
+
+    fn close(&mut self, nonce: u64) -> ProgramResult {
+        let instructions = InstructionBuilder::close_permit(
+            tokenitis::id(),
+            &self.caller,
+            self.transform_num,
+            nonce,
+        )
+        .unwrap();
+        self.bank.process(instructions, &[])
+    }
+}
+
+#[test]
+fn permit_is_single_use() {
+    let mut test = PermitTest::new();
+    let (signer, expiry) = (test.signer, START_UNIX_TIMESTAMP + 100);
+    test.execute(&signer, expiry, 1).unwrap();
+    assert_eq!(
+        test.execute(&signer, expiry, 1),
+        Err(custom_error(TokenitisError::PermitUsed))
+    );
+    test.execute(&signer, expiry, 2).unwrap();
+}
+
+#[test]
+fn permit_rejects_wrong_signer() {
+    let mut test = PermitTest::new();
+    let other_signer = test.bank.new_wallet();
+    assert_eq!(
+        test.execute(&other_signer, START_UNIX_TIMESTAMP + 100, 1),
+        Err(custom_error(TokenitisError::InvalidPermit))
+    );
+}
+
+#[test]
+fn permit_rejects_expired() {
+    let mut test = PermitTest::new();
+    let (signer, expiry) = (test.signer, START_UNIX_TIMESTAMP + 100);
+    test.bank.warp_to_timestamp(expiry + 1);
+    assert_eq!(
+        test.execute(&signer, expiry, 1),
+        Err(custom_error(TokenitisError::PermitExpired))
+    );
+}
+
+#[test]
+fn close_permit_after_expiry() {
+    let mut test = PermitTest::new();
+    let (signer, expiry) = (test.signer, START_UNIX_TIMESTAMP + 100);
+    test.execute(&signer, expiry, 1).unwrap();
+    let (permit_nonce, _) = Tokenitis::find_permit_address(&tokenitis::id(), test.transform_num, 1);
+    let rent = test.bank.lamports(&permit_nonce);
+    assert!(rent > 0);
+
+    test.bank.warp_to_timestamp(expiry);
+    assert_eq!(test.close(1), Err(ProgramError::InvalidArgument));
+
+    test.bank.warp_to_timestamp(expiry + 1);
+    let balance = test.bank.lamports(&test.caller);
+    test.close(1).unwrap();
+    assert_eq!(test.bank.lamports(&permit_nonce), 0);
+    assert_eq!(test.bank.lamports(&test.caller), balance + rent);
+    // nonce cannot be reused since the permit has expired
+    assert_eq!(
+        test.execute(&signer, expiry, 1),
+        Err(custom_error(TokenitisError::PermitExpired))
+    );
+}
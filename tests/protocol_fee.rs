@@ -0,0 +1,142 @@
+mod common;
+
+use common::*;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use tokenitis::errors::MAX_PROTOCOL_FEE_BPS;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{Fee, Rounding, Tokenitis};
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+use tokenitis::tokenitis_instruction::set_protocol_fee::SetProtocolFeeArgs;
+
+fn set_protocol_fee(
+    bank: &mut Bank,
+    admin: &Pubkey,
+    protocol_fee_bps: u16,
+) -> Result<(), ProgramError> {
+    let instructions = InstructionBuilder::set_protocol_fee(
+        tokenitis::id(),
+        admin,
+        SetProtocolFeeArgs { protocol_fee_bps },
+    )
+    .unwrap();
+    bank.process(instructions, &[*admin])
+}
+
+fn withdraw_protocol_fees(
+    bank: &mut Bank,
+    admin: &Pubkey,
+    destinations: BTreeMap<Pubkey, Pubkey>,
+) -> Result<(), ProgramError> {
+    let instructions =
+        InstructionBuilder::withdraw_protocol_fees(tokenitis::id(), admin, destinations).unwrap();
+    bank.process(instructions, &[*admin])
+}
+
+#[test]
+fn only_admin_sets_bounded_protocol_fee() {
+    let mut bank = Bank::new();
+    let admin = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &admin).unwrap();
+
+    let other = bank.new_wallet();
+    assert_eq!(
+        set_protocol_fee(&mut bank, &other, 100),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        set_protocol_fee(&mut bank, &admin, MAX_PROTOCOL_FEE_BPS + 1),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(tokenitis_state(&bank).protocol_fee_bps, 0);
+
+    set_protocol_fee(&mut bank, &admin, MAX_PROTOCOL_FEE_BPS).unwrap();
+    assert_eq!(
+        tokenitis_state(&bank).protocol_fee_bps,
+        MAX_PROTOCOL_FEE_BPS
+    );
+}
+
+#[test]
+fn admin_withdraws_treasury() {
+    let mut bank = Bank::new();
+    let admin = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &admin).unwrap();
+    set_protocol_fee(&mut bank, &admin, 100).unwrap();
+
+    let creator = bank.new_wallet();
+    let args = pair_args(&mut bank, &creator, 100);
+    let input_mint = *args.inputs.keys().next().unwrap();
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 1_000);
+
+    // forward fees are paid on top of the inputs
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 3),
+        &accounts,
+    )
+    .unwrap();
+    let (treasury, _) = Tokenitis::find_treasury_address(&tokenitis::id(), &input_mint);
+    assert_eq!(bank.token_balance(&treasury), 3);
+    assert_eq!(
+        bank.token_balance(&accounts.0[&input_mint]),
+        1_000 - 300 - 3
+    );
+
+    let destination = bank.create_token_account(&input_mint, &admin, 0);
+    let destinations = BTreeMap::from([(input_mint, destination)]);
+    let other = bank.new_wallet();
+    assert_eq!(
+        withdraw_protocol_fees(&mut bank, &other, destinations.clone()),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    withdraw_protocol_fees(&mut bank, &admin, destinations).unwrap();
+    assert_eq!(bank.token_balance(&treasury), 0);
+    assert_eq!(bank.token_balance(&destination), 3);
+}
+
+#[test]
+fn fees_never_take_a_whole_returned_unit() {
+    let mut bank = Bank::new();
+    let admin = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &admin).unwrap();
+    set_protocol_fee(&mut bank, &admin, MAX_PROTOCOL_FEE_BPS).unwrap();
+
+    let creator = bank.new_wallet();
+    let mut args = pair_args(&mut bank, &creator, 1);
+    args.reverse.fee = Some(Fee {
+        bps: 100,
+        rounding: Rounding::Floor,
+    });
+    let input_mint = *args.inputs.keys().next().unwrap();
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 1);
+    let (treasury, _) = Tokenitis::find_treasury_address(&tokenitis::id(), &input_mint);
+
+    // a protocol fee on a single unit rounds down to nothing
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Forward, 1),
+        &accounts,
+    )
+    .unwrap();
+    execute_transform(
+        &mut bank,
+        transform_num,
+        &caller,
+        execute_args(Direction::Reverse, 1),
+        &accounts,
+    )
+    .unwrap();
+    assert_eq!(bank.token_balance(&accounts.0[&input_mint]), 1);
+    assert_eq!(bank.token_balance(&treasury), 0);
+}
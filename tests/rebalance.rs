@@ -0,0 +1,165 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use tokenitis::errors::TokenitisError;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::IndexConfig;
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+use tokenitis::tokenitis_instruction::rebalance::RebalanceArgs;
+
+const UNITS: u64 = 5;
+
+struct RebalanceTest {
+    bank: Bank,
+    transform_num: u64,
+    rebalancer: Pubkey,
+    price_authority: Pubkey,
+    mints: Vec<Pubkey>,
+}
+
+impl RebalanceTest {
+    // index of two inputs with 10 of each per unit, 5 units are outstanding
+    fn new() -> Self {
+        let mut bank = Bank::new();
+        let creator = bank.new_wallet();
+        let rebalancer = bank.new_wallet();
+        let price_authority = bank.new_wallet();
+        initialize_tokenitis(&mut bank, &creator).unwrap();
+
+        let first = escrow_input(&mut bank, &creator, 10);
+        let second = escrow_input(&mut bank, &creator, 10);
+        let mints = vec![first.0, second.0];
+        let output = minted_output(&mut bank, &creator, 1);
+        let mut args = transform_args(BTreeMap::from([first, second]), BTreeMap::from([output]));
+        args.index = Some(IndexConfig {
+            rebalancer: Some(rebalancer),
+            price_authority,
+            max_change_bps: 5_000,
+            nav_tolerance_bps: 100,
+        });
+        let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+
+        let caller = bank.new_wallet();
+        let accounts = caller_accounts(&mut bank, transform_num, &caller, 10 * UNITS);
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &caller,
+            execute_args(Direction::Forward, UNITS),
+            &accounts,
+        )
+        .unwrap();
+
+        RebalanceTest {
+            bank,
+            transform_num,
+            rebalancer,
+            price_authority,
+            mints,
+        }
+    }
+
+    fn counterparty(&mut self, balance: u64) -> (Pubkey, BTreeMap<Pubkey, Pubkey>) {
+        let counterparty = self.bank.new_wallet();
+        let mut accounts = BTreeMap::new();
+        for mint in self.mints.clone() {
+            let account = self
+                .bank
+                .create_token_account(&mint, &counterparty, balance);
+            accounts.insert(mint, account);
+        }
+        (counterparty, accounts)
+    }
+
+    fn rebalance(
+        &mut self,
+        counterparty: &(Pubkey, BTreeMap<Pubkey, Pubkey>),
+        amounts: [u64; 2],
+        prices: [u64; 2],
+        price_authority_signs: bool,
+    ) -> ProgramResult {
+        let mut instructions = InstructionBuilder::rebalance(
+            tokenitis::id(),
+            &self.rebalancer,
+            &counterparty.0,
+            transform_state(&self.bank, self.transform_num),
+            counterparty.1.clone(),
+            RebalanceArgs {
+                amounts: self.mints.iter().copied().zip(amounts).collect(),
+                prices: self.mints.iter().copied().zip(prices).collect(),
+            },
+        )
+        .unwrap();
+        let mut signers = vec![self.rebalancer, counterparty.0];
+        if price_authority_signs {
+            signers.push(self.price_authority);
+        } else {
+            for meta in instructions[0].accounts.iter_mut() {
+                if meta.pubkey == self.price_authority {
+                    meta.is_signer = false;
+                }
+            }
+        }
+        self.bank.process(instructions, &signers)
+    }
+
+    fn amounts(&self) -> Vec<u64> {
+        transform_state(&self.bank, self.transform_num)
+            .inputs
+            .values()
+            .map(|token| token.amount)
+            .collect()
+    }
+}
+
+#[test]
+fn rebalance_swaps_with_counterparty() {
+    let mut test = RebalanceTest::new();
+    let counterparty = test.counterparty(100);
+    test.rebalance(&counterparty, [12, 8], [1, 1], true)
+        .unwrap();
+
+    assert_eq!(test.amounts(), vec![12, 8]);
+    let balances: Vec<u64> = counterparty
+        .1
+        .values()
+        .map(|account| test.bank.token_balance(account))
+        .collect();
+    assert_eq!(balances, vec![100 - 2 * UNITS, 100 + 2 * UNITS]);
+}
+
+#[test]
+fn manipulated_rebalance_rejected() {
+    let mut test = RebalanceTest::new();
+    let counterparty = test.counterparty(100);
+
+    // at the real prices of 1 each the nav drops from 20 to 16, the inflated
+    // price of the first input would hide the drop
+    assert_eq!(
+        test.rebalance(&counterparty, [11, 5], [10, 1], false),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert_eq!(
+        test.rebalance(&counterparty, [11, 5], [1, 1], true),
+        Err(custom_error(TokenitisError::NavDropExceeded))
+    );
+
+    // trading against itself the rebalancer would pocket the difference
+    let rebalancer = test.rebalancer;
+    let mut own_accounts = BTreeMap::new();
+    for mint in test.mints.clone() {
+        own_accounts.insert(
+            mint,
+            test.bank.create_token_account(&mint, &rebalancer, 100),
+        );
+    }
+    assert_eq!(
+        test.rebalance(&(rebalancer, own_accounts), [11, 5], [10, 1], true),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(test.amounts(), vec![10, 10]);
+}
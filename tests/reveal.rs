@@ -0,0 +1,232 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::slot_hashes::MAX_ENTRIES;
+use std::collections::BTreeMap;
+use tokenitis::errors::{TokenitisError, MAX_PACK_PICKS};
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{ClosePolicy, PackConfig};
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+struct PackTest {
+    bank: Bank,
+    creator: Pubkey,
+    transform_num: u64,
+    common_mint: Pubkey,
+    caller: Pubkey,
+    accounts: (BTreeMap<Pubkey, Pubkey>, BTreeMap<Pubkey, Pubkey>),
+}
+
+impl PackTest {
+    fn new() -> Self {
+        let mut bank = Bank::new();
+        let creator = bank.new_wallet();
+        initialize_tokenitis(&mut bank, &creator).unwrap();
+
+        let (input_mint, input) = escrow_input(&mut bank, &creator, 1);
+        let (common_mint, common) = escrow_output(&mut bank, &creator, 1, 10);
+        let (rare_mint, rare) = escrow_output(&mut bank, &creator, 1, 10);
+        let mut args = transform_args(
+            BTreeMap::from([(input_mint, input)]),
+            BTreeMap::from([(common_mint, common), (rare_mint, rare)]),
+        );
+        args.reverse.enabled = false;
+        args.close_policy = ClosePolicy::Anytime;
+        args.pack = Some(PackConfig {
+            weights: BTreeMap::from([(common_mint, 9), (rare_mint, 1)]),
+            picks: 2,
+        });
+        let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+        let caller = bank.new_wallet();
+        let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+
+        PackTest {
+            bank,
+            creator,
+            transform_num,
+            common_mint,
+            caller,
+            accounts,
+        }
+    }
+
+    fn execute(&mut self) -> ProgramResult {
+        self.execute_quantity(1)
+    }
+
+    fn execute_quantity(&mut self, quantity: u64) -> ProgramResult {
+        execute_transform(
+            &mut self.bank,
+            self.transform_num,
+            &self.caller,
+            execute_args(Direction::Forward, quantity),
+            &self.accounts,
+        )
+    }
+
+    fn cancel(&mut self, creator: &Pubkey) -> ProgramResult {
+        let instructions = InstructionBuilder::cancel_pack(
+            tokenitis::id(),
+            creator,
+            &self.caller,
+            transform_state(&self.bank, self.transform_num),
+            self.accounts.0.clone(),
+        )
+        .unwrap();
+        self.bank.process(instructions, &[*creator])
+    }
+
+    fn reserved(&self) -> Vec<u64> {
+        transform_state(&self.bank, self.transform_num)
+            .reserved_outputs
+            .values()
+            .copied()
+            .collect()
+    }
+
+    fn reveal(&mut self, revealer: &Pubkey) -> ProgramResult {
+        let instructions = InstructionBuilder::reveal_pack(
+            tokenitis::id(),
+            revealer,
+            &self.caller,
+            transform_state(&self.bank, self.transform_num),
+            self.accounts.1.clone(),
+        )
+        .unwrap();
+        self.bank.process(instructions, &[*revealer])
+    }
+
+    fn close(&mut self) -> ProgramResult {
+        let creator = self.creator;
+        let creator_accounts = caller_accounts(&mut self.bank, self.transform_num, &creator, 0);
+        close_transform(
+            &mut self.bank,
+            self.transform_num,
+            &creator,
+            &creator_accounts,
+        )
+    }
+}
+
+#[test]
+fn close_waits_for_pending_packs() {
+    let mut test = PackTest::new();
+    test.execute().unwrap();
+    assert_eq!(
+        transform_state(&test.bank, test.transform_num).pending_packs,
+        1
+    );
+    assert_eq!(test.close(), Err(custom_error(TokenitisError::PackPending)));
+
+    let slot = test.bank.clock().slot;
+    test.bank.warp_to_slot(slot + 1);
+    let caller = test.caller;
+    test.reveal(&caller).unwrap();
+    assert_eq!(
+        transform_state(&test.bank, test.transform_num).pending_packs,
+        0
+    );
+    let revealed: u64 = test
+        .accounts
+        .1
+        .values()
+        .map(|account| test.bank.token_balance(account))
+        .sum();
+    assert_eq!(revealed, 2);
+
+    test.close().unwrap();
+}
+
+#[test]
+fn expired_pack_pays_out_fallback() {
+    let mut test = PackTest::new();
+    test.execute().unwrap();
+    let input_account = *test.accounts.0.values().next().unwrap();
+    assert_eq!(test.bank.token_balance(&input_account), 9);
+
+    // hash of the commit slot is no longer available, the inputs stay with the transform
+    // and every pick is the most common output
+    let slot = test.bank.clock().slot;
+    test.bank.warp_to_slot(slot + MAX_ENTRIES as u64 + 1);
+    let revealer = test.bank.new_wallet();
+    test.reveal(&revealer).unwrap();
+
+    assert_eq!(test.bank.token_balance(&input_account), 9);
+    let state = transform_state(&test.bank, test.transform_num);
+    let input_escrow = state.inputs.values().next().unwrap().account;
+    assert_eq!(test.bank.token_balance(&input_escrow), 1);
+    for (mint, account) in test.accounts.1.iter() {
+        let expected = if *mint == test.common_mint { 2 } else { 0 };
+        assert_eq!(test.bank.token_balance(account), expected);
+    }
+    assert_eq!(state.pending_packs, 0);
+}
+
+#[test]
+fn picks_are_capped() {
+    let mut test = PackTest::new();
+    // 2 picks per unit
+    assert_eq!(
+        test.execute_quantity(MAX_PACK_PICKS / 2 + 1),
+        Err(ProgramError::InvalidArgument)
+    );
+    // within the cap the escrows of 10 still cannot cover every pick
+    assert_eq!(
+        test.execute_quantity(MAX_PACK_PICKS / 2),
+        Err(custom_error(TokenitisError::PackOutputsUnavailable))
+    );
+}
+
+#[test]
+fn commit_reserves_worst_case_outputs() {
+    let mut test = PackTest::new();
+    // every one of the 10 picks could be either output
+    test.execute_quantity(5).unwrap();
+    assert_eq!(test.reserved(), vec![10, 10]);
+
+    let other = test.bank.new_wallet();
+    let other_accounts = caller_accounts(&mut test.bank, test.transform_num, &other, 10);
+    assert_eq!(
+        execute_transform(
+            &mut test.bank,
+            test.transform_num,
+            &other,
+            execute_args(Direction::Forward, 1),
+            &other_accounts,
+        ),
+        Err(custom_error(TokenitisError::PackOutputsUnavailable))
+    );
+
+    let slot = test.bank.clock().slot;
+    test.bank.warp_to_slot(slot + 1);
+    let caller = test.caller;
+    test.reveal(&caller).unwrap();
+    assert_eq!(test.reserved(), vec![0, 0]);
+}
+
+#[test]
+fn creator_cancels_expired_pack() {
+    let mut test = PackTest::new();
+    test.execute().unwrap();
+    let creator = test.creator;
+    let caller = test.caller;
+    assert_eq!(
+        test.cancel(&creator),
+        Err(custom_error(TokenitisError::PackNotExpired))
+    );
+
+    let slot = test.bank.clock().slot;
+    test.bank.warp_to_slot(slot + MAX_ENTRIES as u64 + 1);
+    assert_eq!(test.cancel(&caller), Err(ProgramError::InvalidArgument));
+    test.cancel(&creator).unwrap();
+
+    let input_account = *test.accounts.0.values().next().unwrap();
+    assert_eq!(test.bank.token_balance(&input_account), 10);
+    let state = transform_state(&test.bank, test.transform_num);
+    assert_eq!(state.pending_packs, 0);
+    assert_eq!(test.reserved(), vec![0, 0]);
+    test.close().unwrap();
+}
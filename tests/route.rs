@@ -0,0 +1,167 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::AccountMeta;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar;
+use std::collections::BTreeMap;
+use tokenitis::errors::TokenitisError;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{PermitSigner, TokenMode, Tokenitis};
+use tokenitis::tokenitis_instruction::create_transform::CreateTransformArgs;
+use tokenitis::tokenitis_instruction::execute_route::RouteHop;
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+struct RouteTest {
+    bank: Bank,
+    caller: Pubkey,
+    // first, intermediate and last mint of the route
+    mints: [Pubkey; 3],
+    caller_accounts: BTreeMap<Pubkey, Pubkey>,
+}
+
+impl RouteTest {
+    // 1 first mints 2 intermediate, 2 intermediate mint 3 last
+    fn new(configure: impl Fn(&mut CreateTransformArgs)) -> (Self, [u64; 2]) {
+        let mut bank = Bank::new();
+        let creator = bank.new_wallet();
+        initialize_tokenitis(&mut bank, &creator).unwrap();
+
+        let first = escrow_input(&mut bank, &creator, 1);
+        let intermediate = minted_output(&mut bank, &creator, 2);
+        let (first_mint, intermediate_mint) = (first.0, intermediate.0);
+        let first_num = create_transform(
+            &mut bank,
+            &creator,
+            transform_args(BTreeMap::from([first]), BTreeMap::from([intermediate])),
+        )
+        .unwrap();
+
+        let last = minted_output(&mut bank, &creator, 3);
+        let escrow = bank.create_token_account(&intermediate_mint, &creator, 0);
+        let mints = [first_mint, intermediate_mint, last.0];
+        let mut args = transform_args(
+            BTreeMap::from([(intermediate_mint, token(escrow, 2, TokenMode::Escrow))]),
+            BTreeMap::from([last]),
+        );
+        configure(&mut args);
+        let last_num = create_transform(&mut bank, &creator, args).unwrap();
+
+        let caller = bank.new_wallet();
+        let caller_accounts = mints
+            .iter()
+            .map(|mint| {
+                let balance = if *mint == first_mint { 10 } else { 0 };
+                (*mint, bank.create_token_account(mint, &caller, balance))
+            })
+            .collect();
+
+        (
+            RouteTest {
+                bank,
+                caller,
+                mints,
+                caller_accounts,
+            },
+            [first_num, last_num],
+        )
+    }
+
+    fn route(
+        &mut self,
+        transform_nums: [u64; 2],
+        quantities: [u64; 2],
+        min_output: u64,
+    ) -> ProgramResult {
+        let hops = transform_nums
+            .iter()
+            .zip(quantities.iter())
+            .map(|(transform_num, quantity)| {
+                // the sdk does not build permit executions without a permit,
+                // the permit accounts are added below
+                let mut transform_state = transform_state(&self.bank, *transform_num);
+                transform_state.permit_signer = None;
+                (
+                    transform_state,
+                    RouteHop {
+                        transform_id: *transform_num,
+                        direction: Direction::Forward,
+                        quantity: *quantity,
+                    },
+                )
+            })
+            .collect();
+        let mut instructions = InstructionBuilder::execute_route(
+            tokenitis::id(),
+            &self.caller,
+            hops,
+            self.caller_accounts.clone(),
+            &self.caller_accounts[&self.mints[2]],
+            min_output,
+        )
+        .unwrap();
+        // only the last hop can have a permit signer, its accounts end right before the output
+        if transform_state(&self.bank, transform_nums[1])
+            .permit_signer
+            .is_some()
+        {
+            let (permit_nonce, _nonce) =
+                Tokenitis::find_permit_address(&tokenitis::id(), transform_nums[1], 0);
+            let accounts = &mut instructions[0].accounts;
+            let output = accounts.len() - 1;
+            accounts.splice(
+                output..output,
+                vec![
+                    AccountMeta::new_readonly(sysvar::instructions::id(), false),
+                    AccountMeta::new(permit_nonce, false),
+                ],
+            );
+        }
+        self.bank.process(instructions, &[self.caller])
+    }
+
+    fn balances(&self) -> Vec<u64> {
+        self.mints
+            .iter()
+            .map(|mint| self.bank.token_balance(&self.caller_accounts[mint]))
+            .collect()
+    }
+}
+
+#[test]
+fn multi_hop_route() {
+    let (mut test, transform_nums) = RouteTest::new(|_| {});
+    test.route(transform_nums, [4, 4], 12).unwrap();
+    // 4 first mint 8 intermediate, 8 intermediate mint 12 last
+    assert_eq!(test.balances(), vec![6, 0, 12]);
+}
+
+#[test]
+fn route_output_too_low() {
+    let (mut test, transform_nums) = RouteTest::new(|_| {});
+    assert_eq!(
+        test.route(transform_nums, [4, 4], 13),
+        Err(custom_error(TokenitisError::RouteOutputTooLow))
+    );
+    assert_eq!(test.balances(), vec![10, 0, 0]);
+}
+
+#[test]
+fn gated_transforms_cannot_be_routed() {
+    let (mut test, transform_nums) = RouteTest::new(|args| args.allowlist = Some([1; 32]));
+    assert_eq!(
+        test.route(transform_nums, [4, 4], 0),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    let signer = Pubkey::new_unique();
+    let (mut test, transform_nums) =
+        RouteTest::new(|args| args.permit_signer = Some(PermitSigner::Key(signer)));
+    assert_eq!(
+        test.route(transform_nums, [4, 4], 0),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(test.balances(), vec![10, 0, 0]);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::*;
+use solana_program::pubkey::Pubkey;
+use tokenitis::errors::TokenitisError;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::TransformStatus;
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+use tokenitis::tokenitis_instruction::set_transform_status::SetTransformStatusArgs;
+
+fn set_status(bank: &mut Bank, creator: &Pubkey, transform_num: u64, status: TransformStatus) {
+    let instructions = InstructionBuilder::set_transform_status(
+        tokenitis::id(),
+        creator,
+        transform_num,
+        SetTransformStatusArgs { status },
+    )
+    .unwrap();
+    bank.process(instructions, &[*creator]).unwrap();
+}
+
+#[test]
+fn execute_respects_status() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let args = pair_args(&mut bank, &creator, 1);
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+    let execute = |bank: &mut Bank, direction: Direction| {
+        execute_transform(
+            bank,
+            transform_num,
+            &caller,
+            execute_args(direction, 1),
+            &accounts,
+        )
+    };
+    execute(&mut bank, Direction::Forward).unwrap();
+    execute(&mut bank, Direction::Forward).unwrap();
+
+    set_status(&mut bank, &creator, transform_num, TransformStatus::Paused);
+    for direction in [Direction::Forward, Direction::Reverse] {
+        assert_eq!(
+            execute(&mut bank, direction),
+            Err(custom_error(TokenitisError::TransformPaused))
+        );
+    }
+
+    // holders can exit but no new outputs are released
+    set_status(
+        &mut bank,
+        &creator,
+        transform_num,
+        TransformStatus::ReverseOnly,
+    );
+    assert_eq!(
+        execute(&mut bank, Direction::Forward),
+        Err(custom_error(TokenitisError::TransformPaused))
+    );
+    execute(&mut bank, Direction::Reverse).unwrap();
+    assert_eq!(bank.token_balance(accounts.1.values().next().unwrap()), 1);
+
+    set_status(&mut bank, &creator, transform_num, TransformStatus::Active);
+    execute(&mut bank, Direction::Forward).unwrap();
+    execute(&mut bank, Direction::Reverse).unwrap();
+}
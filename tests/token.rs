@@ -0,0 +1,156 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use spl_token::state::{Account, Mint};
+use tokenitis::errors::TokenitisError;
+use tokenitis::util::token::{token_2022, validate_mint_extensions, MintExtensions, TransferFee};
+
+// token 2022 mint padded to the account length, followed by the mint account type
+// and the given extensions
+fn mint_data(extensions: &[(u16, Vec<u8>)]) -> Vec<u8> {
+    let mut data = vec![0u8; Account::LEN];
+    Mint {
+        is_initialized: true,
+        ..Mint::default()
+    }
+    .pack_into_slice(&mut data[..Mint::LEN]);
+    data.push(1);
+    for (extension_type, value) in extensions {
+        data.extend_from_slice(&extension_type.to_le_bytes());
+        data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        data.extend_from_slice(value);
+    }
+    data
+}
+
+fn unpack_extensions(mut data: Vec<u8>) -> Result<MintExtensions, ProgramError> {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let mint = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &token_2022::ID,
+        false,
+        0,
+    );
+    validate_mint_extensions(&mint)?;
+    MintExtensions::unpack(&mint)
+}
+
+fn fee_bytes(fee: &TransferFee) -> Vec<u8> {
+    let mut bytes = fee.epoch.to_le_bytes().to_vec();
+    bytes.extend_from_slice(&fee.maximum_fee.to_le_bytes());
+    bytes.extend_from_slice(&fee.bps.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn transfer_fee_config_offsets() {
+    let older = TransferFee {
+        epoch: 3,
+        maximum_fee: 1_000,
+        bps: 50,
+    };
+    let newer = TransferFee {
+        epoch: 7,
+        maximum_fee: 2_000,
+        bps: 100,
+    };
+    // config and withdraw authorities and the withheld amount come before the fees
+    let mut config = vec![0xaa; 32 + 32 + 8];
+    config.extend(fee_bytes(&older));
+    config.extend(fee_bytes(&newer));
+
+    let extensions = unpack_extensions(mint_data(&[(1, config)])).unwrap();
+    assert_eq!(extensions.transfer_fee, Some((older, newer)));
+    assert_eq!(extensions.transfer_fee(6), Some(older));
+    assert_eq!(extensions.transfer_fee(7), Some(newer));
+    assert_eq!(extensions.transfer_hook, None);
+
+    assert_eq!(
+        unpack_extensions(mint_data(&[(1, vec![0; 100])])),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn gross_amount_rounding() {
+    let fee = TransferFee {
+        epoch: 0,
+        maximum_fee: u64::MAX,
+        bps: 100,
+    };
+    assert_eq!(fee.gross_amount(99), Some(100));
+    // 1% of 101 rounds up to 2, so 101 would only deliver 99
+    assert_eq!(fee.gross_amount(100), Some(102));
+    assert_eq!(fee.gross_amount(0), Some(0));
+
+    let no_fee = TransferFee { bps: 0, ..fee };
+    assert_eq!(no_fee.gross_amount(100), Some(100));
+}
+
+#[test]
+fn gross_amount_maximum_fee() {
+    let fee = TransferFee {
+        epoch: 0,
+        maximum_fee: 5,
+        bps: 100,
+    };
+    assert_eq!(fee.gross_amount(10_000), Some(10_005));
+    // below the cap the bps fee applies
+    assert_eq!(fee.gross_amount(300), Some(304));
+    assert_eq!(fee.gross_amount(u64::MAX), None);
+
+    let full_fee = TransferFee { bps: 10_000, ..fee };
+    assert_eq!(full_fee.gross_amount(10), Some(15));
+}
+
+#[test]
+fn rejected_mint_extensions() {
+    assert_eq!(
+        unpack_extensions(mint_data(&[(9, vec![])])),
+        Err(TokenitisError::MintNotTransferable.into())
+    );
+    assert_eq!(
+        unpack_extensions(mint_data(&[(12, vec![1; 32])])),
+        Err(TokenitisError::MintHasPermanentDelegate.into())
+    );
+
+    assert_eq!(
+        unpack_extensions(mint_data(&[(3, vec![1; 32])])),
+        Err(TokenitisError::MintHasCloseAuthority.into())
+    );
+    assert_eq!(
+        unpack_extensions(mint_data(&[(6, vec![2])])),
+        Err(TokenitisError::MintDefaultFrozen.into())
+    );
+
+    // unset authorities and initialized default accounts are allowed
+    let extensions = unpack_extensions(mint_data(&[
+        (3, vec![0; 32]),
+        (6, vec![1]),
+        (12, vec![0; 32]),
+    ]))
+    .unwrap();
+    assert!(!extensions.permanent_delegate && !extensions.non_transferable);
+    assert!(!extensions.close_authority && !extensions.default_frozen);
+}
+
+#[test]
+fn fee_rounding() {
+    let fee = TransferFee {
+        epoch: 0,
+        maximum_fee: 5,
+        bps: 100,
+    };
+    assert_eq!(fee.fee(100), Some(1));
+    assert_eq!(fee.fee(101), Some(2));
+    assert_eq!(fee.fee(10_000), Some(5));
+    assert_eq!(fee.fee(0), Some(0));
+    // gross amounts deliver the net amount after the fee
+    assert_eq!(fee.fee(fee.gross_amount(300).unwrap()), Some(4));
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::*;
+use std::collections::BTreeMap;
+use tokenitis::state::{Fee, Rounding, Token, TokenMode, TokenProgram, Tokenitis};
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+#[test]
+fn reverse_fee_records_amount_received() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    // 1% transfer fee on every transfer of the input
+    let input_mint = bank.create_transfer_fee_mint(100, u64::MAX);
+    let escrow = bank.create_token_account(&input_mint, &creator, 0);
+    let input = Token {
+        token_program: TokenProgram::Token2022,
+        ..token(escrow, 100, TokenMode::Escrow)
+    };
+    let (output_mint, output) = escrow_output(&mut bank, &creator, 1, 10);
+    let mut args = transform_args(
+        BTreeMap::from([(input_mint, input)]),
+        BTreeMap::from([(output_mint, output)]),
+    );
+    args.reverse.fee = Some(Fee {
+        bps: 1_000,
+        rounding: Rounding::Floor,
+    });
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 1_000);
+    for direction in [Direction::Forward, Direction::Reverse] {
+        execute_transform(
+            &mut bank,
+            transform_num,
+            &caller,
+            execute_args(direction, 1),
+            &accounts,
+        )
+        .unwrap();
+    }
+
+    // forward sends 102 for the escrow to receive 100, reverse withholds a fee of 10
+    // and the transfer fee is taken from both the fee and the returned 90
+    let (fee_vault, _) =
+        Tokenitis::find_fee_vault_address(&tokenitis::id(), transform_num, &input_mint);
+    assert_eq!(bank.token_balance(&escrow), 0);
+    assert_eq!(bank.token_balance(&fee_vault), 9);
+    assert_eq!(bank.token_balance(&accounts.0[&input_mint]), 987);
+    assert_eq!(
+        transform_state(&bank, transform_num).fees_collected[&input_mint],
+        9
+    );
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::*;
+use tokenitis::state::{Fee, Rounding, Tokenitis};
+
+#[test]
+fn create_at_prefunded_addresses() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let mut args = pair_args(&mut bank, &creator, 1);
+    args.forward.fee = Some(Fee {
+        bps: 100,
+        rounding: Rounding::Ceil,
+    });
+    let input_mint = *args.inputs.keys().next().unwrap();
+
+    // treasury, fee vault and transform addresses can be derived by anyone before they exist
+    let transform_num = tokenitis_state(&bank).num_transforms + 1;
+    let (treasury, _) = Tokenitis::find_treasury_address(&tokenitis::id(), &input_mint);
+    let (fee_vault, _) =
+        Tokenitis::find_fee_vault_address(&tokenitis::id(), transform_num, &input_mint);
+    let (transform, _) = Tokenitis::find_transform_address(&tokenitis::id(), transform_num);
+    for address in [treasury, fee_vault, transform] {
+        bank.fund(&address, 1);
+    }
+
+    assert_eq!(
+        create_transform(&mut bank, &creator, args).unwrap(),
+        transform_num
+    );
+    assert_eq!(bank.token_balance(&treasury), 0);
+    assert_eq!(bank.token_balance(&fee_vault), 0);
+    assert_eq!(transform_state(&bank, transform_num).creator, creator);
+}
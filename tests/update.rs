@@ -0,0 +1,78 @@
+mod common;
+
+use common::*;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use tokenitis::sdk::InstructionBuilder;
+use tokenitis::state::{TokenitisConfig, TransformMetadata};
+use tokenitis::tokenitis_instruction::update_transform::UpdateTransformArgs;
+
+fn update_transform(
+    bank: &mut Bank,
+    creator: &Pubkey,
+    transform_num: u64,
+    metadata: TransformMetadata,
+) -> ProgramResult {
+    let instructions = InstructionBuilder::update_transform(
+        tokenitis::id(),
+        creator,
+        transform_num,
+        UpdateTransformArgs { metadata },
+    )
+    .unwrap();
+    bank.process(instructions, &[*creator])
+}
+
+#[test]
+fn creator_updates_metadata() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+    let args = pair_args(&mut bank, &creator, 1);
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+
+    // the account is resized to fit longer metadata
+    let metadata = TransformMetadata {
+        name: "a longer name than the one the transform was created with".to_string(),
+        image: "https://example.com/image.png".to_string(),
+    };
+    update_transform(&mut bank, &creator, transform_num, metadata.clone()).unwrap();
+    let state = transform_state(&bank, transform_num);
+    assert_eq!(state.metadata, metadata);
+    assert_eq!(state.creator, creator);
+
+    let other = bank.new_wallet();
+    assert_eq!(
+        update_transform(
+            &mut bank,
+            &other,
+            transform_num,
+            TransformMetadata {
+                name: "taken over".to_string(),
+                image: "".to_string(),
+            },
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(transform_state(&bank, transform_num).metadata, metadata);
+}
+
+#[test]
+fn metadata_is_bounded_by_config() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+    let args = pair_args(&mut bank, &creator, 1);
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+
+    let max_string_size = TokenitisConfig::default().max_string_size as usize;
+    let metadata = TransformMetadata {
+        name: "a".repeat(max_string_size + 1),
+        image: "".to_string(),
+    };
+    assert_eq!(
+        update_transform(&mut bank, &creator, transform_num, metadata),
+        Err(ProgramError::InvalidInstructionData)
+    );
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::*;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use tokenitis::errors::TokenitisError;
+use tokenitis::tokenitis_instruction::create_transform::CreateTransformArgs;
+use tokenitis::tokenitis_instruction::execute_transform::Direction;
+
+fn windowed_args(bank: &mut Bank, creator: &Pubkey, start: i64, end: i64) -> CreateTransformArgs {
+    let mut args = pair_args(bank, creator, 1);
+    args.forward.start_unix_timestamp = Some(start);
+    args.forward.end_unix_timestamp = Some(end);
+    args
+}
+
+#[test]
+fn execute_only_within_window() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    let (start, end) = (START_UNIX_TIMESTAMP + 100, START_UNIX_TIMESTAMP + 200);
+    let args = windowed_args(&mut bank, &creator, start, end);
+    let transform_num = create_transform(&mut bank, &creator, args).unwrap();
+    let caller = bank.new_wallet();
+    let accounts = caller_accounts(&mut bank, transform_num, &caller, 10);
+
+    let execute = |bank: &mut Bank| {
+        execute_transform(
+            bank,
+            transform_num,
+            &caller,
+            execute_args(Direction::Forward, 1),
+            &accounts,
+        )
+    };
+    assert_eq!(
+        execute(&mut bank),
+        Err(custom_error(TokenitisError::TransformNotStarted))
+    );
+    bank.warp_to_timestamp(start);
+    execute(&mut bank).unwrap();
+    bank.warp_to_timestamp(end);
+    assert_eq!(
+        execute(&mut bank),
+        Err(custom_error(TokenitisError::TransformEnded))
+    );
+}
+
+#[test]
+fn create_rejects_empty_window() {
+    let mut bank = Bank::new();
+    let creator = bank.new_wallet();
+    initialize_tokenitis(&mut bank, &creator).unwrap();
+
+    for (start, end) in [(200, 200), (200, 100)] {
+        let args = windowed_args(&mut bank, &creator, start, end);
+        assert_eq!(
+            create_transform(&mut bank, &creator, args),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}